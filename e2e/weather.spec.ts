@@ -1,9 +1,8 @@
 import { test, expect } from "@playwright/test";
 
-// These hit the live Open-Meteo API through the server, so they assert on
-// structure and labels rather than on any particular temperature. The server
-// caches each location for ten minutes, so only the first test pays for the
-// upstream call.
+// The server replays recorded Open-Meteo responses (see fixtures/README.md),
+// but these still assert on structure and labels rather than on any particular
+// temperature, so they hold against a live server too.
 test.describe("Weather", () => {
   test("loads successfully", async ({ page }) => {
     const response = await page.goto("/weather");
@@ -209,6 +208,21 @@ test.describe("Weather", () => {
     await expect(page).toHaveURL(/[?&]q=Portland/);
   });
 
+  test("suggests places while typing, and opens the one picked", async ({
+    page,
+  }) => {
+    await page.goto("/weather");
+    await page.locator("#q").pressSequentially("Oak");
+    const option = page.locator(
+      '#q-suggestions option[value="Oakland, California, United States"]',
+    );
+    await expect(option).toBeAttached();
+
+    await page.locator("#q").fill("Oakland, California, United States");
+    await page.getByRole("button", { name: "Look up" }).click();
+    await expect(page).toHaveURL(/[?&]lat=37\.8044&lon=-122\.2708&name=Oakland/);
+  });
+
   test("opens an arbitrary location by coordinates", async ({ page }) => {
     await page.goto("/weather?lat=45.5234&lon=-122.6762&name=Portland");
     await expect(page.getByRole("heading", { name: "Portland" })).toBeVisible();
@@ -35,6 +35,9 @@ export default defineConfig({
   ],
   webServer: {
     command: "cargo run",
+    // Recorded upstream responses, so the weather pages are the same on every
+    // run and need no network. See fixtures/README.md.
+    env: { UPSTREAM: "replay" },
     url: "http://localhost:8080",
     reuseExistingServer: !process.env.CI,
     timeout: 120000,
@@ -42,6 +42,8 @@ cargo build --release --target x86_64-unknown-linux-gnu
 cp ./target/x86_64-unknown-linux-gnu/release/website "${tmp_dir}/website"
 cp -r ./scripts "${tmp_dir}/scripts"
 cp -r ./static "${tmp_dir}/static"
+cp ./locations.json "${tmp_dir}/locations.json"
+cp ./scales.json "${tmp_dir}/scales.json"
 cp ./website-blue.service "${tmp_dir}/website-blue.service"
 cp ./website-green.service "${tmp_dir}/website-green.service"
 cp ./caddy.service "${tmp_dir}/caddy.service"
@@ -61,6 +63,10 @@ rsync --partial --progress --archive --verbose --delete \
     --exclude='website-blue' \
     --exclude='website-green' \
     --exclude='.active_port' \
+    --exclude='feedback.jsonl' \
+    --exclude='forecasts.jsonl' \
+    --exclude='forecast-cache.json' \
+    --exclude='geonames' \
     -e "ssh -o StrictHostKeyChecking=no -o IdentitiesOnly=yes -i \"${tmp_key_file}\"" \
     "$tmp_dir/." \
     "${instance_user}@${instance_ip}:/home/${instance_user}/website"
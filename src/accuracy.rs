@@ -0,0 +1,322 @@
+//! How far the headline can be trusted: forecasts kept, then marked.
+//!
+//! Once a day, for each pinned location, the server writes down what the
+//! forecast said the day would be like before it started, and the next day
+//! what the model analysis says it was. Both go to `forecasts.jsonl` (or
+//! wherever `ACCURACY_FILE` points) as one JSON line each, and the file is
+//! never rewritten.
+//!
+//! A snapshot keeps the felt temperatures behind the headline's three numbers
+//! — the typical hour, the coldest shade and the warmest hour — rather than
+//! their scores. Scores depend on the profile they are read on, and a felt
+//! temperature can be placed on whichever profile is asked about, including
+//! ones that did not exist when it was written.
+//!
+//! # What counts as right
+//!
+//! The analysis is not an observation. It is the same model's best estimate
+//! of what happened, given everything measured since, and shares some of the
+//! forecast's blind spots: fog that the model cannot see is missing from both.
+//! What it does catch is the forecast changing its mind, which is most of what
+//! goes wrong with a morning's advice.
+//!
+//! A rain call is right when the forecast and the analysis agree on a dry day,
+//! or when the hours the forecast thought likely overlap the hours it rained.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::{append_json_line, read_json_lines};
+use crate::scale::{Profile, Score};
+use crate::units::Temperature;
+
+/// Which side of the comparison a snapshot is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Taken before the day's daylight window opened.
+    Forecast,
+    /// Taken the day after, from `past_days`.
+    Analysis,
+}
+
+/// One line of the accuracy file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub kind: Kind,
+    /// Slug of the pinned location.
+    pub location: String,
+    /// The local day described, `YYYY-MM-DD`.
+    pub date: String,
+    /// The location's local time when the snapshot was taken,
+    /// `YYYY-MM-DDTHH:MM`.
+    pub taken: String,
+    pub day: DayRecord,
+}
+
+/// A day as the headline sums it up, with the units spelt out in the names.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DayRecord {
+    /// Felt temperature of the hour nearest the day's typical score.
+    pub typical_c: f64,
+    /// The coldest felt temperature out of the sun.
+    pub low_c: f64,
+    /// The warmest exposure-weighted felt temperature.
+    pub high_c: f64,
+    /// First and last local hours of rain: for a forecast, the hours it was
+    /// likely, and for the analysis, the hours it fell. `None` for a dry day.
+    pub rain: Option<[u32; 2]>,
+}
+
+/// Adds one snapshot to the end of the file, creating it if need be.
+pub fn append(path: &Path, snapshot: &Snapshot) -> std::io::Result<()> {
+    append_json_line(path, snapshot)
+}
+
+/// Every snapshot on file, and how many lines were not snapshots.
+pub fn read(path: &Path) -> std::io::Result<(Vec<Snapshot>, usize)> {
+    read_json_lines(path)
+}
+
+/// The snapshots already on file, so a restart does not take the same one
+/// twice.
+pub fn taken(snapshots: &[Snapshot]) -> HashSet<(Kind, &str, &str)> {
+    snapshots
+        .iter()
+        .map(|snapshot| {
+            (
+                snapshot.kind,
+                snapshot.location.as_str(),
+                snapshot.date.as_str(),
+            )
+        })
+        .collect()
+}
+
+/// One day with both sides on file.
+#[derive(Debug)]
+pub struct Pair<'a> {
+    pub date: &'a str,
+    pub forecast: &'a DayRecord,
+    pub analysis: &'a DayRecord,
+}
+
+impl Pair<'_> {
+    /// Whether the forecast's rain call held up.
+    pub fn rain_right(&self) -> bool {
+        match (self.forecast.rain, self.analysis.rain) {
+            (None, None) => true,
+            (Some([first, last]), Some([start, end])) => first <= end && start <= last,
+            _ => false,
+        }
+    }
+}
+
+/// Every day at `location` with both a forecast and an analysis, oldest first.
+/// If the file somehow has two of either for a day, the first is used: the
+/// earliest forecast is the one that was least informed by the day itself.
+pub fn pairs<'a>(snapshots: &'a [Snapshot], location: &str) -> Vec<Pair<'a>> {
+    let first = |kind: Kind, date: &str| {
+        snapshots.iter().find(|snapshot| {
+            snapshot.kind == kind && snapshot.location == location && snapshot.date == date
+        })
+    };
+    let mut dates: Vec<&str> = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.location == location)
+        .map(|snapshot| snapshot.date.as_str())
+        .collect();
+    dates.sort_unstable();
+    dates.dedup();
+    dates
+        .into_iter()
+        .filter_map(|date| {
+            Some(Pair {
+                date,
+                forecast: &first(Kind::Forecast, date)?.day,
+                analysis: &first(Kind::Analysis, date)?.day,
+            })
+        })
+        .collect()
+}
+
+/// How far off one of the headline's numbers has been, in scale points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Miss {
+    /// Mean of forecast minus analysis: positive when the forecast ran warm.
+    pub bias: f64,
+    /// Mean of the size of the miss, whichever way.
+    pub mean_absolute: f64,
+}
+
+/// The record at one location, on one profile.
+#[derive(Debug)]
+pub struct Tally {
+    pub days: usize,
+    pub typical: Miss,
+    pub low: Miss,
+    pub high: Miss,
+    /// Days the rain call held up.
+    pub rain_right: usize,
+}
+
+impl Tally {
+    /// `None` until there is at least one day to mark.
+    pub fn of(pairs: &[Pair], profile: &'static Profile) -> Option<Tally> {
+        if pairs.is_empty() {
+            return None;
+        }
+        let miss = |side: fn(&DayRecord) -> f64| {
+            let errors: Vec<f64> = pairs
+                .iter()
+                .map(|pair| {
+                    score_of(profile, side(pair.forecast)).value()
+                        - score_of(profile, side(pair.analysis)).value()
+                })
+                .collect();
+            let count = errors.len() as f64;
+            Miss {
+                bias: errors.iter().sum::<f64>() / count,
+                mean_absolute: errors.iter().map(|error| error.abs()).sum::<f64>() / count,
+            }
+        };
+        Some(Tally {
+            days: pairs.len(),
+            typical: miss(|day| day.typical_c),
+            low: miss(|day| day.low_c),
+            high: miss(|day| day.high_c),
+            rain_right: pairs.iter().filter(|pair| pair.rain_right()).count(),
+        })
+    }
+}
+
+/// A stored felt temperature, placed on a profile.
+pub fn score_of(profile: &'static Profile, celsius: f64) -> Score {
+    profile.score(Temperature::from_celsius(celsius))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::parse_json_lines;
+    use crate::scale::profiles;
+
+    fn standard() -> &'static Profile {
+        profiles().find("standard").unwrap()
+    }
+
+    fn day(typical_c: f64, rain: Option<[u32; 2]>) -> DayRecord {
+        DayRecord {
+            typical_c,
+            low_c: typical_c - 4.0,
+            high_c: typical_c + 3.0,
+            rain,
+        }
+    }
+
+    fn snapshot(kind: Kind, location: &str, date: &str, day: DayRecord) -> Snapshot {
+        Snapshot {
+            kind,
+            location: location.to_owned(),
+            date: date.to_owned(),
+            taken: format!("{date}T05:00"),
+            day,
+        }
+    }
+
+    #[test]
+    fn snapshots_survive_the_round_trip() {
+        let original = snapshot(
+            Kind::Analysis,
+            "fidi",
+            "2026-08-02",
+            day(18.0, Some([14, 17])),
+        );
+        let line = serde_json::to_string(&original).unwrap();
+        assert!(line.contains(r#""kind":"analysis""#), "{line}");
+        let (snapshots, unreadable) =
+            parse_json_lines::<Snapshot>(&format!("{line}\n{}", &line[..10]));
+        assert_eq!(unreadable, 1);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].day.rain, Some([14, 17]));
+    }
+
+    #[test]
+    fn appends_rather_than_overwrites() {
+        let path = std::env::temp_dir().join(format!("forecasts-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let first = snapshot(Kind::Forecast, "fidi", "2026-08-02", day(18.0, None));
+        let second = snapshot(Kind::Analysis, "fidi", "2026-08-02", day(17.0, None));
+        append(&path, &first).unwrap();
+        append(&path, &second).unwrap();
+        let (snapshots, _) = read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(snapshots.len(), 2);
+        assert!(taken(&snapshots).contains(&(Kind::Analysis, "fidi", "2026-08-02")));
+        assert!(read(&path).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn only_days_with_both_sides_are_marked() {
+        let snapshots = [
+            snapshot(Kind::Forecast, "fidi", "2026-08-01", day(18.0, None)),
+            snapshot(Kind::Forecast, "fidi", "2026-08-02", day(18.0, None)),
+            snapshot(Kind::Analysis, "fidi", "2026-08-02", day(17.0, None)),
+            snapshot(Kind::Analysis, "sunset", "2026-08-01", day(12.0, None)),
+        ];
+        let marked = pairs(&snapshots, "fidi");
+        assert_eq!(marked.len(), 1);
+        assert_eq!(marked[0].date, "2026-08-02");
+        assert!(pairs(&snapshots, "sunset").is_empty());
+    }
+
+    #[test]
+    fn the_earliest_forecast_is_the_one_marked() {
+        let snapshots = [
+            snapshot(Kind::Forecast, "fidi", "2026-08-02", day(20.0, None)),
+            snapshot(Kind::Forecast, "fidi", "2026-08-02", day(17.0, None)),
+            snapshot(Kind::Analysis, "fidi", "2026-08-02", day(17.0, None)),
+        ];
+        assert_eq!(pairs(&snapshots, "fidi")[0].forecast.typical_c, 20.0);
+    }
+
+    #[test]
+    fn bias_keeps_the_sign_and_mean_error_does_not() {
+        let warm = score_of(standard(), 20.0).value() - score_of(standard(), 17.0).value();
+        let snapshots = [
+            snapshot(Kind::Forecast, "fidi", "2026-08-01", day(20.0, None)),
+            snapshot(Kind::Analysis, "fidi", "2026-08-01", day(17.0, None)),
+            snapshot(Kind::Forecast, "fidi", "2026-08-02", day(17.0, None)),
+            snapshot(Kind::Analysis, "fidi", "2026-08-02", day(20.0, None)),
+        ];
+        let tally = Tally::of(&pairs(&snapshots, "fidi"), standard()).unwrap();
+        assert_eq!(tally.days, 2);
+        assert!(tally.typical.bias.abs() < 1e-9, "{:?}", tally.typical);
+        assert!((tally.typical.mean_absolute - warm).abs() < 1e-9);
+        assert!(warm > 0.0);
+    }
+
+    #[test]
+    fn nothing_to_mark_is_not_a_perfect_record() {
+        assert!(Tally::of(&[], standard()).is_none());
+    }
+
+    #[test]
+    fn rain_calls_are_right_when_they_overlap() {
+        let call = |forecast, analysis| {
+            Pair {
+                date: "2026-08-02",
+                forecast: &day(18.0, forecast),
+                analysis: &day(18.0, analysis),
+            }
+            .rain_right()
+        };
+        assert!(call(None, None));
+        assert!(call(Some([14, 17]), Some([16, 20])));
+        assert!(!call(Some([14, 17]), Some([18, 20])));
+        assert!(!call(Some([14, 17]), None));
+        assert!(!call(None, Some([9, 9])));
+    }
+}
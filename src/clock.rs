@@ -0,0 +1,145 @@
+//! Naive local timestamps, without a date library.
+//!
+//! Every time this site handles is a `YYYY-MM-DDTHH:MM` string already in the
+//! place's own clock, which is what keeps most of it to string slicing. The
+//! few things that need real calendar arithmetic — the sun's position, a
+//! calendar feed in UTC — convert to a count of civil days and back with
+//! Hinnant's algorithms, which are a dozen lines each and exact for the
+//! proleptic Gregorian calendar.
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Civil day count for `YYYY-MM-DD`, or the date part of a longer timestamp.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let year = date.get(0..4)?.parse().ok()?;
+    let month = date.get(5..7)?.parse().ok()?;
+    let day = date.get(8..10)?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Civil day count and minutes past local midnight.
+pub fn parse_local(local: &str) -> Option<(i64, f64)> {
+    let days = parse_date(local)?;
+    let hour: u32 = local.get(11..13)?.parse().ok()?;
+    let minute: u32 = local.get(14..16)?.parse().ok()?;
+    Some((days, f64::from(hour * 60 + minute)))
+}
+
+/// `YYYY-MM-DDTHH:MM` for a minute offset from the start of local day `days`,
+/// which may run into the day before or after.
+pub fn format_local(days: i64, minutes: f64) -> String {
+    let total = minutes.round() as i64;
+    let (year, month, day) = civil_from_days(days + total.div_euclid(1440));
+    let minute_of_day = total.rem_euclid(1440);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}",
+        minute_of_day / 60,
+        minute_of_day % 60
+    )
+}
+
+/// Day of the week for a civil day count, `0` for Sunday.
+pub fn weekday(days: i64) -> i64 {
+    // 1970-01-01 was a Thursday.
+    (days + 4).rem_euclid(7)
+}
+
+/// `YYYYMMDD`, iCalendar's basic date form, for a civil day count.
+pub fn basic_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}{month:02}{day:02}")
+}
+
+/// The UTC instant of a local timestamp, in iCalendar's basic form
+/// (`YYYYMMDDTHHMMSSZ`).
+pub fn basic_utc(local: &str, utc_offset_seconds: i32) -> Option<String> {
+    let (days, minutes) = parse_local(local)?;
+    let utc = format_local(days, minutes - f64::from(utc_offset_seconds) / 60.0);
+    Some(format!(
+        "{}T{}{}00Z",
+        &utc[..10].replace('-', ""),
+        &utc[11..13],
+        &utc[14..16]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in [-800_000, -1, 0, 59, 60, 11_016, 20_667, 800_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn knows_the_day_of_the_week() {
+        assert_eq!(weekday(0), 4);
+        // 2026-10-18 is a Sunday.
+        assert_eq!(weekday(parse_date("2026-10-18").unwrap()), 0);
+    }
+
+    #[test]
+    fn times_past_midnight_roll_into_the_next_day() {
+        assert_eq!(format_local(0, 1440.0 + 75.0), "1970-01-02T01:15");
+        assert_eq!(format_local(0, -30.0), "1969-12-31T23:30");
+    }
+
+    #[test]
+    fn reads_dates_and_times_and_nothing_else() {
+        assert_eq!(parse_local("2026-08-02T14:30"), Some((20_667, 870.0)));
+        assert_eq!(parse_date("2026-08-02T14:30"), Some(20_667));
+        assert_eq!(parse_date("2026-13-01"), None);
+        assert_eq!(parse_local("2026-08-02"), None);
+    }
+
+    #[test]
+    fn converts_local_time_to_utc_across_midnight() {
+        // Pacific daylight time is seven hours behind.
+        assert_eq!(
+            basic_utc("2026-08-02T19:00", -7 * 3600).as_deref(),
+            Some("20260803T020000Z")
+        );
+        assert_eq!(
+            basic_utc("2026-01-01T00:30", 3600).as_deref(),
+            Some("20251231T233000Z")
+        );
+        assert_eq!(basic_date(20_667), "20260802");
+    }
+}
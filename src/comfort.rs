@@ -41,8 +41,10 @@
 //!
 //! * `f_p`, the projected area factor of a standing body, is Fanger's (1970)
 //!   azimuth-averaged form `0.308 cos(b(1 - b²/48000))` for solar elevation
-//!   `b` in degrees. It is why noon sun adds less than the raw beam suggests:
-//!   an upright person presents very little area to an overhead sun.
+//!   `b` in degrees, computed from the clock and the coordinates by
+//!   [`crate::solar`]. It is why noon sun adds less than the raw beam
+//!   suggests: an upright person presents very little area to an overhead
+//!   sun.
 //! * `f_eff = 0.725` is the fraction of body surface that exchanges radiation
 //!   with the environment at all, and `F_sky = F_grd = 0.5` the sky/ground view
 //!   factors of an upright cylinder.
@@ -72,6 +74,35 @@
 //! cloud figure instead costs 1.7 points on the scale — the difference between
 //! keeping a jacket on and leaving it at home.
 //!
+//! # Activity
+//!
+//! The scale is calibrated for a walk at 2.4 met (see [`crate::scale`]), so
+//! that is the default [`Activity`]. Any other changes two things. Its own
+//! motion adds air speed, combined with the wind at right angles
+//! (`√(v² + s²)`) and fed to the model's wind input, where it both cools
+//! directly and dilutes the radiation term. And its metabolic rate
+//! shifts the result by `13 ln(M / 2.4)` °C: the logarithmic form of the
+//! familiar finding that each doubling of metabolic heat is worth about nine
+//! degrees of air, fitted so that 2.9 and 2.0 met land half a point either side
+//! of the walk, as the scale's own sensitivity note says they should. Both are
+//! relative to the walk, so walking leaves every number exactly as it was.
+//!
+//! # A second opinion: UTCI
+//!
+//! Steadman is what the scale was calibrated on, and [`felt`] uses it. The
+//! Universal Thermal Climate Index is there as a check on it: a multi-node
+//! physiological model of the same walking person (4 km/h, about 2.3 met),
+//! reduced by Bröde et al. to a sixth-order polynomial in air temperature,
+//! `Tmrt − Ta`, 10 m wind and vapour pressure. It wants mean radiant
+//! temperature rather than `Q`, which falls out of the same budget: `Tmrt` is
+//! the temperature of a black enclosure that would give the body the same net
+//! radiation, `εσ·Tmrt⁴ = εσ·Ta⁴ + Q`. Both sit behind [`ThermalModel`], so
+//! [`felt_with`] runs either over identical inputs and any disagreement is the
+//! models' and not the plumbing's.
+//!
+//! Bröde, P. et al. (2012), *Deriving the operational procedure for the
+//! Universal Thermal Climate Index (UTCI)*, Int. J. Biometeorol. 56, 481-494.
+//!
 //! Inputs and outputs are typed ([`Temperature`], [`Speed`]) so a caller cannot
 //! feed Fahrenheit or mph into formulas calibrated for neither. Inside a single
 //! formula the values are plain `f64`: the physics mixes units by design, and
@@ -99,6 +130,100 @@ const GROUND_VIEW_FACTOR: f64 = 0.5;
 /// Reflectance of ordinary ground cover. Concrete and asphalt bracket this.
 const GROUND_ALBEDO: f64 = 0.2;
 
+/// Metabolic rate the scale's anchors were solved at (met).
+const REFERENCE_MET: f64 = 2.4;
+
+/// °C of felt temperature per natural-log unit of metabolic rate.
+const METABOLIC_DEGREES: f64 = 13.0;
+
+/// What the person in the weather is doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Activity {
+    /// Waiting for a bus.
+    Standing,
+    /// An unhurried walk, window shopping pace.
+    Strolling,
+    /// The pace the scale is calibrated for.
+    #[default]
+    Walking,
+    /// Late for something.
+    BriskWalk,
+    Running,
+    Cycling,
+}
+
+impl Activity {
+    /// Every profile, least exertion first, for pickers.
+    pub const ALL: [Activity; 6] = [
+        Activity::Standing,
+        Activity::Strolling,
+        Activity::Walking,
+        Activity::BriskWalk,
+        Activity::Running,
+        Activity::Cycling,
+    ];
+
+    /// The `?activity=` value.
+    pub fn slug(self) -> &'static str {
+        match self {
+            Activity::Standing => "standing",
+            Activity::Strolling => "strolling",
+            Activity::Walking => "walking",
+            Activity::BriskWalk => "brisk-walk",
+            Activity::Running => "running",
+            Activity::Cycling => "cycling",
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<Activity> {
+        Activity::ALL
+            .into_iter()
+            .find(|activity| activity.slug() == slug)
+    }
+
+    /// Lower case, to follow "how it feels".
+    pub fn name(self) -> &'static str {
+        match self {
+            Activity::Standing => "standing",
+            Activity::Strolling => "strolling",
+            Activity::Walking => "walking",
+            Activity::BriskWalk => "walking briskly",
+            Activity::Running => "running",
+            Activity::Cycling => "cycling",
+        }
+    }
+
+    /// Metabolic rate (met), from the Compendium of Physical Activities.
+    pub fn met(self) -> f64 {
+        match self {
+            Activity::Standing => 1.3,
+            Activity::Strolling => 2.0,
+            Activity::Walking => REFERENCE_MET,
+            Activity::BriskWalk => 2.9,
+            Activity::Running => 8.0,
+            Activity::Cycling => 6.0,
+        }
+    }
+
+    /// Air speed from the body's own motion, beyond what the walk the scale
+    /// was calibrated on already includes. A stroll and a stand-still are not
+    /// negative wind; they just lose the little the walk had.
+    pub fn relative_wind(self) -> Speed {
+        Speed::from_meters_per_second(match self {
+            Activity::Standing | Activity::Strolling | Activity::Walking => 0.0,
+            Activity::BriskWalk => 0.5,
+            Activity::Running => 2.5,
+            Activity::Cycling => 5.0,
+        })
+    }
+
+    /// The shift in felt temperature from working harder or less hard than
+    /// the reference walk (°C).
+    fn metabolic_offset_c(self) -> f64 {
+        METABOLIC_DEGREES * (self.met() / REFERENCE_MET).ln()
+    }
+}
+
 /// One hour of weather, in the units this model works in.
 #[derive(Clone, Copy, Debug)]
 pub struct Conditions {
@@ -110,8 +235,8 @@ pub struct Conditions {
     pub wind: Speed,
     /// Direct normal irradiance: beam strength facing the sun (W/m²).
     pub direct_normal: f64,
-    /// Direct irradiance on a horizontal plane (W/m²). Only used to recover the
-    /// solar elevation, via `direct_horizontal = DNI · sin(elevation)`.
+    /// Direct irradiance on a horizontal plane (W/m²). Lands on the ground
+    /// around a sunlit body and comes back off it.
     pub direct_horizontal: f64,
     /// Diffuse (sky) irradiance on a horizontal plane (W/m²).
     pub diffuse: f64,
@@ -120,6 +245,8 @@ pub struct Conditions {
     pub cloud_cover: f64,
     /// Share of the hour the sun is actually on you, 0 to 1.
     pub sunlit_fraction: f64,
+    /// Degrees above the horizon, from [`crate::solar`].
+    pub solar_elevation: f64,
 }
 
 /// How one hour feels: the two bounds, and the expectation between them.
@@ -151,20 +278,6 @@ fn projected_area_factor(solar_elevation_deg: f64) -> f64 {
             .cos()
 }
 
-/// Recovers solar elevation from the two direct-radiation components.
-///
-/// Saves asking for a separate solar position: the API already reports the beam
-/// both normal to the sun and projected onto the horizontal.
-fn solar_elevation_deg(direct_normal: f64, direct_horizontal: f64) -> f64 {
-    if direct_normal <= 0.0 {
-        return 0.0;
-    }
-    (direct_horizontal / direct_normal)
-        .clamp(0.0, 1.0)
-        .asin()
-        .to_degrees()
-}
-
 /// Net longwave exchange (W/m²), relative to a body in an environment at air
 /// temperature. Negative under a clear sky, ~0 under overcast.
 fn net_longwave(air_c: f64, vapour_hpa: f64, cloud_cover: f64) -> f64 {
@@ -199,15 +312,367 @@ fn apparent_temperature_c(air_c: f64, vapour_hpa: f64, wind_ms: f64, net_radiati
     air_c + 0.348 * vapour_hpa - 0.70 * wind_ms + 0.70 * net_radiation / (wind_ms + 10.0) - 4.25
 }
 
-/// Felt temperature in direct sun and in shade for one hour.
-pub fn felt(conditions: &Conditions) -> Felt {
+/// What a thermal model is handed for one hour: the air, and what a body
+/// standing in it absorbs.
+#[derive(Clone, Copy, Debug)]
+pub struct Environment {
+    /// Air temperature (°C).
+    pub air_c: f64,
+    /// Water vapour pressure (hPa).
+    pub vapour_hpa: f64,
+    /// 10 m wind combined with the body's own motion (m/s).
+    pub wind_ms: f64,
+    /// Net radiation absorbed per unit body surface, relative to surroundings
+    /// at air temperature (W/m²).
+    pub radiation: f64,
+}
+
+impl Environment {
+    /// Mean radiant temperature (°C): the black enclosure that would give the
+    /// body the same net radiation as the real sky and ground.
+    pub fn mean_radiant_c(&self) -> f64 {
+        let air_k = self.air_c + 273.15;
+        let fourth = air_k.powi(4) + self.radiation / (LONGWAVE_EMISSIVITY * STEFAN_BOLTZMANN);
+        fourth.max(0.0).powf(0.25) - 273.15
+    }
+}
+
+/// A way of turning an hour of weather into one felt temperature, for a person
+/// at the reference walk. Activity is applied on top, the same way for each.
+pub trait ThermalModel {
+    /// What the page calls it.
+    fn name(&self) -> &'static str;
+
+    /// Felt temperature (°C).
+    fn felt_c(&self, environment: &Environment) -> f64;
+}
+
+/// Steadman's apparent temperature, radiation form. What the scale is
+/// calibrated on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Steadman;
+
+impl ThermalModel for Steadman {
+    fn name(&self) -> &'static str {
+        "Steadman's apparent temperature"
+    }
+
+    fn felt_c(&self, environment: &Environment) -> f64 {
+        apparent_temperature_c(
+            environment.air_c,
+            environment.vapour_hpa,
+            environment.wind_ms,
+            environment.radiation,
+        )
+    }
+}
+
+/// The Universal Thermal Climate Index, by its operational polynomial.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Utci;
+
+impl ThermalModel for Utci {
+    fn name(&self) -> &'static str {
+        "the Universal Thermal Climate Index"
+    }
+
+    fn felt_c(&self, environment: &Environment) -> f64 {
+        // The polynomial is only fitted inside these bounds and turns wild
+        // outside them; it also has no notion of air stiller than 0.5 m/s.
+        let air = environment.air_c.clamp(-50.0, 50.0);
+        let radiant = (environment.mean_radiant_c() - environment.air_c).clamp(-30.0, 70.0);
+        let wind = environment.wind_ms.clamp(0.5, 17.0);
+        let vapour_kpa = (environment.vapour_hpa / 10.0).clamp(0.0, 5.0);
+        utci_polynomial_c(air, radiant, wind, vapour_kpa)
+    }
+}
+
+/// Bröde et al.'s coefficients, in the order of the reference implementation:
+/// vapour pressure outermost, then `Tmrt − Ta`, then wind, then air
+/// temperature, each power running up to whatever the sixth degree leaves.
+const UTCI_COEFFICIENTS: [f64; 210] = [
+    // Pa⁰ ΔTmrt⁰
+    6.07562052e-01,
+    -2.27712343e-02,
+    8.06470249e-04,
+    -1.54271372e-04,
+    -3.24651735e-06,
+    7.32602852e-08,
+    1.35959073e-09,
+    -2.25836520e+00,
+    8.80326035e-02,
+    2.16844454e-03,
+    -1.53347087e-05,
+    -5.72983704e-07,
+    -2.55090145e-09,
+    -7.51269505e-01,
+    -4.08350271e-03,
+    -5.21670675e-05,
+    1.94544667e-06,
+    1.14099531e-08,
+    1.58137256e-01,
+    -6.57263143e-05,
+    2.22697524e-07,
+    -4.16117031e-08,
+    -1.27762753e-02,
+    9.66891875e-06,
+    2.52785852e-09,
+    4.56306672e-04,
+    -1.74202546e-07,
+    -5.91491269e-06,
+    // Pa⁰ ΔTmrt¹
+    3.98374029e-01,
+    1.83945314e-04,
+    -1.73754510e-04,
+    -7.60781159e-07,
+    3.77830287e-08,
+    5.43079673e-10,
+    -2.00518269e-02,
+    8.92859837e-04,
+    3.45433048e-06,
+    -3.77925774e-07,
+    -1.69699377e-09,
+    1.69992415e-04,
+    -4.99204314e-05,
+    2.47417178e-07,
+    1.07596466e-08,
+    8.49242932e-05,
+    1.35191328e-06,
+    -6.21531254e-09,
+    -4.99410301e-06,
+    -1.89489258e-08,
+    8.15300114e-08,
+    // Pa⁰ ΔTmrt²
+    7.55043090e-04,
+    -5.65095215e-05,
+    -4.52166564e-07,
+    2.46688878e-08,
+    2.42674348e-10,
+    1.54547250e-04,
+    5.24110970e-06,
+    -8.75874982e-08,
+    -1.50743064e-09,
+    -1.56236307e-05,
+    -1.33895614e-07,
+    2.49709824e-09,
+    6.51711721e-07,
+    1.94960053e-09,
+    -1.00361113e-08,
+    // Pa⁰ ΔTmrt³
+    -1.21206673e-05,
+    -2.18203660e-07,
+    7.51269482e-09,
+    9.79063848e-11,
+    1.25006734e-06,
+    -1.81584736e-09,
+    -3.52197671e-10,
+    -3.36514630e-08,
+    1.35908359e-10,
+    4.17032620e-10,
+    // Pa⁰ ΔTmrt⁴
+    -1.30369025e-09,
+    4.13908461e-10,
+    9.22652254e-12,
+    -5.08220384e-09,
+    -2.24730961e-11,
+    1.17139133e-10,
+    // Pa⁰ ΔTmrt⁵
+    6.62154879e-10,
+    4.03863260e-13,
+    1.95087203e-12,
+    // Pa⁰ ΔTmrt⁶
+    -4.73602469e-12,
+    // Pa¹ ΔTmrt⁰
+    5.12733497e+00,
+    -3.12788561e-01,
+    -1.96701861e-02,
+    9.99690870e-04,
+    9.51738512e-06,
+    -4.66426341e-07,
+    5.48050612e-01,
+    -3.30552823e-03,
+    -1.64119440e-03,
+    -5.16670694e-06,
+    9.52692432e-07,
+    -4.29223622e-02,
+    5.00845667e-03,
+    1.00601257e-06,
+    -1.81748644e-06,
+    -1.25813502e-03,
+    -1.79330391e-04,
+    2.34994441e-06,
+    1.29735808e-04,
+    1.29064870e-06,
+    -2.28558686e-06,
+    // Pa¹ ΔTmrt¹
+    -3.69476348e-02,
+    1.62325322e-03,
+    -3.14279680e-05,
+    2.59835559e-06,
+    -4.77136523e-08,
+    8.64203390e-03,
+    -6.87405181e-04,
+    -9.13863872e-06,
+    5.15916806e-07,
+    -3.59217476e-05,
+    3.28696511e-05,
+    -7.10542454e-07,
+    -1.24382300e-05,
+    -7.38584400e-09,
+    2.20609296e-07,
+    // Pa¹ ΔTmrt²
+    -7.32469180e-04,
+    -1.87381964e-05,
+    4.80925239e-06,
+    -8.75492040e-08,
+    2.77862930e-05,
+    -5.06004592e-06,
+    1.14325367e-07,
+    2.53016723e-06,
+    -1.72857035e-08,
+    -3.95079398e-08,
+    // Pa¹ ΔTmrt³
+    -3.59413173e-07,
+    7.04388046e-07,
+    -1.89309167e-08,
+    -4.79768731e-07,
+    7.96079978e-09,
+    1.62897058e-09,
+    // Pa¹ ΔTmrt⁴
+    3.94367674e-08,
+    -1.18566247e-09,
+    3.34678041e-10,
+    // Pa¹ ΔTmrt⁵
+    -1.15606447e-10,
+    // Pa² ΔTmrt⁰
+    -2.80626406e+00,
+    5.48712484e-01,
+    -3.99428410e-03,
+    -9.54009191e-04,
+    1.93090978e-05,
+    -3.08806365e-01,
+    1.16952364e-02,
+    4.95271903e-04,
+    -1.90710882e-05,
+    2.10787756e-03,
+    -6.98445738e-04,
+    2.30109073e-05,
+    4.17856590e-04,
+    -1.27043871e-05,
+    -3.04620472e-06,
+    // Pa² ΔTmrt¹
+    5.14507424e-02,
+    -4.32510997e-03,
+    8.99281156e-05,
+    -7.14663943e-07,
+    -2.66016305e-04,
+    2.63789586e-04,
+    -7.01199003e-06,
+    -1.06823306e-04,
+    3.61341136e-06,
+    2.29748967e-07,
+    // Pa² ΔTmrt²
+    3.04788893e-04,
+    -6.42070836e-05,
+    1.16257971e-06,
+    7.68023384e-06,
+    -5.47446896e-07,
+    -3.59937910e-08,
+    // Pa² ΔTmrt³
+    -4.36497725e-06,
+    1.68737969e-07,
+    2.67489271e-08,
+    // Pa² ΔTmrt⁴
+    3.23926897e-09,
+    // Pa³ ΔTmrt⁰
+    -3.53874123e-02,
+    -2.21201190e-01,
+    1.55126038e-02,
+    -2.63917279e-04,
+    4.53433455e-02,
+    -4.32943862e-03,
+    1.45389826e-04,
+    2.17508610e-04,
+    -6.66724702e-05,
+    3.33217140e-05,
+    // Pa³ ΔTmrt¹
+    -2.26921615e-03,
+    3.80261982e-04,
+    -5.45314314e-09,
+    -7.96355448e-04,
+    2.53458034e-05,
+    -6.31223658e-06,
+    // Pa³ ΔTmrt²
+    3.02122035e-04,
+    -4.77403547e-06,
+    1.73825715e-06,
+    // Pa³ ΔTmrt³
+    -4.09087898e-07,
+    // Pa⁴ ΔTmrt⁰
+    6.14155345e-01,
+    -6.16755931e-02,
+    1.33374846e-03,
+    3.55375387e-03,
+    -5.13027851e-04,
+    1.02449757e-04,
+    // Pa⁴ ΔTmrt¹
+    -1.48526421e-03,
+    -4.11469183e-05,
+    -6.80434415e-06,
+    // Pa⁴ ΔTmrt²
+    -9.77675906e-06,
+    // Pa⁵ ΔTmrt⁰
+    8.82773108e-02,
+    -3.01859306e-03,
+    1.04452989e-03,
+    // Pa⁵ ΔTmrt¹
+    2.47090539e-04,
+    // Pa⁶ ΔTmrt⁰
+    1.48348065e-03,
+];
+
+/// UTCI (°C) from air temperature (°C), `Tmrt − Ta` (K), 10 m wind (m/s) and
+/// vapour pressure (kPa). The offset from air temperature is the polynomial.
+fn utci_polynomial_c(air: f64, radiant: f64, wind: f64, vapour: f64) -> f64 {
+    let mut coefficients = UTCI_COEFFICIENTS.iter();
+    let mut offset = 0.0;
+    for p in 0..=6 {
+        for d in 0..=6 - p {
+            for v in 0..=6 - p - d {
+                for t in 0..=6 - p - d - v {
+                    let term = air.powi(t) * wind.powi(v) * radiant.powi(d) * vapour.powi(p);
+                    offset += coefficients.next().copied().unwrap_or(0.0) * term;
+                }
+            }
+        }
+    }
+    air + offset
+}
+
+/// Felt temperature in direct sun and in shade for one hour, for someone doing
+/// `activity`, by the model the scale is calibrated on.
+pub fn felt(conditions: &Conditions, activity: Activity) -> Felt {
+    felt_with(&Steadman, conditions, activity)
+}
+
+/// [`felt`], by any model.
+pub fn felt_with<M: ThermalModel + ?Sized>(
+    model: &M,
+    conditions: &Conditions,
+    activity: Activity,
+) -> Felt {
     let air_c = conditions.air.celsius();
     let vapour = vapour_pressure_hpa(air_c, conditions.relative_humidity);
-    let wind = conditions.wind.meters_per_second().max(0.0);
+    let wind = conditions
+        .wind
+        .meters_per_second()
+        .max(0.0)
+        .hypot(activity.relative_wind().meters_per_second());
+    let offset = activity.metabolic_offset_c();
     let longwave = net_longwave(air_c, vapour, conditions.cloud_cover);
 
-    let elevation = solar_elevation_deg(conditions.direct_normal, conditions.direct_horizontal);
-    let beam = projected_area_factor(elevation) * conditions.direct_normal.max(0.0);
+    let beam =
+        projected_area_factor(conditions.solar_elevation) * conditions.direct_normal.max(0.0);
     let diffuse = conditions.diffuse.max(0.0);
     let global = conditions.direct_horizontal.max(0.0) + diffuse;
 
@@ -216,8 +681,14 @@ pub fn felt(conditions: &Conditions) -> Felt {
     let sun_radiation = absorbed_shortwave(beam, diffuse, global) + longwave;
     let shade_radiation = absorbed_shortwave(0.0, diffuse, diffuse) + longwave;
 
-    let sun = apparent_temperature_c(air_c, vapour, wind, sun_radiation);
-    let shade = apparent_temperature_c(air_c, vapour, wind, shade_radiation);
+    let environment = |radiation| Environment {
+        air_c,
+        vapour_hpa: vapour,
+        wind_ms: wind,
+        radiation,
+    };
+    let sun = model.felt_c(&environment(sun_radiation)) + offset;
+    let shade = model.felt_c(&environment(shade_radiation)) + offset;
     let sunlit = conditions.sunlit_fraction.clamp(0.0, 1.0);
 
     Felt {
@@ -231,6 +702,15 @@ pub fn felt(conditions: &Conditions) -> Felt {
 mod tests {
     use super::*;
 
+    /// At the calibrated walk, which is what every test below is about unless
+    /// it says otherwise.
+    fn felt(conditions: &Conditions) -> Felt {
+        super::felt(conditions, Activity::default())
+    }
+
+    /// Both models, for the comparisons that should hold whichever is used.
+    const MODELS: [&dyn ThermalModel; 2] = [&Steadman, &Utci];
+
     /// A clear San Francisco afternoon: mild air, stiff sea breeze, full sun.
     fn sunny_sf() -> Conditions {
         Conditions {
@@ -242,6 +722,7 @@ mod tests {
             diffuse: 100.0,
             cloud_cover: 0.0,
             sunlit_fraction: 1.0,
+            solar_elevation: 60.0,
         }
     }
 
@@ -294,15 +775,6 @@ mod tests {
         assert_eq!(projected_area_factor(120.0), projected_area_factor(90.0));
     }
 
-    #[test]
-    fn solar_elevation_is_recovered_from_the_beam_components() {
-        assert!((solar_elevation_deg(1000.0, 500.0) - 30.0).abs() < 1e-9);
-        assert!((solar_elevation_deg(1000.0, 1000.0) - 90.0).abs() < 1e-9);
-        // Rounding in the source data must not produce a NaN from asin.
-        assert!((solar_elevation_deg(800.0, 801.0) - 90.0).abs() < 1e-9);
-        assert_eq!(solar_elevation_deg(0.0, 0.0), 0.0);
-    }
-
     #[test]
     fn clear_sky_pulls_heat_off_a_body_and_overcast_does_not() {
         let vapour = vapour_pressure_hpa(18.0, 70.0);
@@ -401,19 +873,32 @@ mod tests {
 
     #[test]
     fn full_sun_opens_a_real_gap_over_shade() {
-        let felt = felt(&sunny_sf());
-        let gap = (felt.sun - felt.shade).fahrenheit();
-        // The whole premise of the page: a mild, windy, sunny day is two days.
-        assert!((8.0..16.0).contains(&gap), "sun/shade gap was {gap}°F");
-        let air = Temperature::from_celsius(18.0);
-        assert!(felt.sun > air);
-        assert!(felt.shade < air);
+        for model in MODELS {
+            let felt = felt_with(model, &sunny_sf(), Activity::default());
+            let gap = (felt.sun - felt.shade).fahrenheit();
+            // The whole premise of the page: a mild, windy, sunny day is two days.
+            assert!(
+                (8.0..16.0).contains(&gap),
+                "{}: sun/shade gap was {gap}°F",
+                model.name()
+            );
+            assert!(
+                felt.shade < Temperature::from_celsius(18.0),
+                "{}",
+                model.name()
+            );
+        }
+        // Steadman, calibrated against the scale, puts the sun above the air.
+        assert!(felt(&sunny_sf()).sun > Temperature::from_celsius(18.0));
     }
 
     #[test]
     fn overcast_leaves_almost_no_gap() {
-        let felt = felt(&overcast_sf());
-        assert!((felt.sun - felt.shade).fahrenheit().abs() < 1.0);
+        for model in MODELS {
+            let felt = felt_with(model, &overcast_sf(), Activity::default());
+            let gap = (felt.sun - felt.shade).fahrenheit().abs();
+            assert!(gap < 1.0, "{}: {gap}°F", model.name());
+        }
     }
 
     #[test]
@@ -458,14 +943,133 @@ mod tests {
         // Same beam strength, different elevation: the projected area factor
         // means an overhead sun lands on less of a standing person.
         let overhead = Conditions {
-            direct_horizontal: 850.0, // elevation 90°
+            solar_elevation: 90.0,
             ..sunny_sf()
         };
         let low = Conditions {
-            direct_horizontal: 425.0, // elevation 30°
+            solar_elevation: 30.0,
             ..sunny_sf()
         };
-        assert!(felt(&low).sun > felt(&overhead).sun);
+        for model in MODELS {
+            let sun = |conditions| felt_with(model, conditions, Activity::default()).sun;
+            assert!(sun(&low) > sun(&overhead), "{}", model.name());
+        }
+    }
+
+    #[test]
+    fn mean_radiant_temperature_comes_from_the_same_budget() {
+        let neutral = Environment {
+            air_c: 18.0,
+            vapour_hpa: 14.0,
+            wind_ms: 3.0,
+            radiation: 0.0,
+        };
+        assert!((neutral.mean_radiant_c() - 18.0).abs() < 1e-9);
+        let sunlit = Environment {
+            radiation: 150.0,
+            ..neutral
+        };
+        // Full sun on a person is worth twenty-odd degrees of radiant heat.
+        let excess = sunlit.mean_radiant_c() - 18.0;
+        assert!((15.0..35.0).contains(&excess), "{excess}");
+    }
+
+    #[test]
+    fn utci_matches_the_reference_implementation() {
+        // 25°C, radiatively neutral, 1 m/s and 50% humidity is 24.6 in the
+        // published operational procedure.
+        let environment = Environment {
+            air_c: 25.0,
+            vapour_hpa: vapour_pressure_hpa(25.0, 50.0),
+            wind_ms: 1.0,
+            radiation: 0.0,
+        };
+        let utci = Utci.felt_c(&environment);
+        assert!((utci - 24.6).abs() < 0.1, "{utci}");
+    }
+
+    #[test]
+    fn utci_is_held_to_the_range_it_was_fitted_on() {
+        let environment = Environment {
+            air_c: 20.0,
+            vapour_hpa: 12.0,
+            wind_ms: 0.0,
+            radiation: 0.0,
+        };
+        let calmer = Environment {
+            wind_ms: -4.0,
+            ..environment
+        };
+        assert_eq!(Utci.felt_c(&environment), Utci.felt_c(&calmer));
+    }
+
+    #[test]
+    fn the_two_models_agree_on_an_ordinary_day() {
+        // Different physics, so not the same number; but a mild overcast
+        // afternoon should not be a point of outfit apart.
+        let steadman = felt(&overcast_sf()).shade;
+        let utci = felt_with(&Utci, &overcast_sf(), Activity::default()).shade;
+        let scale = crate::scale::profiles().default();
+        let apart = (scale.score(steadman).value() - scale.score(utci).value()).abs();
+        assert!(
+            apart < 1.0,
+            "{steadman:?} vs {utci:?}, {apart:.2} points apart"
+        );
+    }
+
+    #[test]
+    fn walking_is_the_model_as_calibrated() {
+        // Nothing added and nothing taken away, so the anchors still hold.
+        assert_eq!(Activity::default(), Activity::Walking);
+        assert_eq!(Activity::Walking.metabolic_offset_c(), 0.0);
+        assert_eq!(Activity::Walking.relative_wind().meters_per_second(), 0.0);
+    }
+
+    #[test]
+    fn harder_work_feels_warmer_in_the_same_weather() {
+        let calm = Conditions {
+            wind: Speed::from_meters_per_second(0.5),
+            ..overcast_sf()
+        };
+        let shade = |activity| super::felt(&calm, activity).shade;
+        assert!(shade(Activity::Standing) < shade(Activity::Strolling));
+        assert!(shade(Activity::Strolling) < shade(Activity::Walking));
+        assert!(shade(Activity::Walking) < shade(Activity::BriskWalk));
+        assert!(shade(Activity::BriskWalk) < shade(Activity::Running));
+    }
+
+    #[test]
+    fn a_brisk_walk_and_a_stroll_sit_either_side_of_the_walk() {
+        // The scale's own note: half a point warm at 2.9 met, half a point
+        // cool at a stroll. Near neutral a point is eight or nine degrees.
+        let brisk = Activity::BriskWalk.metabolic_offset_c() * 9.0 / 5.0;
+        let stroll = Activity::Strolling.metabolic_offset_c() * 9.0 / 5.0;
+        assert!((3.5..5.5).contains(&brisk), "{brisk}");
+        assert!((-5.5..-3.5).contains(&stroll), "{stroll}");
+    }
+
+    #[test]
+    fn riding_makes_its_own_wind() {
+        // Cycling works harder than a walk but moves much faster; in still air
+        // the headwind takes back part of what the legs put in.
+        let still = Conditions {
+            wind: Speed::from_meters_per_second(0.0),
+            ..overcast_sf()
+        };
+        let windless = super::felt(&still, Activity::Cycling).shade.celsius();
+        let offset_only = felt(&still).shade.celsius() + Activity::Cycling.metabolic_offset_c();
+        assert!(windless < offset_only - 3.0, "{windless} vs {offset_only}");
+        // And a crosswind adds to the headwind rather than replacing it.
+        let breezy = super::felt(&overcast_sf(), Activity::Cycling).shade;
+        assert!(breezy.celsius() < windless);
+    }
+
+    #[test]
+    fn activities_round_trip_through_their_slugs() {
+        for activity in Activity::ALL {
+            assert_eq!(Activity::from_slug(activity.slug()), Some(activity));
+        }
+        assert_eq!(Activity::from_slug("skydiving"), None);
     }
 
     #[test]
@@ -479,8 +1083,11 @@ mod tests {
             diffuse: -10.0,
             cloud_cover: 300.0,
             sunlit_fraction: 7.0,
+            solar_elevation: -400.0,
         };
-        let felt = felt(&broken);
-        assert!(felt.sun.celsius().is_finite() && felt.shade.celsius().is_finite());
+        for model in MODELS {
+            let felt = felt_with(model, &broken, Activity::default());
+            assert!(felt.sun.celsius().is_finite() && felt.shade.celsius().is_finite());
+        }
     }
 }
@@ -1,9 +1,33 @@
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::OnceLock;
 
+use crate::services::{fixtures, gazetteer};
+
 pub struct Config {
     pub port: u16,
     pub website_domain: String,
+    /// The pinned locations file; see [`crate::locations`].
+    pub locations_path: PathBuf,
+    /// The comfort scale profiles; see [`crate::scale`].
+    pub scales_path: PathBuf,
+    /// Where votes on the scale are appended; see [`crate::feedback`].
+    pub feedback_path: PathBuf,
+    /// Where forecasts are kept to be marked later; see [`crate::accuracy`].
+    pub accuracy_path: PathBuf,
+    /// Where the forecast cache is saved across restarts; see
+    /// [`crate::services::open_meteo::OpenMeteo::with_snapshot`].
+    pub forecast_cache_path: PathBuf,
+    /// A GeoNames dump to search places in, if any; see
+    /// [`crate::services::gazetteer`].
+    pub gazetteer_path: Option<PathBuf>,
+    /// Whether the gazetteer is searched before upstream or only after it
+    /// fails.
+    pub gazetteer_order: gazetteer::Order,
+    /// Whether upstream APIs are called, recorded or replayed; see
+    /// [`crate::services::fixtures`].
+    pub upstream: fixtures::Mode,
+    pub fixtures_path: PathBuf,
 }
 
 pub fn get_config() -> &'static Config {
@@ -11,10 +35,50 @@ pub fn get_config() -> &'static Config {
     CONFIG.get_or_init(|| {
         let port: u16 = u16::from_str(&std::env::var("PORT").unwrap_or_default()).unwrap_or(8080);
         let website_domain = std::env::var("SERVER_HOSTNAME").unwrap_or("localhost".to_owned());
+        let locations_path = std::env::var_os("LOCATIONS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("locations.json"));
+        let scales_path = std::env::var_os("SCALES_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("scales.json"));
+        let feedback_path = std::env::var_os("FEEDBACK_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("feedback.jsonl"));
+        let accuracy_path = std::env::var_os("ACCURACY_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("forecasts.jsonl"));
+        let forecast_cache_path = std::env::var_os("FORECAST_CACHE_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("forecast-cache.json"));
+        let gazetteer_path = std::env::var_os("GAZETTEER_FILE").map(PathBuf::from);
+        let gazetteer_order = std::env::var("GAZETTEER_ORDER")
+            .ok()
+            .and_then(|name| gazetteer::Order::from_name(&name))
+            .unwrap_or(gazetteer::Order::Fallback);
+        let upstream = std::env::var("UPSTREAM")
+            .ok()
+            .and_then(|name| fixtures::Mode::from_name(&name))
+            .unwrap_or(if cfg!(test) {
+                fixtures::Mode::Replay
+            } else {
+                fixtures::Mode::Live
+            });
+        let fixtures_path = std::env::var_os("FIXTURES_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("fixtures"));
 
         Config {
             port,
             website_domain,
+            locations_path,
+            scales_path,
+            feedback_path,
+            accuracy_path,
+            forecast_cache_path,
+            gazetteer_path,
+            gazetteer_order,
+            upstream,
+            fixtures_path,
         }
     })
 }
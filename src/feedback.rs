@@ -0,0 +1,377 @@
+//! What readers say about the scale, and what it says back.
+//!
+//! The weather page asks "how does it feel out right now?" with three buttons.
+//! Each answer is appended as one JSON line to `feedback.jsonl` (or wherever
+//! `FEEDBACK_FILE` points), carrying everything the page knew when it showed
+//! the score: the hour's [`Conditions`], the [`Felt`] readings and the
+//! [`Score`](crate::scale::Score) itself, on whichever profile the reader had chosen. The file is
+//! never rewritten, so nothing about the model at the time of the vote is lost
+//! when the model changes later.
+//!
+//! Readers are told apart by a random id in a cookie, set the first time they
+//! vote. There are no accounts; clearing cookies makes a new reader.
+//!
+//! # Fitting
+//!
+//! `website fit-feedback` reads the file back and fits one number per reader:
+//! how many degrees their anchors sit above the default profile's. A runs-cold
+//! profile is exactly that shape, five degrees up at every point, so the
+//! result reads straight across to `scales.json`.
+//!
+//! Each vote is a constraint on that offset. The shown score, mapped back
+//! through the default profile, says which felt temperature the page thought
+//! it was describing; the difference from the real one is where the reader's
+//! offset was assumed to be. "About right" pulls the fit towards it, "too
+//! cold" pushes it at least [`MARGIN_F`] warmer, "too warm" as far cooler.
+//! The fit minimises the squared misses, plus a pull towards no offset worth
+//! one "about right" vote, so a single tap cannot move a reader far.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::comfort::{Conditions, Felt};
+use crate::helpers::{append_json_line, parse_json_lines};
+use crate::scale::{Profile, Profiles};
+use crate::units::Temperature;
+
+/// Degrees a "too cold" or "too warm" means the reader is off by, at least.
+/// Roughly half a rung in the middle of the scale.
+pub const MARGIN_F: f64 = 3.0;
+
+/// How hard the fit is pulled towards no offset, in "about right" votes.
+const PRIOR_WEIGHT: f64 = 1.0;
+
+/// The offsets the fit considers, in tenths of a degree either way.
+const SEARCH_TENTHS: i32 = 300;
+
+/// One tap on the feedback form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Vote {
+    TooCold,
+    Right,
+    TooWarm,
+}
+
+/// One line of the feedback file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Record {
+    /// Seconds since the Unix epoch when the vote arrived.
+    pub recorded: u64,
+    /// The reader's cookie id.
+    pub visitor: String,
+    /// The forecast hour voted on, local `YYYY-MM-DDTHH:MM`.
+    pub hour: String,
+    /// The place, as the page's own query string.
+    pub place: String,
+    pub activity: String,
+    /// Slug of the profile the score was shown on.
+    pub scale: String,
+    pub vote: Vote,
+    pub conditions: ConditionsRecord,
+    pub felt: FeltRecord,
+    /// The typical score for the hour, as shown.
+    pub score: f64,
+}
+
+/// [`Conditions`], with the units spelt out in the names since a file has no
+/// types to carry them.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConditionsRecord {
+    pub air_c: f64,
+    pub relative_humidity: f64,
+    pub wind_ms: f64,
+    pub direct_normal: f64,
+    pub direct_horizontal: f64,
+    pub diffuse: f64,
+    pub cloud_cover: f64,
+    pub sunlit_fraction: f64,
+    pub solar_elevation: f64,
+}
+
+impl From<&Conditions> for ConditionsRecord {
+    fn from(conditions: &Conditions) -> Self {
+        ConditionsRecord {
+            air_c: conditions.air.celsius(),
+            relative_humidity: conditions.relative_humidity,
+            wind_ms: conditions.wind.meters_per_second(),
+            direct_normal: conditions.direct_normal,
+            direct_horizontal: conditions.direct_horizontal,
+            diffuse: conditions.diffuse,
+            cloud_cover: conditions.cloud_cover,
+            sunlit_fraction: conditions.sunlit_fraction,
+            solar_elevation: conditions.solar_elevation,
+        }
+    }
+}
+
+/// [`Felt`], likewise.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FeltRecord {
+    pub sun_c: f64,
+    pub shade_c: f64,
+    pub typical_c: f64,
+}
+
+impl From<&Felt> for FeltRecord {
+    fn from(felt: &Felt) -> Self {
+        FeltRecord {
+            sun_c: felt.sun.celsius(),
+            shade_c: felt.shade.celsius(),
+            typical_c: felt.typical.celsius(),
+        }
+    }
+}
+
+impl Record {
+    /// The felt temperature the vote is about.
+    fn typical(&self) -> Temperature {
+        Temperature::from_celsius(self.felt.typical_c)
+    }
+}
+
+/// Adds one record to the end of the file, creating it if need be.
+pub fn append(path: &Path, record: &Record) -> std::io::Result<()> {
+    append_json_line(path, record)
+}
+
+/// Every record in a feedback file's contents, and how many lines were not
+/// records.
+pub fn parse(contents: &str) -> (Vec<Record>, usize) {
+    parse_json_lines(contents)
+}
+
+/// One reader's fitted offset.
+#[derive(Debug)]
+pub struct Fit<'a> {
+    pub visitor: &'a str,
+    pub too_cold: usize,
+    pub right: usize,
+    pub too_warm: usize,
+    /// Degrees Fahrenheit to add to every default anchor.
+    pub offset_f: f64,
+    /// The shipped profile whose anchors sit nearest that, on average.
+    pub nearest: &'static Profile,
+}
+
+/// An offset per reader, in order of first vote.
+///
+/// Votes on a score at either end of the scale are skipped: the score is
+/// clamped there, so it no longer says which temperature it was describing.
+pub fn fit<'a>(records: &'a [Record], profiles: &'static Profiles) -> Vec<Fit<'a>> {
+    let default = profiles.default();
+    let mut visitors: Vec<&str> = Vec::new();
+    for record in records {
+        if !visitors.contains(&record.visitor.as_str()) {
+            visitors.push(&record.visitor);
+        }
+    }
+
+    visitors
+        .into_iter()
+        .map(|visitor| {
+            let votes: Vec<(f64, Vote)> = records
+                .iter()
+                .filter(|record| record.visitor == visitor)
+                .filter(|record| record.score > 0.0 && record.score < 10.0)
+                .map(|record| {
+                    let described = default.felt_at(record.score);
+                    let assumed = record.typical().fahrenheit() - described.fahrenheit();
+                    (assumed, record.vote)
+                })
+                .collect();
+            let count = |vote: Vote| votes.iter().filter(|(_, cast)| *cast == vote).count();
+            let offset_f = best_offset(&votes);
+            Fit {
+                visitor,
+                too_cold: count(Vote::TooCold),
+                right: count(Vote::Right),
+                too_warm: count(Vote::TooWarm),
+                offset_f,
+                nearest: profiles
+                    .all()
+                    .iter()
+                    .min_by(|a, b| {
+                        let miss =
+                            |profile: &Profile| (profile.offset_from(default) - offset_f).abs();
+                        miss(a).total_cmp(&miss(b))
+                    })
+                    .unwrap_or(default),
+            }
+        })
+        .collect()
+}
+
+/// The offset with the least loss, to a tenth of a degree. The loss is convex,
+/// so a scan is as good as anything cleverer and much easier to check.
+fn best_offset(votes: &[(f64, Vote)]) -> f64 {
+    let loss = |offset: f64| {
+        let votes: f64 = votes
+            .iter()
+            .map(|&(assumed, vote)| match vote {
+                Vote::Right => (offset - assumed).powi(2),
+                Vote::TooCold => (assumed + MARGIN_F - offset).max(0.0).powi(2),
+                Vote::TooWarm => (offset - (assumed - MARGIN_F)).max(0.0).powi(2),
+            })
+            .sum();
+        PRIOR_WEIGHT * offset.powi(2) + votes
+    };
+    (-SEARCH_TENTHS..=SEARCH_TENTHS)
+        .map(|tenths| f64::from(tenths) / 10.0)
+        .min_by(|a, b| loss(*a).total_cmp(&loss(*b)))
+        .unwrap_or(0.0)
+}
+
+/// The report `website fit-feedback` prints.
+pub fn report(fits: &[Fit], unreadable: usize) -> String {
+    let mut out = String::new();
+    for fit in fits {
+        out.push_str(&format!(
+            "{}  {:>3} cold {:>3} right {:>3} warm  {:>+5.1}\u{b0}F  nearest {}\n",
+            fit.visitor,
+            fit.too_cold,
+            fit.right,
+            fit.too_warm,
+            fit.offset_f,
+            fit.nearest.slug()
+        ));
+    }
+    if fits.is_empty() {
+        out.push_str("No feedback yet.\n");
+    }
+    if unreadable > 0 {
+        let lines = if unreadable == 1 { "line" } else { "lines" };
+        out.push_str(&format!("Skipped {unreadable} unreadable {lines}.\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scale::profiles;
+
+    fn standard() -> &'static Profile {
+        profiles().find("standard").unwrap()
+    }
+
+    /// A vote cast at a felt temperature, with the score the standard profile
+    /// gave it.
+    fn vote(visitor: &str, fahrenheit: f64, vote: Vote) -> Record {
+        let felt = Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0);
+        Record {
+            recorded: 0,
+            visitor: visitor.to_owned(),
+            hour: "2026-08-02T13:00".to_owned(),
+            place: "loc=inner-sunset".to_owned(),
+            activity: "walking".to_owned(),
+            scale: "standard".to_owned(),
+            vote,
+            conditions: ConditionsRecord {
+                air_c: felt.celsius(),
+                relative_humidity: 70.0,
+                wind_ms: 3.0,
+                direct_normal: 0.0,
+                direct_horizontal: 0.0,
+                diffuse: 0.0,
+                cloud_cover: 100.0,
+                sunlit_fraction: 0.0,
+                solar_elevation: 40.0,
+            },
+            felt: FeltRecord {
+                sun_c: felt.celsius(),
+                shade_c: felt.celsius(),
+                typical_c: felt.celsius(),
+            },
+            score: standard().score(felt).value(),
+        }
+    }
+
+    #[test]
+    fn records_survive_the_round_trip() {
+        let original = vote("a", 60.0, Vote::TooCold);
+        let line = serde_json::to_string(&original).unwrap();
+        assert!(line.contains(r#""vote":"too_cold""#), "{line}");
+        let (records, unreadable) = parse(&format!("{line}\n{line}\n"));
+        assert_eq!(records.len(), 2);
+        assert_eq!(unreadable, 0);
+        assert_eq!(records[0].vote, Vote::TooCold);
+        assert!((records[0].felt.typical_c - original.felt.typical_c).abs() < 1e-12);
+    }
+
+    #[test]
+    fn a_torn_line_costs_one_vote() {
+        let line = serde_json::to_string(&vote("a", 60.0, Vote::Right)).unwrap();
+        let torn = &line[..line.len() / 2];
+        let (records, unreadable) = parse(&format!("{line}\n{torn}"));
+        assert_eq!(records.len(), 1);
+        assert_eq!(unreadable, 1);
+    }
+
+    #[test]
+    fn appends_rather_than_overwrites() {
+        let path = std::env::temp_dir().join(format!("feedback-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        append(&path, &vote("a", 60.0, Vote::Right)).unwrap();
+        append(&path, &vote("a", 62.0, Vote::TooWarm)).unwrap();
+        let (records, _) = parse(&std::fs::read_to_string(&path).unwrap());
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].vote, Vote::TooWarm);
+    }
+
+    #[test]
+    fn agreement_leaves_the_scale_alone() {
+        let records: Vec<Record> = [50.0, 60.0, 70.0]
+            .into_iter()
+            .map(|f| vote("a", f, Vote::Right))
+            .collect();
+        let fits = fit(&records, profiles());
+        assert_eq!(fits.len(), 1);
+        assert!(fits[0].offset_f.abs() < 0.05, "{}", fits[0].offset_f);
+        assert_eq!(fits[0].nearest.slug(), "standard");
+    }
+
+    #[test]
+    fn a_reader_who_is_always_cold_is_moved_warmer() {
+        let records: Vec<Record> = [48.0, 55.0, 58.0, 61.0, 64.0, 70.0]
+            .into_iter()
+            .map(|f| vote("a", f, Vote::TooCold))
+            .collect();
+        let fit = &fit(&records, profiles())[0];
+        assert!(fit.offset_f > 2.0, "{}", fit.offset_f);
+        assert_eq!(fit.too_cold, 6);
+        assert_eq!(fit.nearest.slug(), "runs-cold");
+    }
+
+    #[test]
+    fn one_tap_does_not_move_a_reader_far() {
+        let records = [vote("a", 60.0, Vote::TooWarm)];
+        let fit = &fit(&records, profiles())[0];
+        assert!(
+            fit.offset_f < 0.0 && fit.offset_f > -MARGIN_F,
+            "{}",
+            fit.offset_f
+        );
+    }
+
+    #[test]
+    fn readers_are_fitted_separately() {
+        let mut records: Vec<Record> = (0..5).map(|_| vote("cold", 60.0, Vote::TooCold)).collect();
+        records.extend((0..5).map(|_| vote("warm", 60.0, Vote::TooWarm)));
+        let fits = fit(&records, profiles());
+        assert_eq!(fits[0].visitor, "cold");
+        assert!(fits[0].offset_f > 0.0);
+        assert_eq!(fits[1].visitor, "warm");
+        assert!(fits[1].offset_f < 0.0);
+    }
+
+    #[test]
+    fn votes_on_a_clamped_score_are_skipped() {
+        let records = [vote("a", 120.0, Vote::TooWarm)];
+        let fit = &fit(&records, profiles())[0];
+        assert_eq!(fit.too_warm, 0);
+        assert_eq!(fit.offset_f, 0.0);
+    }
+}
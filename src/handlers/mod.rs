@@ -16,4 +16,7 @@ pub use private_relay::icloud_private_relay;
 pub use sha::sha;
 pub use slot::slot;
 pub use uuid::uuid_route;
-pub use weather::weather;
+pub use weather::{
+    best_time, calendar, commute, compare, forecast_accuracy, keep_snapshots, places,
+    record_feedback, weather,
+};
@@ -1,20 +1,21 @@
 //! iCloud Private Relay detection endpoint.
 
-use axum::extract::ConnectInfo;
+use axum::extract::{ConnectInfo, State};
 use axum::http::header::{self, HeaderMap, HeaderValue};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use std::net::SocketAddr;
 
 use crate::extractors::get_real_ip;
-use crate::services::private_relay::get_private_relay_range;
+use crate::state::AppState;
 
 pub async fn icloud_private_relay(
+    State(state): State<AppState>,
     headers: HeaderMap,
     ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
 ) -> Response {
     let real_ip = get_real_ip(&headers, &peer_addr);
-    match get_private_relay_range(&real_ip).await {
+    match state.relay.range_for(&real_ip).await {
         Ok(None) => (
             [(
                 header::CONTENT_TYPE,
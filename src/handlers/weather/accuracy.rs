@@ -0,0 +1,412 @@
+//! Keeping the pinned forecasts, and the page that marks them.
+//!
+//! A background task looks at every pin once an hour. Until a day's daylight
+//! window opens it can still take that day's forecast; once the day is over it
+//! takes the analysis from `past_days`. Either is taken once, whichever hour
+//! first gets the chance, and summed up by the same [`Pivots`] the headline
+//! uses, so what is marked is what the page said. See [`crate::accuracy`] for
+//! what is kept and how it is marked.
+
+use std::sync::Arc;
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+
+use super::{
+    activity_param, chosen_scale, date_of, daylight_hours, daylight_window, hour_label, hour_of,
+    rain_spell, remember_scale, resolve, short_hour_label, weekday_of, Exposure, Pin, Pivots,
+    WeatherQuery, CACHE_CONTROL,
+};
+use crate::accuracy::{self, DayRecord, Kind, Miss, Snapshot, Tally};
+use crate::comfort::Activity;
+use crate::config::get_config;
+use crate::locations;
+use crate::scale::{self, Profile};
+use crate::services::open_meteo::{Day, Forecast};
+use crate::services::WeatherProvider;
+use crate::state::AppState;
+
+/// How often the pins are looked at. Well inside the hours before dawn in
+/// which a day's forecast can be taken, and no load on the upstream cache.
+const SNAPSHOT_EVERY: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Rain that actually fell, in mm for the hour. Below this it is drizzle the
+/// model smears across half the day.
+const RAIN_FELL_MM: f64 = 0.1;
+
+/// How many days the page lists one by one.
+const RECENT_DAYS: usize = 14;
+
+/// A day's headline numbers, as a snapshot keeps them.
+///
+/// The hours are modelled for a walk on the default profile. Neither changes
+/// which hours are picked, only how they would be read, and the page reads
+/// them again on whichever profile is asked for.
+fn summarize(forecast: &Forecast, day: &Day, kind: Kind) -> Option<DayRecord> {
+    let visible = daylight_hours(
+        forecast,
+        day,
+        Activity::default(),
+        scale::profiles().default(),
+    );
+    let pivots = Pivots::of(&visible)?;
+    let rain = match kind {
+        Kind::Forecast => rain_spell(&visible).map(|spell| [spell.first, spell.last]),
+        Kind::Analysis => {
+            let wet: Vec<u32> = visible
+                .iter()
+                .filter(|hour| hour.raw.precipitation_mm >= RAIN_FELL_MM)
+                .map(|hour| hour.hour)
+                .collect();
+            wet.first()
+                .zip(wet.last())
+                .map(|(first, last)| [*first, *last])
+        }
+    };
+    Some(DayRecord {
+        typical_c: pivots.representative.reading(Exposure::Typical).celsius(),
+        low_c: pivots.coldest.reading(Exposure::Shade).celsius(),
+        high_c: pivots.warmest.reading(Exposure::Typical).celsius(),
+        rain,
+    })
+}
+
+/// The snapshots a forecast for `location` can provide that are not already
+/// taken: today's forecast while today's daylight is still ahead, and
+/// yesterday's analysis.
+fn due(
+    forecast: &Forecast,
+    location: &str,
+    taken: &std::collections::HashSet<(Kind, &str, &str)>,
+) -> Vec<Snapshot> {
+    let today = date_of(&forecast.current_time);
+    let now = hour_of(&forecast.current_time).unwrap_or(0);
+    let mut due = Vec::new();
+
+    if let Some(day) = forecast.days.iter().find(|day| day.date == today) {
+        let (start, _) = daylight_window(day);
+        if now < start && !taken.contains(&(Kind::Forecast, location, today)) {
+            due.extend(
+                summarize(forecast, day, Kind::Forecast)
+                    .map(|record| (day, Kind::Forecast, record)),
+            );
+        }
+    }
+    if let Some(day) = forecast
+        .days
+        .iter()
+        .rev()
+        .find(|day| day.date.as_str() < today)
+    {
+        if !taken.contains(&(Kind::Analysis, location, &day.date)) {
+            due.extend(
+                summarize(forecast, day, Kind::Analysis)
+                    .map(|record| (day, Kind::Analysis, record)),
+            );
+        }
+    }
+
+    due.into_iter()
+        .map(|(day, kind, record)| Snapshot {
+            kind,
+            location: location.to_owned(),
+            date: day.date.clone(),
+            taken: forecast.current_time.clone(),
+            day: record,
+        })
+        .collect()
+}
+
+/// Takes whatever snapshots are due, every hour, for as long as the server
+/// runs. Failures are logged and the hour skipped; the next one tries again.
+pub async fn keep_snapshots(weather: Arc<dyn WeatherProvider>) {
+    let mut ticks = tokio::time::interval(SNAPSHOT_EVERY);
+    loop {
+        ticks.tick().await;
+        let path = &get_config().accuracy_path;
+        let snapshots = match accuracy::read(path) {
+            Ok((snapshots, _)) => snapshots,
+            Err(err) => {
+                tracing::warn!("Could not read {}: {err}", path.display());
+                continue;
+            }
+        };
+        let taken = accuracy::taken(&snapshots);
+        for location in locations::pinned().all() {
+            let forecast = match weather
+                .forecast(location.latitude, location.longitude)
+                .await
+            {
+                // Only Open-Meteo's own forecasts are marked against its
+                // analysis; a fallback's would be scored on someone else's.
+                Ok(forecast) if forecast.fallback.is_none() => forecast,
+                Ok(forecast) => {
+                    tracing::warn!(
+                        "No snapshot of {} this hour: from {}",
+                        location.slug,
+                        forecast.provider
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    tracing::warn!("No snapshot of {} this hour: {err}", location.slug);
+                    continue;
+                }
+            };
+            for snapshot in due(&forecast, &location.slug, &taken) {
+                match accuracy::append(path, &snapshot) {
+                    Ok(()) => tracing::info!(
+                        "Kept the {:?} for {} on {}",
+                        snapshot.kind,
+                        snapshot.location,
+                        snapshot.date
+                    ),
+                    Err(err) => tracing::warn!("Dropped a snapshot: {err}"),
+                }
+            }
+        }
+    }
+}
+
+// ==================== Page ====================
+
+/// A score as the page shows it.
+struct Cell {
+    level: u8,
+    score: String,
+    label: String,
+}
+
+impl Cell {
+    fn of(profile: &'static Profile, celsius: f64) -> Self {
+        let score = accuracy::score_of(profile, celsius);
+        Cell {
+            level: score.level(),
+            score: score.to_string(),
+            label: score.label(),
+        }
+    }
+}
+
+/// One of the headline's numbers and how far off it has run.
+struct MissRow {
+    label: &'static str,
+    /// `+0.4`: which way it has leaned.
+    bias: String,
+    /// `0.6`: how far it is off on a typical day.
+    mean_absolute: String,
+}
+
+impl MissRow {
+    fn new(label: &'static str, miss: Miss) -> Self {
+        MissRow {
+            label,
+            bias: format!("{:+.1}", miss.bias),
+            mean_absolute: format!("{:.1}", miss.mean_absolute),
+        }
+    }
+}
+
+struct DayRow {
+    /// e.g. `Sun 2026-08-02`.
+    label: String,
+    forecast: Cell,
+    analysis: Cell,
+    rain_forecast: String,
+    rain_fell: String,
+    rain_right: bool,
+}
+
+/// `dry`, `2 PM`, or `2p–5p`.
+fn rain_phrase(rain: Option<[u32; 2]>) -> String {
+    match rain {
+        None => "dry".to_owned(),
+        Some([first, last]) if first == last => hour_label(first),
+        Some([first, last]) => format!(
+            "{}\u{2013}{}",
+            short_hour_label(first),
+            short_hour_label(last)
+        ),
+    }
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "weather-accuracy.html.jinja")]
+struct AccuracyTemplate {
+    path: String,
+    place_name: String,
+    place_param: String,
+    /// `&activity=...` for the link back, or empty.
+    activity_param: String,
+    pins: Vec<Pin>,
+    scale_name: &'static str,
+    days: usize,
+    rain_right: usize,
+    misses: Vec<MissRow>,
+    recent: Vec<DayRow>,
+    error: Option<String>,
+}
+
+/// How each pinned location's forecasts have held up.
+pub async fn forecast_accuracy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<WeatherQuery>,
+) -> Response {
+    let (target, _, mut error) = resolve(&*state.weather, &query).await;
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+    let remembered = remember_scale(query.scale.as_deref());
+
+    let snapshots = match accuracy::read(&get_config().accuracy_path) {
+        Ok((snapshots, _)) => snapshots,
+        Err(err) => {
+            error.get_or_insert_with(|| err.to_string());
+            Vec::new()
+        }
+    };
+    let pairs = match &target.pin {
+        Some(slug) => accuracy::pairs(&snapshots, slug),
+        None => {
+            error.get_or_insert_with(|| "Only pinned places are tracked.".to_owned());
+            Vec::new()
+        }
+    };
+    let tally = Tally::of(&pairs, scale);
+
+    let page = AccuracyTemplate {
+        path: uri.path().to_string(),
+        pins: locations::pinned()
+            .all()
+            .iter()
+            .map(|location| Pin {
+                name: location.name.clone(),
+                detail: location.detail.clone(),
+                href: format!("/weather/accuracy?loc={}", location.slug),
+                active: target.pin.as_ref() == Some(&location.slug),
+            })
+            .collect(),
+        activity_param: activity_param(query.activity()),
+        place_name: target.name,
+        place_param: target.param,
+        scale_name: scale.name(),
+        days: tally.as_ref().map_or(0, |tally| tally.days),
+        rain_right: tally.as_ref().map_or(0, |tally| tally.rain_right),
+        misses: tally
+            .map(|tally| {
+                vec![
+                    MissRow::new("Typical", tally.typical),
+                    MissRow::new("Low", tally.low),
+                    MissRow::new("High", tally.high),
+                ]
+            })
+            .unwrap_or_default(),
+        recent: pairs
+            .iter()
+            .rev()
+            .take(RECENT_DAYS)
+            .map(|pair| DayRow {
+                label: match weekday_of(pair.date) {
+                    Some(weekday) => format!("{weekday} {}", pair.date),
+                    None => pair.date.to_owned(),
+                },
+                forecast: Cell::of(scale, pair.forecast.typical_c),
+                analysis: Cell::of(scale, pair.analysis.typical_c),
+                rain_forecast: rain_phrase(pair.forecast.rain),
+                rain_fell: rain_phrase(pair.analysis.rain),
+                rain_right: pair.rain_right(),
+            })
+            .collect(),
+        error,
+    };
+
+    let caching = [
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(CACHE_CONTROL),
+        ),
+        (header::VARY, HeaderValue::from_static("cookie")),
+    ];
+    (caching, remembered, page).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::super::tests::forecast;
+    use super::*;
+
+    fn before_dawn() -> Forecast {
+        let mut early = forecast();
+        early.current_time = "2026-08-02T04:10".to_owned();
+        early
+    }
+
+    #[test]
+    fn summarises_a_day_the_way_the_headline_does() {
+        let forecast = forecast();
+        let today = &forecast.days[1];
+        let record = summarize(&forecast, today, Kind::Forecast).unwrap();
+        assert!(record.low_c < record.typical_c && record.typical_c <= record.high_c);
+        assert_eq!(record.rain, None);
+    }
+
+    #[test]
+    fn the_analysis_reports_rain_that_fell() {
+        let mut wet = forecast();
+        for hour in &mut wet.hours {
+            if hour.time.starts_with("2026-08-01") && ["T15", "T16"].contains(&&hour.time[10..13]) {
+                hour.precipitation_mm = 1.2;
+            }
+        }
+        let yesterday = wet.days[0].clone();
+        let record = summarize(&wet, &yesterday, Kind::Analysis).unwrap();
+        assert_eq!(record.rain, Some([15, 16]));
+        // No chance was forecast, so as a forecast the day was dry.
+        let record = summarize(&wet, &yesterday, Kind::Forecast).unwrap();
+        assert_eq!(record.rain, None);
+    }
+
+    #[test]
+    fn takes_the_forecast_only_before_the_day_starts() {
+        let taken = HashSet::new();
+        let kinds = |forecast: &Forecast| -> Vec<(Kind, String)> {
+            due(forecast, "fidi", &taken)
+                .into_iter()
+                .map(|snapshot| (snapshot.kind, snapshot.date))
+                .collect()
+        };
+        assert_eq!(
+            kinds(&before_dawn()),
+            [
+                (Kind::Forecast, "2026-08-02".to_owned()),
+                (Kind::Analysis, "2026-08-01".to_owned())
+            ]
+        );
+        // By the afternoon the day has given too much of itself away.
+        assert_eq!(
+            kinds(&forecast()),
+            [(Kind::Analysis, "2026-08-01".to_owned())]
+        );
+    }
+
+    #[test]
+    fn takes_nothing_twice() {
+        let forecast = before_dawn();
+        let first = due(&forecast, "fidi", &HashSet::new());
+        let taken = accuracy::taken(&first);
+        assert!(due(&forecast, "fidi", &taken).is_empty());
+        assert_eq!(due(&forecast, "sunset", &taken).len(), 2);
+    }
+
+    #[test]
+    fn rain_reads_as_hours() {
+        assert_eq!(rain_phrase(None), "dry");
+        assert_eq!(rain_phrase(Some([14, 14])), "2 PM");
+        assert_eq!(rain_phrase(Some([9, 17])), "9a\u{2013}5p");
+    }
+}
@@ -0,0 +1,623 @@
+//! When to go out: the best stretch of the rest of today for a given errand.
+//!
+//! The main page answers "what do I wear"; this answers the other question a
+//! walk raises, "when". It slides a window the length of the outing across
+//! today's remaining modelled hours and ranks each position by how close it
+//! sits to a target score and how much rain and gust it risks. The pieces are
+//! the page's own: [`Modelled`] hours, [`wear_for`] for the outfit, and
+//! [`rain_window`] for the one sentence about rain.
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+use super::json::Reading;
+use super::{
+    activity_choices, activity_param, chosen_scale, date_of, daylight_window, hour_of, hours_on,
+    median, no_usable_hours, rain_window, remember_scale, resolve, twelve_hour, wear_for,
+    ActivityChoice, Exposure, Modelled, Probe, Target, WeatherQuery, CACHE_CONTROL, UNCACHED,
+};
+use crate::comfort::Activity;
+use crate::helpers::requested_html;
+use crate::scale::{Profile, Score};
+use crate::services::open_meteo::Forecast;
+use crate::state::AppState;
+
+/// How many windows to offer. More than this and the list stops being an
+/// answer.
+const SHORTLIST: usize = 5;
+
+/// A certain shower costs as much as being two points off the target: enough
+/// that a dry hour beats a slightly nicer wet one, not so much that a drizzle
+/// chance of 10% outweighs a freezing evening.
+const RAIN_COST_PER_CERTAINTY: f64 = 2.0;
+
+/// Gusts below this are weather; above it they are a reason to stay in.
+const GUST_TOLERANCE_MPH: f64 = 20.0;
+
+/// A point of cost for every ten mph of gust beyond the tolerance.
+const GUST_COST_PER_MPH: f64 = 0.1;
+
+#[derive(Deserialize)]
+pub struct BestQuery {
+    #[serde(flatten)]
+    place: WeatherQuery,
+    /// Length of the outing.
+    minutes: Option<String>,
+    /// Earliest hour to start, 0-23.
+    from: Option<String>,
+    /// Hour to be back by, 0-24.
+    until: Option<String>,
+    /// Rule out any window with an hour wetter than this chance (%).
+    rain: Option<String>,
+    /// The score to aim for. Defaults to neutral.
+    target: Option<String>,
+}
+
+/// The query, parsed and filled in. Anything unreadable falls back to its
+/// default, the same way a mangled `/weather` URL falls back to home.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Constraints {
+    minutes: u32,
+    from: u32,
+    until: u32,
+    max_rain: Option<f64>,
+    target: Score,
+}
+
+impl Constraints {
+    fn parse(query: &BestQuery, daylight: (u32, u32), scale: &'static Profile) -> Self {
+        fn number<T: std::str::FromStr>(value: &Option<String>) -> Option<T> {
+            value.as_deref().and_then(|value| value.trim().parse().ok())
+        }
+        let minutes = number::<u32>(&query.minutes)
+            .unwrap_or(60)
+            .clamp(15, 12 * 60);
+        let from = number::<u32>(&query.from).unwrap_or(daylight.0).min(23);
+        // `until` is when to be home, so the last hour it admits ends there.
+        let until = number::<u32>(&query.until)
+            .unwrap_or(daylight.1 + 1)
+            .clamp(from + 1, 24);
+        Constraints {
+            minutes,
+            from,
+            until,
+            max_rain: number::<f64>(&query.rain).map(|rain| rain.clamp(0.0, 100.0)),
+            target: scale.point(number::<f64>(&query.target).unwrap_or(5.0)),
+        }
+    }
+
+    /// Whole hours of data the outing touches.
+    fn span(&self) -> usize {
+        self.minutes.div_ceil(60) as usize
+    }
+}
+
+/// One candidate stretch, ranked.
+struct Window<'a> {
+    hours: &'a [Modelled<'a>],
+    /// Lower is better.
+    cost: f64,
+    /// Mean distance from the target, in scale points.
+    distance: f64,
+    rain_chance: f64,
+    gust_mph: f64,
+}
+
+impl<'a> Window<'a> {
+    fn new(hours: &'a [Modelled<'a>], target: Score) -> Self {
+        let distance = hours
+            .iter()
+            .map(|hour| (hour.score(Exposure::Typical).value() - target.value()).abs())
+            .sum::<f64>()
+            / hours.len() as f64;
+        let rain_chance = hours
+            .iter()
+            .map(|hour| hour.raw.precipitation_probability)
+            .fold(0.0, f64::max);
+        let gust_mph = hours
+            .iter()
+            .map(|hour| hour.raw.gust.miles_per_hour())
+            .fold(0.0, f64::max);
+        Window {
+            hours,
+            cost: distance
+                + RAIN_COST_PER_CERTAINTY * rain_chance / 100.0
+                + GUST_COST_PER_MPH * (gust_mph - GUST_TOLERANCE_MPH).max(0.0),
+            distance,
+            rain_chance,
+            gust_mph,
+        }
+    }
+
+    fn start(&self) -> u32 {
+        self.hours[0].hour
+    }
+
+    fn overlaps(&self, other: &Window) -> bool {
+        let (a, b) = (self.start(), other.start());
+        let span = self.hours.len() as u32;
+        a < b + span && b < a + span
+    }
+}
+
+/// Every window that satisfies the constraints, best first, none overlapping
+/// a better one. The hours must be consecutive: a gap in the data is not an
+/// hour anyone can be outside in.
+fn rank<'a>(hours: &'a [Modelled<'a>], constraints: &Constraints) -> Vec<Window<'a>> {
+    let span = constraints.span();
+    let mut candidates: Vec<Window> = hours
+        .windows(span)
+        .filter(|run| run.windows(2).all(|pair| pair[1].hour == pair[0].hour + 1))
+        .filter(|run| {
+            run[0].hour >= constraints.from
+                && run[0].hour * 60 + constraints.minutes <= constraints.until * 60
+        })
+        .filter(|run| {
+            constraints.max_rain.is_none_or(|limit| {
+                run.iter()
+                    .all(|hour| hour.raw.precipitation_probability <= limit)
+            })
+        })
+        .map(|run| Window::new(run, constraints.target))
+        .collect();
+    candidates.sort_by(|a, b| {
+        a.cost
+            .partial_cmp(&b.cost)
+            .expect("no NaN")
+            .then(a.start().cmp(&b.start()))
+    });
+
+    let mut chosen: Vec<Window> = Vec::new();
+    for candidate in candidates {
+        if chosen.iter().all(|better| !better.overlaps(&candidate)) {
+            chosen.push(candidate);
+        }
+        if chosen.len() == SHORTLIST {
+            break;
+        }
+    }
+    chosen
+}
+
+/// `14, 90` -> `3:30 PM`.
+fn clock_after(hour: u32, minutes: u32) -> String {
+    let total = hour * 60 + minutes;
+    let (display, suffix) = twelve_hour((total / 60) % 24);
+    format!("{display}:{:02} {suffix}", total % 60)
+}
+
+/// `2026-08-02`, `14, 90` -> `2026-08-02T15:30`. Past midnight it reads
+/// `24:00`, which ISO 8601 allows and which keeps this free of date arithmetic.
+fn iso_after(date: &str, hour: u32, minutes: u32) -> String {
+    let total = hour * 60 + minutes;
+    format!("{date}T{:02}:{:02}", total / 60, total % 60)
+}
+
+pub(super) struct Suggestion {
+    /// Local `YYYY-MM-DDTHH:MM`.
+    start: String,
+    end: String,
+    /// e.g. `2:00 PM to 3:30 PM`.
+    label: String,
+    /// The middle of the window's hours, with the hour it comes from.
+    typical: Probe,
+    /// The outfit that covers the whole window.
+    wear: &'static str,
+    /// Mean distance from the target, in scale points.
+    distance: String,
+    rain_chance: i32,
+    gust_mph: i32,
+    /// As the verdict phrases it, when rain is likely at all.
+    rain: Option<String>,
+}
+
+fn suggest(window: &Window, constraints: &Constraints) -> Option<Suggestion> {
+    let date = date_of(&window.hours[0].raw.time);
+    let start = window.start();
+    let typical = median(
+        window
+            .hours
+            .iter()
+            .map(|hour| hour.score(Exposure::Typical)),
+    )?;
+    let warmest = window
+        .hours
+        .iter()
+        .map(|hour| hour.score(Exposure::Typical))
+        .reduce(Score::max)?;
+    let representative = window.hours.iter().min_by(|a, b| {
+        let distance =
+            |hour: &Modelled| (hour.score(Exposure::Typical).value() - typical.value()).abs();
+        distance(a).partial_cmp(&distance(b)).expect("no NaN")
+    })?;
+    Some(Suggestion {
+        start: iso_after(date, start, 0),
+        end: iso_after(date, start, constraints.minutes),
+        label: format!(
+            "{} to {}",
+            clock_after(start, 0),
+            clock_after(start, constraints.minutes)
+        ),
+        typical: representative.probe(Exposure::Typical),
+        wear: wear_for(typical, warmest).advice(),
+        distance: format!("{:.1}", window.distance),
+        rain_chance: window.rain_chance.round() as i32,
+        gust_mph: window.gust_mph.round() as i32,
+        rain: rain_window(window.hours),
+    })
+}
+
+/// The hours still ahead today that the constraints could use.
+fn candidates_today<'a>(
+    forecast: &'a Forecast,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Option<(Vec<Modelled<'a>>, (u32, u32))> {
+    let today = date_of(&forecast.current_time);
+    let day = forecast.days.iter().find(|day| day.date == today)?;
+    let now = hour_of(&forecast.current_time)?;
+    // The current hour counts: its conditions are the ones outside the door.
+    let hours = hours_on(forecast, today, activity, scale)
+        .into_iter()
+        .filter(|hour| hour.hour >= now)
+        .collect();
+    Some((hours, daylight_window(day)))
+}
+
+/// A pinned place is its slug; anywhere else is its coordinates and name.
+fn place_fields(target: &Target) -> Vec<(&'static str, String)> {
+    match &target.pin {
+        Some(pin) => vec![("loc", pin.clone())],
+        None => vec![
+            ("lat", target.latitude.to_string()),
+            ("lon", target.longitude.to_string()),
+            ("name", target.name.clone()),
+        ],
+    }
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "weather-best.html.jinja")]
+struct BestTemplate {
+    path: String,
+    place_name: String,
+    place_param: String,
+    /// The place again, as hidden fields, so changing a limit keeps it.
+    place_fields: Vec<(&'static str, String)>,
+    activities: Vec<ActivityChoice>,
+    /// `&activity=...` for the link back, or empty.
+    activity_param: String,
+    minutes: u32,
+    from: u32,
+    until: u32,
+    rain: String,
+    target: String,
+    suggestions: Vec<Suggestion>,
+    error: Option<String>,
+}
+
+/// The JSON shape. Versioned the way `/weather`'s document is.
+#[derive(Serialize)]
+struct Document {
+    version: u32,
+    place: String,
+    minutes: u32,
+    /// Earliest start and latest return, as hours of the local day.
+    from_hour: u32,
+    until_hour: u32,
+    max_rain_percent: Option<f64>,
+    target: f64,
+    /// The `?scale=` the target and every score are on.
+    scale: &'static str,
+    /// Best first; no two overlap.
+    windows: Vec<WindowJson>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WindowJson {
+    /// Local `YYYY-MM-DDTHH:MM`.
+    start: String,
+    end: String,
+    typical: Reading,
+    wear: &'static str,
+    /// Mean distance from the target, in scale points.
+    distance: f64,
+    rain_chance_percent: i32,
+    max_gust_mph: i32,
+    rain: Option<String>,
+}
+
+/// JSON unless a browser asks for the page: this is the endpoint a script
+/// polls before a run, so the machine-readable answer is the default.
+pub async fn best_time(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<BestQuery>,
+) -> Response {
+    let (target, _, mut error) = resolve(&*state.weather, &query.place).await;
+    let activity = query.place.activity();
+    let scale = chosen_scale(query.place.scale.as_deref(), &headers);
+    let remembered = remember_scale(query.place.scale.as_deref());
+
+    let mut constraints = Constraints::parse(&query, (7, 20), scale);
+    let mut suggestions = Vec::new();
+    let mut status = StatusCode::OK;
+    match state
+        .weather
+        .forecast(target.latitude, target.longitude)
+        .await
+    {
+        Ok(forecast) => match candidates_today(&forecast, activity, scale) {
+            Some((hours, daylight)) => {
+                constraints = Constraints::parse(&query, daylight, scale);
+                suggestions = rank(&hours, &constraints)
+                    .iter()
+                    .filter_map(|window| suggest(window, &constraints))
+                    .collect();
+                if suggestions.is_empty() && error.is_none() {
+                    error = Some("Nothing left today fits those limits.".to_owned());
+                }
+            }
+            None => {
+                status = StatusCode::BAD_GATEWAY;
+                error = Some(no_usable_hours(&forecast));
+            }
+        },
+        Err(err) => {
+            status = StatusCode::BAD_GATEWAY;
+            error = Some(err.to_string());
+        }
+    }
+
+    let caching = [
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(if status == StatusCode::OK {
+                CACHE_CONTROL
+            } else {
+                UNCACHED
+            }),
+        ),
+        (header::VARY, HeaderValue::from_static("accept, cookie")),
+    ];
+
+    if requested_html(&headers) {
+        let page = BestTemplate {
+            path: uri.path().to_string(),
+            place_fields: place_fields(&target),
+            activities: activity_choices(activity),
+            activity_param: activity_param(activity),
+            place_name: target.name,
+            place_param: target.param,
+            minutes: constraints.minutes,
+            from: constraints.from,
+            until: constraints.until,
+            rain: constraints
+                .max_rain
+                .map(|rain| format!("{rain:.0}"))
+                .unwrap_or_default(),
+            target: constraints.target.to_string(),
+            suggestions,
+            error,
+        };
+        return (status, caching, remembered, page).into_response();
+    }
+
+    let document = Document {
+        version: super::json::VERSION,
+        place: target.name,
+        minutes: constraints.minutes,
+        from_hour: constraints.from,
+        until_hour: constraints.until,
+        max_rain_percent: constraints.max_rain,
+        target: constraints.target.value(),
+        scale: scale.slug(),
+        windows: suggestions
+            .iter()
+            .map(|suggestion| WindowJson {
+                start: suggestion.start.clone(),
+                end: suggestion.end.clone(),
+                typical: Reading::from(&suggestion.typical),
+                wear: suggestion.wear,
+                distance: suggestion.distance.parse().unwrap_or_default(),
+                rain_chance_percent: suggestion.rain_chance,
+                max_gust_mph: suggestion.gust_mph,
+                rain: suggestion.rain.clone(),
+            })
+            .collect(),
+        error,
+    };
+    match serde_json::to_string_pretty(&document) {
+        Ok(body) => (
+            status,
+            caching,
+            remembered,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            format!("{body}\n"),
+        )
+            .into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{forecast, standard};
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> BestQuery {
+        let encoded = pairs
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<String>>()
+            .join("&");
+        Query::<BestQuery>::try_from_uri(&format!("/weather/best?{encoded}").parse().unwrap())
+            .unwrap()
+            .0
+    }
+
+    fn constraints(pairs: &[(&str, &str)]) -> Constraints {
+        Constraints::parse(&query(pairs), (5, 22), standard())
+    }
+
+    #[test]
+    fn reads_the_limits_and_defaults_the_rest() {
+        let parsed = constraints(&[("minutes", "90"), ("from", "7"), ("rain", "30")]);
+        assert_eq!(parsed.minutes, 90);
+        assert_eq!(parsed.span(), 2);
+        assert_eq!(parsed.from, 7);
+        assert_eq!(parsed.until, 23);
+        assert_eq!(parsed.max_rain, Some(30.0));
+        assert_eq!(parsed.target.value(), 5.0);
+
+        // A mangled value falls back rather than failing the request.
+        let mangled = constraints(&[("minutes", "lots"), ("until", "3"), ("from", "10")]);
+        assert_eq!(mangled.minutes, 60);
+        assert_eq!(mangled.until, 11, "until is pushed past from");
+    }
+
+    #[test]
+    fn the_place_comes_from_the_usual_parameters() {
+        let parsed = query(&[("loc", "fidi"), ("minutes", "30")]);
+        assert_eq!(parsed.place.loc.as_deref(), Some("fidi"));
+    }
+
+    #[test]
+    fn the_best_window_is_the_one_nearest_the_target() {
+        let forecast = forecast();
+        let (hours, daylight) =
+            candidates_today(&forecast, Activity::default(), standard()).unwrap();
+        let constraints = Constraints::parse(&query(&[("minutes", "90")]), daylight, standard());
+        let ranked = rank(&hours, &constraints);
+
+        assert!(!ranked.is_empty() && ranked.len() <= SHORTLIST);
+        for pair in ranked.windows(2) {
+            assert!(pair[0].cost <= pair[1].cost);
+        }
+        // Nothing starts before "now" in the fixture, 1:15 PM.
+        assert!(ranked.iter().all(|window| window.start() >= 13));
+        // And nothing is offered twice.
+        for (i, a) in ranked.iter().enumerate() {
+            for b in &ranked[i + 1..] {
+                assert!(!a.overlaps(b));
+            }
+        }
+    }
+
+    #[test]
+    fn a_warmer_target_moves_the_best_window_toward_the_afternoon() {
+        let forecast = forecast();
+        let (hours, daylight) =
+            candidates_today(&forecast, Activity::default(), standard()).unwrap();
+        let cool = rank(
+            &hours,
+            &Constraints::parse(&query(&[("target", "4")]), daylight, standard()),
+        );
+        let warm = rank(
+            &hours,
+            &Constraints::parse(&query(&[("target", "8")]), daylight, standard()),
+        );
+        assert!(
+            warm[0].start() < cool[0].start(),
+            "warm {} should come before the cool evening {}",
+            warm[0].start(),
+            cool[0].start()
+        );
+    }
+
+    #[test]
+    fn a_rain_limit_rules_windows_out_rather_than_ranking_them_down() {
+        let mut showery = forecast();
+        for hour in &mut showery.hours {
+            if hour_of(&hour.time).is_some_and(|hour| (15..=17).contains(&hour)) {
+                hour.precipitation_probability = 60.0;
+            }
+        }
+        let (hours, daylight) =
+            candidates_today(&showery, Activity::default(), standard()).unwrap();
+        let limited = Constraints::parse(&query(&[("rain", "30")]), daylight, standard());
+        for window in rank(&hours, &limited) {
+            assert!(window.rain_chance <= 30.0, "{}", window.start());
+        }
+
+        // Without the limit the wet hours are still allowed, just costlier.
+        let wet_only = &query(&[("from", "15"), ("until", "18")]);
+        let unlimited = Constraints::parse(wet_only, daylight, standard());
+        assert_eq!(rank(&hours, &unlimited).len(), 3);
+        let limited = Constraints {
+            max_rain: Some(30.0),
+            ..unlimited
+        };
+        assert!(rank(&hours, &limited).is_empty());
+    }
+
+    #[test]
+    fn gusts_cost_more_than_a_breeze() {
+        let forecast = forecast();
+        let hours = hours_on(&forecast, "2026-08-02", Activity::default(), standard());
+        let calm = Window::new(&hours[14..15], standard().point(5.0));
+        let mut gusty_forecast = forecast.clone();
+        for hour in &mut gusty_forecast.hours {
+            hour.gust = crate::units::Speed::from_meters_per_second(15.0);
+        }
+        let gusty_hours = hours_on(
+            &gusty_forecast,
+            "2026-08-02",
+            Activity::default(),
+            standard(),
+        );
+        let gusty = Window::new(&gusty_hours[14..15], standard().point(5.0));
+        assert!(gusty.cost > calm.cost);
+    }
+
+    #[test]
+    fn the_window_must_end_by_the_return_hour() {
+        let forecast = forecast();
+        let (hours, _) = candidates_today(&forecast, Activity::default(), standard()).unwrap();
+        let constraints = Constraints::parse(
+            &query(&[("minutes", "120"), ("from", "13"), ("until", "17")]),
+            (5, 22),
+            standard(),
+        );
+        let starts: Vec<u32> = rank(&hours, &constraints)
+            .iter()
+            .map(Window::start)
+            .collect();
+        assert!(
+            starts.iter().all(|start| (13..=15).contains(start)),
+            "{starts:?}"
+        );
+    }
+
+    #[test]
+    fn labels_the_window_by_clock_time() {
+        assert_eq!(clock_after(14, 90), "3:30 PM");
+        assert_eq!(clock_after(11, 60), "12:00 PM");
+        assert_eq!(clock_after(23, 30), "11:30 PM");
+        assert_eq!(iso_after("2026-08-02", 14, 90), "2026-08-02T15:30");
+    }
+
+    #[test]
+    fn a_suggestion_names_an_outfit_and_the_rain() {
+        let forecast = forecast();
+        let (hours, daylight) =
+            candidates_today(&forecast, Activity::default(), standard()).unwrap();
+        let constraints = Constraints::parse(&query(&[("minutes", "90")]), daylight, standard());
+        let ranked = rank(&hours, &constraints);
+        let suggestion = suggest(&ranked[0], &constraints).unwrap();
+        assert!(!suggestion.wear.is_empty());
+        assert!(suggestion.label.contains(" to "));
+        assert!(suggestion.rain.is_none(), "the fixture is dry");
+        assert!(suggestion.end > suggestion.start);
+    }
+}
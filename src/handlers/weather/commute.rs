@@ -0,0 +1,484 @@
+//! One outfit for a day spent in two places.
+//!
+//! The main verdict assumes you stay put, which is exactly wrong for a commute:
+//! the morning leg is one neighbourhood at eight and the evening leg another at
+//! six, and what matters is whatever the two have in common. This models just
+//! those two hours, each at its own place, and decides once: what to put on at
+//! the door, and which rungs of the [`Score::layer`] ladder to carry for the
+//! colder leg.
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use super::compare::place_query;
+use super::{
+    activity_choices, chosen_scale, date_of, hour_label, hour_of, hours_on, remember_scale,
+    resolve, wear_for, wind_phrase, ActivityChoice, Exposure, Modelled, Target, CACHE_CONTROL,
+    RAIN_WORTH_MENTIONING,
+};
+use crate::comfort::Activity;
+use crate::locations;
+use crate::scale::{Profile, Score};
+use crate::services::open_meteo::Forecast;
+use crate::state::AppState;
+
+const DEFAULT_LEAVE: u32 = 8;
+const DEFAULT_BACK: u32 = 18;
+
+#[derive(Deserialize)]
+pub struct CommuteQuery {
+    /// Where the morning leg starts: a pin slug or a search.
+    from: Option<String>,
+    /// When the morning leg starts, `8` or `08:30`.
+    leave: Option<String>,
+    /// Where the evening leg starts.
+    to: Option<String>,
+    /// When the evening leg starts.
+    back: Option<String>,
+    /// An activity slug, for both legs.
+    activity: Option<String>,
+    /// A scale profile slug, for both legs.
+    scale: Option<String>,
+}
+
+/// `8`, `08`, `8:30` -> `8`. Minutes are dropped: the forecast is hourly,
+/// and the hour a leg starts in is the one it is walked in.
+fn hour_param(value: Option<&str>, fallback: u32) -> u32 {
+    value
+        .and_then(|value| value.trim().split(':').next()?.parse::<u32>().ok())
+        .filter(|hour| *hour < 24)
+        .unwrap_or(fallback)
+}
+
+/// Today, unless the evening leg has already started, in which case the plan
+/// is for the next commute.
+fn commute_date(forecast: &Forecast, back: u32) -> Option<&str> {
+    let today = date_of(&forecast.current_time);
+    let past = hour_of(&forecast.current_time).is_some_and(|now| now > back);
+    let index = forecast.days.iter().position(|day| day.date == today)?;
+    let index = if past { index + 1 } else { index };
+    forecast.days.get(index).map(|day| day.date.as_str())
+}
+
+/// The modelled hour a leg is walked in.
+fn leg_hour<'a>(
+    forecast: &'a Forecast,
+    date: &str,
+    hour: u32,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Option<Modelled<'a>> {
+    hours_on(forecast, date, activity, scale)
+        .into_iter()
+        .find(|modelled| modelled.hour == hour)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LegName {
+    Morning,
+    Evening,
+}
+
+/// The decision for both legs together.
+#[derive(Debug, PartialEq)]
+struct Outfit {
+    wear: Score,
+    /// Every rung between what is worn and the colder leg out of the sun,
+    /// outermost first.
+    carry: Vec<&'static str>,
+    /// Which leg the carried layers are for.
+    colder: LegName,
+}
+
+/// Dressed for the warmer leg, as the day page dresses for its peak, and
+/// floored at the colder one so a warm evening cannot strip the morning bare.
+/// The gap down to the colder leg's shade is what goes in the bag.
+fn outfit(morning: &Modelled, evening: &Modelled) -> Outfit {
+    let typical = |hour: &Modelled| hour.score(Exposure::Typical);
+    let shade = |hour: &Modelled| hour.score(Exposure::Shade);
+
+    let wear = wear_for(
+        typical(morning).min(typical(evening)),
+        typical(morning).max(typical(evening)),
+    );
+    let colder = if shade(evening) < shade(morning) {
+        LegName::Evening
+    } else {
+        LegName::Morning
+    };
+    let coolest = shade(morning).min(shade(evening));
+
+    // `layer` at a point is what that point adds over the one above it, so
+    // the climb down from `wear` passes every point from `wear - 1` to the
+    // coolest, nearest the outfit first.
+    let mut carry: Vec<&'static str> = Vec::new();
+    for level in (coolest.level()..wear.level()).rev() {
+        if let Some(layer) = wear.with_value(f64::from(level)).layer() {
+            if !carry.contains(&layer) {
+                carry.push(layer);
+            }
+        }
+    }
+    Outfit {
+        wear,
+        carry,
+        colder,
+    }
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn and_list(items: &[&str]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => (*only).to_owned(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+struct LegRow {
+    /// `Morning` or `Evening`.
+    name: &'static str,
+    place: String,
+    place_param: String,
+    /// e.g. `8 AM`.
+    time: String,
+    typical_level: u8,
+    typical_score: String,
+    shade_level: u8,
+    shade_score: String,
+    felt_f: i32,
+    air_f: i32,
+    wind: String,
+    rain_chance: i32,
+}
+
+impl LegRow {
+    fn new(name: &'static str, target: &Target, hour: &Modelled) -> Self {
+        let typical = hour.score(Exposure::Typical);
+        let shade = hour.score(Exposure::Shade);
+        LegRow {
+            name,
+            place: target.name.clone(),
+            place_param: target.param.clone(),
+            time: hour_label(hour.hour),
+            typical_level: typical.level(),
+            typical_score: typical.to_string(),
+            shade_level: shade.level(),
+            shade_score: shade.to_string(),
+            felt_f: hour.felt.typical.round_fahrenheit(),
+            air_f: hour.raw.air.round_fahrenheit(),
+            wind: wind_phrase(hour.raw.wind, hour.raw.gust),
+            rain_chance: hour.raw.precipitation_probability.round() as i32,
+        }
+    }
+}
+
+struct Plan {
+    /// `today` or `tomorrow`.
+    day: &'static str,
+    verdict: Vec<String>,
+    legs: [LegRow; 2],
+}
+
+fn plan(
+    morning: (&Target, &Forecast),
+    evening: (&Target, &Forecast),
+    leave: u32,
+    back: u32,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Option<Plan> {
+    let (from, from_forecast) = morning;
+    let (to, to_forecast) = evening;
+    // The evening forecast's clock decides the day: it is the leg that can
+    // already be over.
+    let date = commute_date(to_forecast, back)?;
+    let morning_hour = leg_hour(from_forecast, date, leave, activity, scale)?;
+    let evening_hour = leg_hour(to_forecast, date, back, activity, scale)?;
+    let decision = outfit(&morning_hour, &evening_hour);
+
+    let mut verdict = vec![format!("Wear {}.", decision.wear.advice())];
+    if decision.carry.is_empty() {
+        verdict.push("Nothing to carry: both legs sit inside the same outfit.".to_owned());
+    } else {
+        let layers = and_list(&decision.carry);
+        verdict.push(match decision.colder {
+            LegName::Evening => format!(
+                "Take {layers} for the way home from {} at {}.",
+                to.name,
+                hour_label(back)
+            ),
+            LegName::Morning => format!(
+                "Add {layers} for the {} start, and carry it home.",
+                hour_label(leave)
+            ),
+        });
+    }
+    for (name, hour) in [("morning", &morning_hour), ("evening", &evening_hour)] {
+        let chance = hour.raw.precipitation_probability;
+        if chance >= RAIN_WORTH_MENTIONING {
+            verdict.push(format!(
+                "Rain is likely on the {name} leg, {}%.",
+                chance.round() as i32
+            ));
+        }
+    }
+
+    Some(Plan {
+        day: if date == date_of(&to_forecast.current_time) {
+            "today"
+        } else {
+            "tomorrow"
+        },
+        verdict,
+        legs: [
+            LegRow::new("Morning", from, &morning_hour),
+            LegRow::new("Evening", to, &evening_hour),
+        ],
+    })
+}
+
+struct Choice {
+    slug: String,
+    name: String,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "weather-commute.html.jinja")]
+struct CommuteTemplate {
+    path: String,
+    from_value: String,
+    to_value: String,
+    leave: u32,
+    back: u32,
+    choices: Vec<Choice>,
+    activities: Vec<ActivityChoice>,
+    plan: Option<Plan>,
+    errors: Vec<String>,
+}
+
+pub async fn commute(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<CommuteQuery>,
+) -> Response {
+    let pins = locations::pinned();
+    let from_query = place_query(query.from.as_deref(), &pins.home().slug);
+    let to_query = place_query(query.to.as_deref(), &pins.away().slug);
+    let leave = hour_param(query.leave.as_deref(), DEFAULT_LEAVE);
+    let back = hour_param(query.back.as_deref(), DEFAULT_BACK);
+    let activity = query
+        .activity
+        .as_deref()
+        .and_then(Activity::from_slug)
+        .unwrap_or_default();
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+
+    let ((from, _, from_error), (to, _, to_error)) = tokio::join!(
+        resolve(&*state.weather, &from_query),
+        resolve(&*state.weather, &to_query)
+    );
+    let (from_forecast, to_forecast) = tokio::join!(
+        state.weather.forecast(from.latitude, from.longitude),
+        state.weather.forecast(to.latitude, to.longitude),
+    );
+
+    let mut errors: Vec<String> = [from_error, to_error].into_iter().flatten().collect();
+    let plan = match (&from_forecast, &to_forecast) {
+        (Ok(from_forecast), Ok(to_forecast)) => {
+            let plan = plan(
+                (&from, from_forecast),
+                (&to, to_forecast),
+                leave,
+                back,
+                activity,
+                scale,
+            );
+            if plan.is_none() {
+                errors.push("Open-Meteo has no forecast for one of those hours.".to_owned());
+            }
+            plan
+        }
+        _ => {
+            for err in [&from_forecast, &to_forecast]
+                .into_iter()
+                .filter_map(|forecast| forecast.as_ref().err())
+            {
+                errors.push(err.to_string());
+            }
+            None
+        }
+    };
+    errors.dedup();
+
+    let page = CommuteTemplate {
+        path: uri.path().to_string(),
+        from_value: from_query.loc.or(from_query.q).unwrap_or_default(),
+        to_value: to_query.loc.or(to_query.q).unwrap_or_default(),
+        leave,
+        back,
+        choices: pins
+            .all()
+            .iter()
+            .map(|location| Choice {
+                slug: location.slug.clone(),
+                name: location.name.clone(),
+            })
+            .collect(),
+        activities: activity_choices(activity),
+        plan,
+        errors,
+    };
+    (
+        [
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(CACHE_CONTROL),
+            ),
+            (header::VARY, HeaderValue::from_static("cookie")),
+        ],
+        remember_scale(query.scale.as_deref()),
+        page,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{forecast, standard, target};
+    use super::*;
+    use crate::units::Temperature;
+
+    fn with_air(celsius: f64) -> Forecast {
+        let mut flat = forecast();
+        for hour in &mut flat.hours {
+            hour.air = Temperature::from_celsius(celsius);
+        }
+        flat
+    }
+
+    #[test]
+    fn reads_hours_with_or_without_minutes() {
+        assert_eq!(hour_param(Some("8"), 7), 8);
+        assert_eq!(hour_param(Some("08:30"), 7), 8);
+        assert_eq!(hour_param(Some("17:45"), 7), 17);
+        assert_eq!(hour_param(Some("25"), 7), 7);
+        assert_eq!(hour_param(Some("soon"), 7), 7);
+        assert_eq!(hour_param(None, 18), 18);
+    }
+
+    #[test]
+    fn plans_tomorrow_once_the_evening_leg_has_started() {
+        let mut forecast = forecast();
+        forecast.days.push(crate::services::open_meteo::Day {
+            date: "2026-08-03".to_owned(),
+            ..forecast.days[1].clone()
+        });
+        // The fixture's now is 1:15 PM.
+        assert_eq!(commute_date(&forecast, 18), Some("2026-08-02"));
+        assert_eq!(commute_date(&forecast, 12), Some("2026-08-03"));
+        assert_eq!(commute_date(&forecast, 13), Some("2026-08-02"));
+    }
+
+    #[test]
+    fn lists_layers_in_order() {
+        assert_eq!(and_list(&["a"]), "a");
+        assert_eq!(and_list(&["a", "b"]), "a and b");
+        assert_eq!(and_list(&["a", "b", "c"]), "a, b and c");
+    }
+
+    #[test]
+    fn same_weather_both_ways_needs_nothing_carried() {
+        let forecast = with_air(18.0);
+        let hours = hours_on(&forecast, "2026-08-02", Activity::default(), standard());
+        // Both outside the fixture's sunny hours, so shade is all there is.
+        let decision = outfit(&hours[7], &hours[20]);
+        assert!(decision.carry.is_empty(), "{decision:?}");
+    }
+
+    #[test]
+    fn a_cold_evening_somewhere_else_is_carried_home() {
+        let (warm, cold) = (with_air(22.0), with_air(8.0));
+        let morning = hours_on(&warm, "2026-08-02", Activity::default(), standard())[9];
+        let evening = hours_on(&cold, "2026-08-02", Activity::default(), standard())[18];
+        let decision = outfit(&morning, &evening);
+
+        assert_eq!(decision.colder, LegName::Evening);
+        assert!(
+            decision.wear.level() > evening.score(Exposure::Shade).level(),
+            "dressed for the warm leg"
+        );
+        assert!(decision.carry.len() >= 2, "{:?}", decision.carry);
+        let mut unique = decision.carry.clone();
+        unique.dedup();
+        assert_eq!(unique, decision.carry, "each layer once");
+    }
+
+    #[test]
+    fn a_cold_morning_is_dressed_for_and_the_layer_comes_home() {
+        let (cold, warm) = (with_air(8.0), with_air(22.0));
+        let morning = hours_on(&cold, "2026-08-02", Activity::default(), standard())[7];
+        let evening = hours_on(&warm, "2026-08-02", Activity::default(), standard())[17];
+        assert_eq!(outfit(&morning, &evening).colder, LegName::Morning);
+
+        let plan = plan(
+            (&target(), &cold),
+            (&target(), &warm),
+            7,
+            17,
+            Activity::default(),
+            standard(),
+        )
+        .unwrap();
+        assert_eq!(plan.day, "today");
+        assert!(plan.verdict[1].starts_with("Add "), "{:?}", plan.verdict);
+        assert_eq!(plan.legs[0].time, "7 AM");
+        assert_eq!(plan.legs[1].time, "5 PM");
+    }
+
+    #[test]
+    fn mentions_rain_on_the_leg_it_falls_on() {
+        let mut wet = forecast();
+        for hour in &mut wet.hours {
+            if hour.time == "2026-08-02T18:00" {
+                hour.precipitation_probability = 70.0;
+            }
+        }
+        let dry = forecast();
+        let plan = plan(
+            (&target(), &dry),
+            (&target(), &wet),
+            8,
+            18,
+            Activity::default(),
+            standard(),
+        )
+        .unwrap();
+        assert!(plan
+            .verdict
+            .iter()
+            .any(|line| line == "Rain is likely on the evening leg, 70%."));
+        assert!(!plan.verdict.iter().any(|line| line.contains("morning leg")));
+    }
+
+    #[test]
+    fn a_missing_hour_is_no_plan() {
+        let mut ragged = forecast();
+        ragged.hours.retain(|hour| hour.time != "2026-08-02T08:00");
+        let whole = forecast();
+        assert!(plan(
+            (&target(), &ragged),
+            (&target(), &whole),
+            8,
+            18,
+            Activity::default(),
+            standard()
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,475 @@
+//! Two places, one afternoon.
+//!
+//! Both places go through the same [`resolve`] and the same daylight-window
+//! [`Pivots`] as the main page, and both comfort curves are drawn against one
+//! [`Axes`], so a line that sits higher really does feel warmer. The two
+//! forecasts are fetched at once: the page waits for the slower one, not for
+//! both in turn.
+
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use super::{
+    activity_choices, chosen_scale, date_of, daylight_hours, daylight_window, hour_label,
+    no_usable_hours, remember_scale, resolve, round1, ActivityChoice, Axes, AxisTick, Exposure,
+    FeelBand, GridLine, Marker, Modelled, Pivots, Probe, Target, WeatherQuery, AXIS_LABEL_Y,
+    CACHE_CONTROL, CHART_HEIGHT, CHART_WIDTH, MARKER_LABEL_Y, PLOT_BOTTOM, PLOT_LEFT, PLOT_RIGHT,
+    PLOT_TOP,
+};
+use crate::comfort::Activity;
+use crate::locations;
+use crate::scale::{Profile, Score};
+use crate::services::open_meteo::Forecast;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct CompareQuery {
+    /// A pinned slug, or anything the place search understands.
+    a: Option<String>,
+    b: Option<String>,
+    /// An activity slug, applied to both places.
+    activity: Option<String>,
+    /// A scale profile slug, for both places.
+    scale: Option<String>,
+}
+
+/// A pin slug is looked up as a pin; anything else is searched for.
+pub(super) fn place_query(value: Option<&str>, fallback: &str) -> WeatherQuery {
+    let value = value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(fallback);
+    let pinned = locations::pinned().find(value).is_some();
+    WeatherQuery {
+        loc: pinned.then(|| value.to_owned()),
+        q: (!pinned).then(|| value.to_owned()),
+        lat: None,
+        lon: None,
+        name: None,
+        activity: None,
+        scale: None,
+        noted: None,
+    }
+}
+
+/// One place's day, reduced to what the comparison needs.
+struct Day<'a> {
+    date: &'a str,
+    hours: Vec<Modelled<'a>>,
+    window: (u32, u32),
+    wear: Score,
+    carry: Option<&'static str>,
+    typical: Probe,
+}
+
+impl<'a> Day<'a> {
+    fn of(forecast: &'a Forecast, activity: Activity, scale: &'static Profile) -> Option<Self> {
+        let today = date_of(&forecast.current_time);
+        let day = forecast.days.iter().find(|day| day.date == today)?;
+        let hours = daylight_hours(forecast, day, activity, scale);
+        let pivots = Pivots::of(&hours)?;
+        Some(Day {
+            date: &day.date,
+            window: daylight_window(day),
+            wear: pivots.wear(),
+            carry: pivots.carry(),
+            typical: pivots.representative.probe(Exposure::Typical),
+            hours,
+        })
+    }
+}
+
+/// Which place lets you leave something at home. A warmer outfit rung means
+/// fewer layers; on the same rung, the place with nothing to carry wins.
+fn fewer_layers(a: (&str, &Day), b: (&str, &Day)) -> String {
+    let ((a_name, a_day), (b_name, b_day)) = (a, b);
+    let (lighter, heavier) = match a_day.wear.level().cmp(&b_day.wear.level()) {
+        std::cmp::Ordering::Greater => (a, b),
+        std::cmp::Ordering::Less => (b, a),
+        std::cmp::Ordering::Equal => {
+            return match (a_day.carry, b_day.carry) {
+                (None, Some(layer)) => format!(
+                    "{a_name} needs fewer layers: both call for {}, but {b_name} also wants {layer} on hand.",
+                    a_day.wear.advice()
+                ),
+                (Some(layer), None) => format!(
+                    "{b_name} needs fewer layers: both call for {}, but {a_name} also wants {layer} on hand.",
+                    b_day.wear.advice()
+                ),
+                _ => format!(
+                    "Same layers in both: {}.",
+                    a_day.wear.advice()
+                ),
+            };
+        }
+    };
+    format!(
+        "{} needs fewer layers: {} there, against {} in {}.",
+        lighter.0,
+        lighter.1.wear.advice(),
+        heavier.1.wear.advice(),
+        heavier.0
+    )
+}
+
+struct HourRow {
+    label: String,
+    a_level: u8,
+    a_score: String,
+    b_level: u8,
+    b_score: String,
+    /// `b` minus `a`, e.g. `+0.8`, or `same`.
+    delta: String,
+    /// `up`, `down` or `flat`, for colouring.
+    direction: &'static str,
+}
+
+/// The hours both places have, by local clock.
+fn hour_rows(a: &[Modelled], b: &[Modelled]) -> Vec<HourRow> {
+    a.iter()
+        .filter_map(|a_hour| {
+            let b_hour = b.iter().find(|b_hour| b_hour.hour == a_hour.hour)?;
+            let a_score = a_hour.score(Exposure::Typical);
+            let b_score = b_hour.score(Exposure::Typical);
+            let difference = round1(b_score.value()) - round1(a_score.value());
+            Some(HourRow {
+                label: hour_label(a_hour.hour),
+                a_level: a_score.level(),
+                a_score: a_score.to_string(),
+                b_level: b_score.level(),
+                b_score: b_score.to_string(),
+                delta: match (difference * 10.0).round() as i32 {
+                    0 => "same".to_owned(),
+                    tenths if tenths > 0 => format!("+{difference:.1}"),
+                    _ => format!("{difference:.1}"),
+                },
+                direction: match (difference * 10.0).round() as i32 {
+                    0 => "flat",
+                    tenths if tenths > 0 => "up",
+                    _ => "down",
+                },
+            })
+        })
+        .collect()
+}
+
+/// Both places' typical curves on one shared scale. Same geometry fields as
+/// the day chart, so the frame template draws either.
+struct CompareChart {
+    width: f64,
+    height: f64,
+    plot_left: f64,
+    plot_right: f64,
+    plot_width: f64,
+    plot_top: f64,
+    plot_bottom: f64,
+    axis_label_y: f64,
+    marker_label_y: f64,
+    grid_label_x: f64,
+    a_line: String,
+    b_line: String,
+    grid: Vec<GridLine>,
+    bands: Vec<FeelBand>,
+    ticks: Vec<AxisTick>,
+    now: Option<Marker>,
+}
+
+impl CompareChart {
+    fn build(a: &Day, b: &Day, now: &str) -> Option<CompareChart> {
+        if a.hours.len() < 2 || b.hours.len() < 2 {
+            return None;
+        }
+        let window = (a.window.0.min(b.window.0), a.window.1.max(b.window.1));
+        let axes = Axes::fit(
+            window,
+            a.hours.iter().chain(b.hours.iter()).map(|hour| {
+                let typical = hour.score(Exposure::Typical).value();
+                (typical, typical)
+            }),
+        )?;
+        Some(CompareChart {
+            width: CHART_WIDTH,
+            height: CHART_HEIGHT,
+            plot_left: PLOT_LEFT,
+            plot_right: PLOT_RIGHT,
+            plot_width: PLOT_RIGHT - PLOT_LEFT,
+            plot_top: PLOT_TOP,
+            plot_bottom: PLOT_BOTTOM,
+            axis_label_y: AXIS_LABEL_Y,
+            marker_label_y: MARKER_LABEL_Y,
+            grid_label_x: PLOT_LEFT - 4.0,
+            a_line: axes.trace(&a.hours, Exposure::Typical),
+            b_line: axes.trace(&b.hours, Exposure::Typical),
+            grid: axes.grid(),
+            bands: axes.bands(),
+            ticks: axes.ticks(),
+            now: axes.marker(now, "now".to_owned()),
+        })
+    }
+}
+
+struct Side {
+    name: String,
+    param: String,
+    typical: Probe,
+    wear: &'static str,
+    carry: Option<&'static str>,
+}
+
+impl Side {
+    fn new(target: &Target, day: Day) -> Self {
+        Side {
+            name: target.name.clone(),
+            param: target.param.clone(),
+            typical: day.typical,
+            wear: day.wear.advice(),
+            carry: day.carry,
+        }
+    }
+}
+
+struct Comparison {
+    a: Side,
+    b: Side,
+    verdict: String,
+    chart: Option<CompareChart>,
+    hours: Vec<HourRow>,
+    /// False when the two places keep different clocks, so "2 PM" in each
+    /// column is not the same moment.
+    same_clock: bool,
+}
+
+impl Comparison {
+    fn sides(&self) -> [&Side; 2] {
+        [&self.a, &self.b]
+    }
+}
+
+fn compare_days(
+    a: (&Target, &Forecast),
+    b: (&Target, &Forecast),
+    activity: Activity,
+    scale: &'static Profile,
+) -> Option<Comparison> {
+    let (a_target, a_forecast) = a;
+    let (b_target, b_forecast) = b;
+    let a_day = Day::of(a_forecast, activity, scale)?;
+    let b_day = Day::of(b_forecast, activity, scale)?;
+    let verdict = fewer_layers((&a_target.name, &a_day), (&b_target.name, &b_day));
+    let chart = CompareChart::build(&a_day, &b_day, &a_forecast.current_time);
+    let hours = hour_rows(&a_day.hours, &b_day.hours);
+    let same_clock = a_forecast.timezone_abbreviation == b_forecast.timezone_abbreviation
+        && a_day.date == b_day.date;
+    Some(Comparison {
+        a: Side::new(a_target, a_day),
+        b: Side::new(b_target, b_day),
+        verdict,
+        chart,
+        hours,
+        same_clock,
+    })
+}
+
+struct Choice {
+    slug: String,
+    name: String,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "weather-compare.html.jinja")]
+struct CompareTemplate {
+    path: String,
+    a_name: String,
+    b_name: String,
+    /// What was asked for, to refill the form.
+    a_value: String,
+    b_value: String,
+    choices: Vec<Choice>,
+    activities: Vec<ActivityChoice>,
+    comparison: Option<Comparison>,
+    errors: Vec<String>,
+}
+
+pub async fn compare(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<CompareQuery>,
+) -> Response {
+    let activity = query
+        .activity
+        .as_deref()
+        .and_then(Activity::from_slug)
+        .unwrap_or_default();
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+    let pins = locations::pinned();
+    let a_query = place_query(query.a.as_deref(), &pins.home().slug);
+    let b_query = place_query(query.b.as_deref(), &pins.away().slug);
+    let ((a_target, _, a_error), (b_target, _, b_error)) = tokio::join!(
+        resolve(&*state.weather, &a_query),
+        resolve(&*state.weather, &b_query)
+    );
+    let (a_forecast, b_forecast) = tokio::join!(
+        state
+            .weather
+            .forecast(a_target.latitude, a_target.longitude),
+        state
+            .weather
+            .forecast(b_target.latitude, b_target.longitude),
+    );
+
+    let mut errors: Vec<String> = [a_error, b_error].into_iter().flatten().collect();
+    let comparison = match (&a_forecast, &b_forecast) {
+        (Ok(a_forecast), Ok(b_forecast)) => {
+            let comparison = compare_days(
+                (&a_target, a_forecast),
+                (&b_target, b_forecast),
+                activity,
+                scale,
+            );
+            if comparison.is_none() {
+                for forecast in [a_forecast, b_forecast] {
+                    if Day::of(forecast, activity, scale).is_none() {
+                        errors.push(no_usable_hours(forecast));
+                    }
+                }
+                errors.dedup();
+            }
+            comparison
+        }
+        _ => {
+            for err in [&a_forecast, &b_forecast]
+                .into_iter()
+                .filter_map(|forecast| forecast.as_ref().err())
+            {
+                errors.push(err.to_string());
+            }
+            None
+        }
+    };
+    errors.dedup();
+
+    let page = CompareTemplate {
+        path: uri.path().to_string(),
+        a_name: a_target.name,
+        b_name: b_target.name,
+        a_value: a_query.loc.or(a_query.q).unwrap_or_default(),
+        b_value: b_query.loc.or(b_query.q).unwrap_or_default(),
+        choices: pins
+            .all()
+            .iter()
+            .map(|location| Choice {
+                slug: location.slug.clone(),
+                name: location.name.clone(),
+            })
+            .collect(),
+        activities: activity_choices(activity),
+        comparison,
+        errors,
+    };
+    (
+        [
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(CACHE_CONTROL),
+            ),
+            (header::VARY, HeaderValue::from_static("cookie")),
+        ],
+        remember_scale(query.scale.as_deref()),
+        page,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{forecast, standard, target};
+    use super::*;
+
+    /// The fixture with every hour shifted by a fixed amount of air.
+    fn warmer_by(celsius: f64) -> Forecast {
+        let mut warmer = forecast();
+        for hour in &mut warmer.hours {
+            hour.air = crate::units::Temperature::from_celsius(hour.air.celsius() + celsius);
+        }
+        warmer
+    }
+
+    #[test]
+    fn a_pin_slug_is_a_pin_and_anything_else_a_search() {
+        let pinned = place_query(Some("fidi"), "inner-sunset");
+        assert_eq!(pinned.loc.as_deref(), Some("fidi"));
+        assert!(pinned.q.is_none());
+
+        let searched = place_query(Some("Oakland"), "inner-sunset");
+        assert!(searched.loc.is_none());
+        assert_eq!(searched.q.as_deref(), Some("Oakland"));
+
+        let fallback = place_query(Some("  "), "inner-sunset");
+        assert_eq!(fallback.loc.as_deref(), Some("inner-sunset"));
+    }
+
+    #[test]
+    fn the_warmer_place_needs_fewer_layers() {
+        let (cool, warm) = (forecast(), warmer_by(6.0));
+        let comparison = compare_days(
+            (&target(), &cool),
+            (&target(), &warm),
+            Activity::default(),
+            standard(),
+        )
+        .unwrap();
+        assert!(comparison.hours.iter().all(|row| row.direction == "up"));
+        let a_day = Day::of(&cool, Activity::default(), standard()).unwrap();
+        let b_day = Day::of(&warm, Activity::default(), standard()).unwrap();
+        assert!(b_day.wear.level() > a_day.wear.level());
+
+        let verdict = fewer_layers(("Cool", &a_day), ("Warm", &b_day));
+        assert!(verdict.starts_with("Warm needs fewer layers"), "{verdict}");
+        let flipped = fewer_layers(("Warm", &b_day), ("Cool", &a_day));
+        assert!(flipped.starts_with("Warm needs fewer layers"), "{flipped}");
+    }
+
+    #[test]
+    fn identical_places_say_so() {
+        let forecast = forecast();
+        let day = Day::of(&forecast, Activity::default(), standard()).unwrap();
+        let verdict = fewer_layers(("A", &day), ("B", &day));
+        assert!(verdict.starts_with("Same layers in both"), "{verdict}");
+
+        let rows = hour_rows(&day.hours, &day.hours);
+        assert_eq!(rows.len(), day.hours.len());
+        assert!(rows.iter().all(|row| row.delta == "same"));
+    }
+
+    #[test]
+    fn both_curves_share_one_scale() {
+        let (cool, warm) = (forecast(), warmer_by(6.0));
+        let a_day = Day::of(&cool, Activity::default(), standard()).unwrap();
+        let b_day = Day::of(&warm, Activity::default(), standard()).unwrap();
+        let chart = CompareChart::build(&a_day, &b_day, &cool.current_time).unwrap();
+        let heights = |line: &str| -> Vec<f64> {
+            line.split(' ')
+                .map(|point| point.split(',').nth(1).unwrap().parse().unwrap())
+                .collect()
+        };
+        // SVG y grows downward, so the warmer place is the smaller y at every
+        // hour only if both were placed on the same axis.
+        for (a, b) in heights(&chart.a_line).iter().zip(heights(&chart.b_line)) {
+            assert!(b < *a, "{b} should sit above {a}");
+        }
+        assert!(chart.now.is_some());
+    }
+
+    #[test]
+    fn only_hours_both_places_have_are_compared() {
+        let forecast = forecast();
+        let day = Day::of(&forecast, Activity::default(), standard()).unwrap();
+        let rows = hour_rows(&day.hours, &day.hours[3..]);
+        assert_eq!(rows.len(), day.hours.len() - 3);
+    }
+}
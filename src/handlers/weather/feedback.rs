@@ -0,0 +1,110 @@
+//! The "how does it feel?" buttons under the current hour.
+//!
+//! The form posts to `/weather/feedback` with the page's own query string, so
+//! the place and activity go through the same [`resolve`] and the hour is
+//! modelled exactly as the page modelled it. The forecast is fetched again
+//! rather than trusted from the form: it is almost always still cached, and a
+//! record built from posted numbers would be whatever anyone chose to post.
+//!
+//! Recording is best effort. If the forecast or the file is unavailable the
+//! vote is logged and dropped, and the reader goes back to the page either way.
+
+use axum::extract::{Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Form;
+use serde::Deserialize;
+
+use super::{
+    activity_param, chosen_scale, conditions, date_of, hour_of, resolve, Exposure, Modelled,
+    WeatherQuery,
+};
+use crate::config::get_config;
+use crate::feedback::{self, ConditionsRecord, FeltRecord, Record, Vote};
+use crate::helpers::cookie;
+use crate::state::AppState;
+
+/// Cookie that tells one reader's votes from another's.
+const VISITOR_COOKIE: &str = "visitor";
+
+#[derive(Deserialize)]
+pub struct FeedbackForm {
+    vote: Vote,
+}
+
+/// Records one vote and sends the reader back to the page they voted on.
+pub async fn record_feedback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<WeatherQuery>,
+    Form(form): Form<FeedbackForm>,
+) -> Response {
+    let (target, _, error) = resolve(&*state.weather, &query).await;
+    let activity = query.activity();
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+    let known = cookie(&headers, VISITOR_COOKIE).map(str::to_owned);
+    let visitor = known
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let recorded = match (
+        error,
+        state
+            .weather
+            .forecast(target.latitude, target.longitude)
+            .await,
+    ) {
+        (Some(error), _) => Err(error),
+        (None, Err(err)) => Err(err.to_string()),
+        (None, Ok(forecast)) => {
+            let today = date_of(&forecast.current_time);
+            let now = hour_of(&forecast.current_time);
+            forecast
+                .hours
+                .iter()
+                .filter(|hour| date_of(&hour.time) == today && hour_of(&hour.time) == now)
+                .find_map(|hour| Modelled::new(hour, activity, scale))
+                .ok_or_else(|| "no forecast hour for now".to_owned())
+                .and_then(|hour| {
+                    let record = Record {
+                        recorded: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map_or(0, |elapsed| elapsed.as_secs()),
+                        visitor: visitor.clone(),
+                        hour: hour.raw.time.clone(),
+                        place: target.param.clone(),
+                        activity: activity.slug().to_owned(),
+                        scale: scale.slug().to_owned(),
+                        vote: form.vote,
+                        conditions: ConditionsRecord::from(&conditions(hour.raw)),
+                        felt: FeltRecord::from(&hour.felt),
+                        score: hour.score(Exposure::Typical).value(),
+                    };
+                    feedback::append(&get_config().feedback_path, &record)
+                        .map_err(|err| err.to_string())
+                })
+        }
+    };
+
+    let mut back = format!("/weather?{}{}", target.param, activity_param(activity));
+    match recorded {
+        Ok(()) => back.push_str("&noted=1"),
+        Err(err) => tracing::warn!("Dropped a feedback vote: {err}"),
+    }
+
+    let remember = known.is_none().then(|| {
+        let value =
+            format!("{VISITOR_COOKIE}={visitor}; Path=/; Max-Age=31536000; SameSite=Lax; HttpOnly");
+        [(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&value).expect("a uuid is a valid header value"),
+        )]
+    });
+    let location = [(
+        header::LOCATION,
+        HeaderValue::from_str(&format!("{back}#feedback"))
+            .unwrap_or_else(|_| HeaderValue::from_static("/weather")),
+    )];
+    (StatusCode::SEE_OTHER, remember, location).into_response()
+}
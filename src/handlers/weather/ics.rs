@@ -0,0 +1,400 @@
+//! The week's outfit decisions as an iCalendar feed.
+//!
+//! `/weather.ics` takes the same query as `/weather` and renders the same
+//! [`Report`]: one all-day event per outlook day, titled with what to wear and
+//! what to take, and one timed event for each spell of rain worth planning
+//! around. Subscribed to from a calendar app, that puts "take a light jacket"
+//! beside the day's meetings, and it can never disagree with the page because
+//! every word and number in it is the page's.
+//!
+//! # Time
+//!
+//! All-day events carry plain dates, which a calendar shows on that day
+//! wherever it is. Timed events are written in UTC from the forecast's current
+//! offset, since the feed has no timezone database to name the zone with; a
+//! daylight-saving change inside the week leaves that day's rain an hour out.
+//!
+//! # Format
+//!
+//! RFC 5545, by hand: it is a dozen properties, and the only subtleties are
+//! CRLF line endings, escaping `\`, `;`, `,` and newlines in text, and folding
+//! any line longer than 75 octets onto continuation lines.
+
+use axum::extract::{Query, State};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use super::outlook::OutlookDay;
+use super::{
+    build_report, chosen_scale, no_usable_hours, resolve, Probe, Report, Target, WeatherQuery,
+    CACHE_CONTROL,
+};
+use crate::clock;
+use crate::comfort::Activity;
+use crate::scale;
+use crate::state::AppState;
+
+/// Longest line RFC 5545 allows before folding, in octets.
+const LINE_LIMIT: usize = 75;
+
+/// How often a subscribed calendar should come back. Any sooner and it would
+/// only be fetching the same cached forecast.
+const REFRESH: &str = "PT1H";
+
+/// The feed for one place, or a 502 when there is nothing trustworthy to put
+/// in it. A calendar app keeps the last good copy through an error, which is
+/// better than replacing the week with an empty one or with somewhere else.
+pub async fn calendar(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<WeatherQuery>,
+) -> Response {
+    let (target, _, error) = resolve(&*state.weather, &query).await;
+    let activity = query.activity();
+    // Calendar apps send no cookies, so the subscribe link carries `?scale=`;
+    // the cookie only matters when a browser opens the feed directly.
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+
+    let report = match (
+        error,
+        state
+            .weather
+            .forecast(target.latitude, target.longitude)
+            .await,
+    ) {
+        (Some(error), _) => Err(error),
+        (None, Ok(forecast)) => build_report(&forecast, &target, activity, scale)
+            .ok_or_else(|| no_usable_hours(&forecast)),
+        (None, Err(err)) => Err(err.to_string()),
+    };
+
+    match report {
+        Ok(report) => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/calendar; charset=utf-8"),
+                ),
+                (
+                    header::CACHE_CONTROL,
+                    HeaderValue::from_static(CACHE_CONTROL),
+                ),
+                // The scale may come from the cookie, and a shared cache must
+                // not hand one reader's ladder to another.
+                (header::VARY, HeaderValue::from_static("cookie")),
+            ],
+            render(&target, &report),
+        )
+            .into_response(),
+        Err(message) => (StatusCode::BAD_GATEWAY, message).into_response(),
+    }
+}
+
+/// The whole `VCALENDAR`, CRLF-terminated.
+fn render(target: &Target, report: &Report) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//website//weather//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "METHOD:PUBLISH".to_owned(),
+        format!("X-WR-CALNAME:{}", escape(&calendar_name(target, report))),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{REFRESH}"),
+        format!("X-PUBLISHED-TTL:{REFRESH}"),
+    ];
+
+    let stamp = clock::basic_utc(&report.as_of, report.utc_offset_seconds);
+    let feed = Feed {
+        place: uid_part(&target.param),
+        activity: report.activity,
+        scale: report.scale.slug(),
+        stamp: stamp.as_deref().unwrap_or("19700101T000000Z"),
+    };
+    for day in &report.outlook {
+        lines.extend(feed.day_event(day, report));
+        lines.extend(feed.rain_event(day, report.utc_offset_seconds));
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn calendar_name(target: &Target, report: &Report) -> String {
+    let mut name = format!("What to wear in {}", target.name);
+    if report.activity != Activity::default() {
+        name.push_str(&format!(", {}", report.activity.name()));
+    }
+    if !std::ptr::eq(report.scale, scale::profiles().default()) {
+        name.push_str(&format!(" ({})", report.scale.name()));
+    }
+    name
+}
+
+/// What every event in one feed shares.
+struct Feed<'a> {
+    /// The place's query string, made safe for a UID.
+    place: String,
+    activity: Activity,
+    /// Slug of the comfort scale the words come from.
+    scale: &'a str,
+    /// When the forecast was read, as `DTSTAMP`.
+    stamp: &'a str,
+}
+
+impl Feed<'_> {
+    /// Stable across refreshes, so a calendar updates the day in place rather
+    /// than stacking a new copy of it each hour.
+    fn uid(&self, date: &str, kind: &str) -> String {
+        format!(
+            "{date}-{kind}-{}-{}-{}@weather",
+            self.place,
+            self.activity.slug(),
+            self.scale
+        )
+    }
+
+    fn day_event(&self, day: &OutlookDay, report: &Report) -> Vec<String> {
+        let Some(days) = clock::parse_date(&day.date) else {
+            return Vec::new();
+        };
+        // Today leads with the page's own verdict; later days have only their
+        // outlook line to go on.
+        let mut description = if day.date == report.date {
+            report.verdict.clone()
+        } else {
+            let mut lines = vec![format!("Wear {}.", day.wear)];
+            if let Some(layer) = day.carry {
+                lines.push(format!("Take {layer}."));
+            }
+            lines.extend(day.rain.clone());
+            lines
+        };
+        description.push(String::new());
+        description.push(reading("Typical", &day.typical));
+        description.push(reading("Low, out of the sun", &day.low));
+        description.push(reading("High", &day.high));
+
+        vec![
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}", self.uid(&day.date, "day")),
+            format!("DTSTAMP:{}", self.stamp),
+            format!("DTSTART;VALUE=DATE:{}", clock::basic_date(days)),
+            format!("DTEND;VALUE=DATE:{}", clock::basic_date(days + 1)),
+            format!("SUMMARY:{}", escape(&summary(day))),
+            format!("DESCRIPTION:{}", escape(&description.join("\n"))),
+            "TRANSP:TRANSPARENT".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]
+    }
+
+    /// A timed event covering the wet hours, when there are any.
+    fn rain_event(&self, day: &OutlookDay, utc_offset_seconds: i32) -> Vec<String> {
+        let Some(spell) = day.rain_spell else {
+            return Vec::new();
+        };
+        // Through the end of the last wet hour; `T24:00` rolls into tomorrow.
+        let start = format!("{}T{:02}:00", day.date, spell.first);
+        let end = format!("{}T{:02}:00", day.date, spell.last + 1);
+        let (Some(start), Some(end)) = (
+            clock::basic_utc(&start, utc_offset_seconds),
+            clock::basic_utc(&end, utc_offset_seconds),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut event = vec![
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}", self.uid(&day.date, "rain")),
+            format!("DTSTAMP:{}", self.stamp),
+            format!("DTSTART:{start}"),
+            format!("DTEND:{end}"),
+            format!(
+                "SUMMARY:{}",
+                escape(&format!("Rain likely, {}%", spell.peak))
+            ),
+        ];
+        if let Some(rain) = &day.rain {
+            event.push(format!("DESCRIPTION:{}", escape(rain)));
+        }
+        event.push("TRANSP:TRANSPARENT".to_owned());
+        event.push("END:VEVENT".to_owned());
+        event
+    }
+}
+
+/// The title a calendar shows on the day, e.g. `Wear jeans and a light
+/// jacket, take a warm jacket`.
+fn summary(day: &OutlookDay) -> String {
+    match day.carry {
+        Some(layer) => format!("Wear {}, take {layer}", day.wear),
+        None => format!("Wear {}", day.wear),
+    }
+}
+
+/// `Typical 5.3 (64°): mild — jeans and a long-sleeve t-shirt`.
+fn reading(name: &str, probe: &Probe) -> String {
+    format!(
+        "{name} {} ({}\u{b0}): {}",
+        probe.score, probe.degrees, probe.label
+    )
+}
+
+/// Letters and digits kept, everything else a hyphen: `loc=fidi` becomes
+/// `loc-fidi`.
+fn uid_part(param: &str) -> String {
+    param
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// RFC 5545 TEXT escaping.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// One content line, folded so no physical line passes [`LINE_LIMIT`] octets
+/// and never inside a character, with its CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line.
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{report, target};
+    use super::*;
+
+    fn feed() -> String {
+        render(&target(), &report())
+    }
+
+    /// The logical lines, with folding undone.
+    fn unfolded(feed: &str) -> Vec<String> {
+        feed.replace("\r\n ", "")
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn is_one_calendar_with_an_event_per_day() {
+        let feed = feed();
+        assert!(feed.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(feed.ends_with("END:VCALENDAR\r\n"));
+        let lines = unfolded(&feed);
+        let days = lines
+            .iter()
+            .filter(|line| line.starts_with("DTSTART;VALUE=DATE:"))
+            .count();
+        assert_eq!(days, report().outlook.len());
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20260802".to_owned()));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260803".to_owned()));
+    }
+
+    #[test]
+    fn today_carries_the_verdict_and_the_three_labels() {
+        let report = report();
+        let lines = unfolded(&feed());
+        let description = lines
+            .iter()
+            .find(|line| line.starts_with("DESCRIPTION:"))
+            .unwrap();
+        assert!(
+            description.contains(&escape(&report.verdict[0])),
+            "{description}"
+        );
+        for probe in [
+            &report.outlook[0].typical,
+            &report.outlook[0].low,
+            &report.outlook[0].high,
+        ] {
+            assert!(description.contains(&escape(&probe.label)), "{description}");
+        }
+        let summary = lines
+            .iter()
+            .find(|line| line.starts_with("SUMMARY:"))
+            .unwrap();
+        assert!(summary.starts_with("SUMMARY:Wear "), "{summary}");
+    }
+
+    #[test]
+    fn rain_becomes_a_timed_event_in_utc() {
+        let mut report = report();
+        report.outlook[0].rain_spell = Some(super::super::RainSpell {
+            first: 15,
+            last: 17,
+            peak: 60,
+        });
+        let lines = unfolded(&render(&target(), &report));
+        // 15:00 to 18:00 at seven hours behind UTC.
+        assert!(lines.contains(&"DTSTART:20260802T220000Z".to_owned()));
+        assert!(lines.contains(&"DTEND:20260803T010000Z".to_owned()));
+        assert!(lines.contains(&"SUMMARY:Rain likely\\, 60%".to_owned()));
+    }
+
+    #[test]
+    fn a_dry_week_has_no_timed_events() {
+        let lines = unfolded(&feed());
+        assert!(!lines.iter().any(|line| line.starts_with("DTSTART:")));
+    }
+
+    #[test]
+    fn uids_are_stable_and_distinct() {
+        let uids = |report: &Report| -> Vec<String> {
+            unfolded(&render(&target(), report))
+                .into_iter()
+                .filter(|line| line.starts_with("UID:"))
+                .collect()
+        };
+        let earlier = report();
+        let mut later = report();
+        later.as_of = "2026-08-02T14:15".to_owned();
+
+        let mut unique = uids(&earlier);
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), uids(&earlier).len());
+        // A refresh an hour on updates the same events rather than adding more.
+        assert_eq!(uids(&earlier), uids(&later));
+        assert!(unique[0].contains("-loc-"), "{}", unique[0]);
+    }
+
+    #[test]
+    fn escapes_text_the_way_rfc_5545_asks() {
+        assert_eq!(escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne");
+    }
+
+    #[test]
+    fn folds_long_lines_without_splitting_a_character() {
+        let long = format!("DESCRIPTION:{}", "\u{2014}".repeat(60));
+        let folded = fold(&long);
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= LINE_LIMIT, "{} octets", line.len());
+        }
+        assert_eq!(unfolded(&folded), vec![long]);
+    }
+}
@@ -0,0 +1,515 @@
+//! The weather report as JSON, for scripts and dashboards.
+//!
+//! Served from `/weather` itself when the request asks for `application/json`,
+//! so there is one URL per place whichever way it is read. The document is the
+//! same [`Report`] the page renders, field for field, with the same rounding:
+//! a dashboard and the page can never disagree about a number.
+//!
+//! Temperatures are whole degrees Fahrenheit and wind is mph, as on the page,
+//! and every field that carries a unit says so in its name. Scores are the
+//! 0-10 comfort scale to one decimal, exactly as printed.
+//!
+//! # Versioning
+//!
+//! `version` changes whenever a field is removed or renamed or changes what it
+//! means. Adding a field does not change it, so readers should ignore fields
+//! they do not know.
+
+use axum::http::header::{self, HeaderValue};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use super::outlook::OutlookDay;
+use super::{Alternate, Comparison, HourRow, NowRow, Probe, Report, Target};
+
+/// The current shape of [`Document`].
+pub const VERSION: u32 = 1;
+
+/// Everything `/weather` knows about one request.
+#[derive(Serialize)]
+pub struct Document {
+    /// Bumped on any incompatible change; see the module docs.
+    version: u32,
+    place: Place,
+    /// Other matches for a `q=` search, best first.
+    alternates: Vec<AlternatePlace>,
+    /// Why there is no report, or why the place is not the one asked for.
+    error: Option<String>,
+    /// Absent when the forecast could not be fetched or had no usable hours.
+    report: Option<Body>,
+}
+
+#[derive(Serialize)]
+struct Place {
+    name: String,
+    detail: String,
+    /// The point that was asked for, not the model grid cell that answered.
+    latitude: f64,
+    longitude: f64,
+    /// Query string that reproduces this place, e.g. `loc=fidi`.
+    query: String,
+    /// Slug, when this is a pinned location.
+    pin: Option<String>,
+    /// When the request gave only coordinates and `name` is the nearest
+    /// place, how far that place is from them.
+    named_from_mi: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct AlternatePlace {
+    label: String,
+    /// Site-relative link to that place's report.
+    href: String,
+}
+
+#[derive(Serialize)]
+struct Body {
+    /// Local date the report is for, `YYYY-MM-DD`.
+    date: String,
+    /// Local time the forecast was read, `YYYY-MM-DDTHH:MM`.
+    as_of: String,
+    /// e.g. `GMT-7`. Every time in the document is in this zone.
+    timezone: String,
+    /// `the rest of today` or `today`: which hours the headline covers.
+    scope: &'static str,
+    /// The `?activity=` every score in the document is for, e.g. `walking`.
+    activity: &'static str,
+    /// The `?scale=` every score in the document is read on, e.g. `standard`.
+    scale: &'static str,
+    /// The headline: what the covered hours mostly feel like.
+    typical: Reading,
+    /// Share of the covered hours that want the same outfit as `typical`.
+    typical_share_percent: i32,
+    /// The coldest it gets out of the sun.
+    low: Reading,
+    /// The warmest it gets, exposure-weighted.
+    high: Reading,
+    /// `high.felt_f - low.felt_f`.
+    swing_f: i32,
+    /// What to wear and what to carry, as sentences.
+    verdict: Vec<String>,
+    /// The current hour, when it falls inside the hourly window.
+    now: Option<Now>,
+    /// From an hour before sunrise to a couple of hours after sunset.
+    hours: Vec<Hour>,
+    /// Local clock times, e.g. `6:14 AM`.
+    sunrise: String,
+    sunset: String,
+    air_high_f: i32,
+    air_low_f: i32,
+    /// Highest hourly chance of rain across the whole window.
+    rain_chance_percent: i32,
+    rain_total_in: f64,
+    /// Today against yesterday over the same hours. Empty when yesterday is
+    /// not in the forecast.
+    yesterday: Vec<Change>,
+    /// Today and up to six days after it, each summed up over its whole
+    /// daylight window.
+    outlook: Vec<Day>,
+    grid: Grid,
+    source: Source,
+}
+
+/// A headline score, with the hour and the weather that produced it.
+#[derive(Serialize)]
+pub(super) struct Reading {
+    score: f64,
+    /// The nearest whole point, which is also its colour on the page.
+    level: u8,
+    /// e.g. `neutral — jeans and a light jacket`.
+    label: String,
+    felt_f: i32,
+    /// e.g. `2 PM`.
+    hour: String,
+    air_f: i32,
+    /// e.g. `8 mph, gusting 21`.
+    wind: String,
+    humidity_percent: i32,
+    cloud_percent: i32,
+}
+
+#[derive(Serialize)]
+struct Now {
+    /// e.g. `1:15 PM`.
+    time: String,
+    /// Absent when there is no direct sun to stand in.
+    sun: Option<Reading>,
+    shade: Reading,
+    air_f: i32,
+    wind: String,
+    /// e.g. `3° warmer than this time yesterday`.
+    versus_yesterday: Option<String>,
+}
+
+/// One score without the context a [`Reading`] carries; the hour's own row
+/// already has it.
+#[derive(Serialize)]
+struct Point {
+    score: f64,
+    level: u8,
+    felt_f: i32,
+}
+
+#[derive(Serialize)]
+struct Hour {
+    /// Local `YYYY-MM-DDTHH:MM`.
+    time: String,
+    /// e.g. `5 AM`.
+    label: String,
+    /// Absent when there is no direct sun that hour.
+    sun: Option<Point>,
+    shade: Point,
+    air_f: i32,
+    wind_mph: i32,
+    humidity_percent: i32,
+    cloud_percent: i32,
+    rain_chance_percent: i32,
+    /// The sun's mean height over the hour; negative once it has set.
+    sun_elevation_deg: i32,
+    /// Clockwise from true north.
+    sun_azimuth_deg: i32,
+    /// No radiation was published for the hour, so the scores rest on an
+    /// estimate from the cloud cover.
+    radiation_estimated: bool,
+    now: bool,
+    past: bool,
+    /// The sun sets between this hour and the next.
+    sunset_follows: bool,
+}
+
+#[derive(Serialize)]
+struct Change {
+    label: String,
+    /// As printed, unit included, e.g. `64°` or `5.3`.
+    today: String,
+    yesterday: String,
+    /// e.g. `+4°`, or `same`.
+    change: String,
+    /// `up`, `down` or `flat`.
+    direction: &'static str,
+}
+
+#[derive(Serialize)]
+struct Day {
+    /// Local `YYYY-MM-DD`.
+    date: String,
+    /// `Today`, `Tomorrow`, then e.g. `Wed 5`.
+    label: String,
+    typical: Reading,
+    low: Reading,
+    high: Reading,
+    /// The outfit, e.g. `jeans and a light jacket`.
+    wear: &'static str,
+    /// A layer to carry on top of it, e.g. `a warm jacket`.
+    carry: Option<&'static str>,
+    /// When rain is likely, as a sentence. Absent on a dry day.
+    rain: Option<String>,
+    air_high_f: i32,
+    air_low_f: i32,
+}
+
+#[derive(Serialize)]
+struct Grid {
+    /// How far the model's grid cell is from the point asked for.
+    distance_mi: f64,
+    elevation_ft: i32,
+}
+
+#[derive(Serialize)]
+struct Source {
+    /// Whose forecast this is, e.g. `Open-Meteo`.
+    provider: &'static str,
+    /// Why it is not Open-Meteo's; absent when it is.
+    fallback: Option<String>,
+    /// Fields that provider does not publish, estimated here instead.
+    estimated: Vec<&'static str>,
+    /// Served past its time while a newer one is fetched; `as_of` says when
+    /// it was read.
+    stale: bool,
+}
+
+/// A score as printed on the page. Parsed back from the printed figure rather
+/// than taken from the float, so the JSON and the page agree to the digit.
+fn points(printed: &str) -> f64 {
+    printed.parse().unwrap_or_default()
+}
+
+impl From<&Probe> for Reading {
+    fn from(probe: &Probe) -> Self {
+        Reading {
+            score: points(&probe.score),
+            level: probe.level,
+            label: probe.label.clone(),
+            felt_f: probe.degrees,
+            hour: probe.hour_label.clone(),
+            air_f: probe.air_f,
+            wind: probe.wind.clone(),
+            humidity_percent: probe.humidity,
+            cloud_percent: probe.cloud,
+        }
+    }
+}
+
+impl From<&NowRow> for Now {
+    fn from(now: &NowRow) -> Self {
+        Now {
+            time: now.label.clone(),
+            sun: now.has_sun.then(|| Reading::from(&now.sun)),
+            shade: Reading::from(&now.shade),
+            air_f: now.air_f,
+            wind: now.wind.clone(),
+            versus_yesterday: now.versus_yesterday.clone(),
+        }
+    }
+}
+
+impl From<&HourRow> for Hour {
+    fn from(row: &HourRow) -> Self {
+        Hour {
+            time: row.time.clone(),
+            label: row.label.clone(),
+            sun: row.has_sun.then(|| Point {
+                score: points(&row.sun_score),
+                level: row.sun_level,
+                felt_f: row.sun_f,
+            }),
+            shade: Point {
+                score: points(&row.shade_score),
+                level: row.shade_level,
+                felt_f: row.shade_f,
+            },
+            air_f: row.air_f,
+            wind_mph: row.wind_mph,
+            humidity_percent: row.humidity,
+            cloud_percent: row.cloud,
+            rain_chance_percent: row.rain_chance,
+            sun_elevation_deg: row.sun_elevation_deg,
+            sun_azimuth_deg: row.sun_azimuth_deg,
+            radiation_estimated: row.estimated,
+            now: row.is_now,
+            past: row.past,
+            sunset_follows: row.sunset_follows,
+        }
+    }
+}
+
+impl From<&Comparison> for Change {
+    fn from(row: &Comparison) -> Self {
+        Change {
+            label: row.label.clone(),
+            today: row.today.clone(),
+            yesterday: row.yesterday.clone(),
+            change: row.delta.clone(),
+            direction: row.direction,
+        }
+    }
+}
+
+impl From<&OutlookDay> for Day {
+    fn from(day: &OutlookDay) -> Self {
+        Day {
+            date: day.date.clone(),
+            label: day.label.clone(),
+            typical: Reading::from(&day.typical),
+            low: Reading::from(&day.low),
+            high: Reading::from(&day.high),
+            wear: day.wear,
+            carry: day.carry,
+            rain: day.rain.clone(),
+            air_high_f: day.air_high_f,
+            air_low_f: day.air_low_f,
+        }
+    }
+}
+
+impl From<&Report> for Body {
+    fn from(report: &Report) -> Self {
+        Body {
+            date: report.date.clone(),
+            as_of: report.as_of.clone(),
+            timezone: report.timezone.clone(),
+            scope: report.headline_scope,
+            activity: report.activity.slug(),
+            scale: report.scale.slug(),
+            typical: Reading::from(&report.typical),
+            typical_share_percent: report.typical_share,
+            low: Reading::from(&report.low),
+            high: Reading::from(&report.high),
+            swing_f: report.swing_f,
+            verdict: report.verdict.clone(),
+            now: report.now.as_ref().map(Now::from),
+            hours: report.hours.iter().map(Hour::from).collect(),
+            sunrise: report.sunrise_label.clone(),
+            sunset: report.sunset_label.clone(),
+            air_high_f: report.air_high_f,
+            air_low_f: report.air_low_f,
+            rain_chance_percent: report.rain_chance,
+            rain_total_in: report.rain_total_in.parse().unwrap_or_default(),
+            yesterday: report.comparisons.iter().map(Change::from).collect(),
+            outlook: report.outlook.iter().map(Day::from).collect(),
+            grid: Grid {
+                distance_mi: report.grid_distance_mi.parse().unwrap_or_default(),
+                elevation_ft: report.grid_elevation_ft,
+            },
+            source: Source {
+                provider: report.provider,
+                fallback: report.fallback.clone(),
+                estimated: report.estimated.clone(),
+                stale: report.stale,
+            },
+        }
+    }
+}
+
+impl Document {
+    pub fn new(
+        target: &Target,
+        alternates: &[Alternate],
+        report: Option<&Report>,
+        error: Option<&str>,
+    ) -> Self {
+        Document {
+            version: VERSION,
+            place: Place {
+                name: target.name.clone(),
+                detail: target.detail.clone(),
+                latitude: target.latitude,
+                longitude: target.longitude,
+                query: target.param.clone(),
+                pin: target.pin.clone(),
+                named_from_mi: target
+                    .named_from_mi
+                    .map(|miles| (miles * 10.0).round() / 10.0),
+            },
+            alternates: alternates
+                .iter()
+                .map(|alternate| AlternatePlace {
+                    label: alternate.label.clone(),
+                    href: alternate.href.clone(),
+                })
+                .collect(),
+            error: error.map(str::to_owned),
+            report: report.map(Body::from),
+        }
+    }
+}
+
+/// Pretty-printed with a trailing newline, like `/echo`, so it reads in a
+/// terminal as well as it parses.
+///
+/// A missing report is a 502, whether the upstream forecast failed or came
+/// back with no usable hours for today: either way a script polling this
+/// should be able to tell from the status alone.
+impl IntoResponse for Document {
+    fn into_response(self) -> Response {
+        let status = if self.report.is_some() {
+            StatusCode::OK
+        } else {
+            StatusCode::BAD_GATEWAY
+        };
+        match serde_json::to_string_pretty(&self) {
+            Ok(body) => (
+                status,
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                )],
+                format!("{body}\n"),
+            )
+                .into_response(),
+            Err(err) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/plain; charset=utf-8"),
+                )],
+                err.to_string(),
+            )
+                .into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{report, target};
+    use super::*;
+
+    fn document() -> serde_json::Value {
+        let report = report();
+        serde_json::to_value(Document::new(&target(), &[], Some(&report), None)).unwrap()
+    }
+
+    #[test]
+    fn a_looked_up_name_carries_its_distance() {
+        assert!(document()["place"]["named_from_mi"].is_null());
+        let mut named = target();
+        named.named_from_mi = Some(3.87);
+        let json = serde_json::to_value(Document::new(&named, &[], None, None)).unwrap();
+        assert_eq!(json["place"]["named_from_mi"], 3.9);
+    }
+
+    #[test]
+    fn carries_its_version() {
+        assert_eq!(document()["version"], VERSION);
+    }
+
+    #[test]
+    fn the_headline_matches_the_page_to_the_digit() {
+        let report = report();
+        let json = document();
+        let typical = &json["report"]["typical"];
+        assert_eq!(
+            typical["score"].as_f64().unwrap(),
+            report.typical.score.parse::<f64>().unwrap()
+        );
+        assert_eq!(typical["level"], report.typical.level);
+        assert_eq!(json["report"]["swing_f"], report.swing_f);
+        assert_eq!(json["report"]["verdict"][0], report.verdict[0]);
+    }
+
+    #[test]
+    fn hours_carry_timestamps_and_leave_out_a_sun_reading_after_dark() {
+        let json = document();
+        let hours = json["report"]["hours"].as_array().unwrap();
+        assert_eq!(hours[0]["time"], "2026-08-02T05:00");
+        assert!(hours[0]["sun"].is_null(), "5 AM has no sun");
+        assert!(hours[0]["shade"]["score"].is_number());
+        let noon = hours.iter().find(|hour| hour["label"] == "12 PM").unwrap();
+        assert!(noon["sun"]["felt_f"].as_i64() > noon["shade"]["felt_f"].as_i64());
+    }
+
+    #[test]
+    fn says_which_activity_the_scores_are_for() {
+        assert_eq!(document()["report"]["activity"], "walking");
+    }
+
+    #[test]
+    fn says_which_scale_the_scores_are_on() {
+        assert_eq!(document()["report"]["scale"], "standard");
+    }
+
+    #[test]
+    fn includes_the_outlook() {
+        let json = document();
+        let outlook = json["report"]["outlook"].as_array().unwrap();
+        assert_eq!(outlook[0]["label"], "Today");
+        assert!(outlook[0]["wear"].is_string());
+        assert!(outlook[0]["typical"]["score"].is_number());
+    }
+
+    #[test]
+    fn describes_the_place_asked_for() {
+        let place = &document()["place"];
+        assert_eq!(place["name"], "Inner Sunset");
+        assert_eq!(place["query"], "loc=inner-sunset");
+        assert_eq!(place["pin"], "inner-sunset");
+    }
+
+    #[test]
+    fn a_missing_report_is_a_gateway_error() {
+        let document = Document::new(&target(), &[], None, Some("Could not reach Open-Meteo."));
+        assert_eq!(document.into_response().status(), StatusCode::BAD_GATEWAY);
+    }
+}
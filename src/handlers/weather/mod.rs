@@ -2,26 +2,66 @@
 //!
 //! Everything is rendered server-side in one pass so the page is readable the
 //! moment it arrives. The only client-side work is remembering which location
-//! was last chosen.
+//! was last chosen and suggesting places as the search box is typed into.
+
+mod accuracy;
+mod best;
+mod commute;
+mod compare;
+mod feedback;
+mod ics;
+mod json;
+mod outlook;
+mod places;
+mod text;
 
 use askama::Template;
 use askama_web::WebTemplate;
-use axum::extract::{OriginalUri, Query};
-use axum::http::header::{self, HeaderValue};
+use axum::extract::{OriginalUri, Query, State};
+use axum::http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use axum::response::{IntoResponse, Response};
 use serde::Deserialize;
 
-use crate::comfort::{self, Conditions, Felt};
-use crate::helpers::urlencode;
+use crate::comfort::{self, Activity, Conditions, Felt, ThermalModel, Utci};
+use crate::helpers::{cookie, requested_html, requested_json, urlencode};
 use crate::locations;
-use crate::scale::{self, Score};
+use crate::scale::{self, Profile, Score};
 use crate::services::open_meteo::{self, Day, Forecast, Hour, Place};
+use crate::services::WeatherProvider;
+use crate::state::AppState;
 use crate::units::{Speed, Temperature};
 
+pub use accuracy::{forecast_accuracy, keep_snapshots};
+pub use best::best_time;
+pub use commute::commute;
+pub use compare::compare;
+pub use feedback::record_feedback;
+pub use ics::calendar;
+pub use places::places;
+
 /// How long a browser may reuse the page. Comfortably inside the upstream
 /// cache window, and short enough that a reload before leaving is current.
 const CACHE_CONTROL: &str = "public, max-age=300";
 
+/// What to say when a forecast arrived with nothing in it for today, naming
+/// whoever made it, which since the fallback is not always Open-Meteo.
+fn no_usable_hours(forecast: &Forecast) -> String {
+    // Credits are written to follow "from", e.g. "the National Weather
+    // Service", so this one starts a sentence with a capital.
+    let mut rest = forecast.provider.chars();
+    let provider: String = rest
+        .next()
+        .map(|first| first.to_ascii_uppercase())
+        .into_iter()
+        .chain(rest)
+        .collect();
+    format!("{provider} returned no usable hours for today.")
+}
+
+/// For a page without a forecast. Upstream may be back by the next request,
+/// and nobody should be shown its failure from a cache for five minutes.
+const UNCACHED: &str = "no-store";
+
 // ==================== Query ====================
 
 /// Strings rather than typed numbers so a hand-mangled URL falls back to home
@@ -35,6 +75,90 @@ pub struct WeatherQuery {
     lat: Option<String>,
     lon: Option<String>,
     name: Option<String>,
+    /// An [`Activity`] slug. Walking when absent or unknown.
+    activity: Option<String>,
+    /// A [`Profile`] slug. See [`chosen_scale`] for what happens without one.
+    scale: Option<String>,
+    /// Set by the redirect after a feedback vote, to say it was recorded.
+    noted: Option<String>,
+}
+
+impl WeatherQuery {
+    fn activity(&self) -> Activity {
+        self.activity
+            .as_deref()
+            .and_then(Activity::from_slug)
+            .unwrap_or_default()
+    }
+}
+
+/// One option in an activity picker.
+struct ActivityChoice {
+    slug: &'static str,
+    name: &'static str,
+    selected: bool,
+}
+
+fn activity_choices(current: Activity) -> Vec<ActivityChoice> {
+    Activity::ALL
+        .into_iter()
+        .map(|activity| ActivityChoice {
+            slug: activity.slug(),
+            name: activity.name(),
+            selected: activity == current,
+        })
+        .collect()
+}
+
+/// `&activity=running`, or nothing for the default, so links that carry it
+/// stay as short as they were.
+fn activity_param(activity: Activity) -> String {
+    if activity == Activity::default() {
+        String::new()
+    } else {
+        format!("&activity={}", activity.slug())
+    }
+}
+
+/// The cookie that remembers a scale picked with `?scale=`.
+const SCALE_COOKIE: &str = "scale";
+
+/// The scale a request is read on: `?scale=` when it names one, else the one
+/// this browser picked before, else the default.
+fn chosen_scale(asked: Option<&str>, headers: &HeaderMap) -> &'static Profile {
+    let profiles = scale::profiles();
+    asked
+        .and_then(|slug| profiles.find(slug))
+        .or_else(|| cookie(headers, SCALE_COOKIE).and_then(|slug| profiles.find(slug)))
+        .unwrap_or_else(|| profiles.default())
+}
+
+/// A `Set-Cookie` for a scale picked with `?scale=`, so the other weather
+/// pages and the next visit keep it without every link having to carry it.
+fn remember_scale(asked: Option<&str>) -> Option<[(HeaderName, HeaderValue); 2]> {
+    let profile = scale::profiles().find(asked?)?;
+    let cookie = format!(
+        "{SCALE_COOKIE}={}; Path=/; Max-Age=31536000; SameSite=Lax; HttpOnly",
+        profile.slug()
+    );
+    Some([
+        (header::SET_COOKIE, HeaderValue::from_str(&cookie).ok()?),
+        // A response that sets a cookie is this browser's alone.
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, max-age=300"),
+        ),
+    ])
+}
+
+/// `&scale=runs-cold`, or nothing for the default. Only for links that leave
+/// the browser, like a calendar subscription, where the cookie cannot follow.
+fn scale_param(scale: &Profile) -> String {
+    if scale.slug() == scale::profiles().default().slug() {
+        String::new()
+    } else {
+        format!("&scale={}", scale.slug())
+    }
 }
 
 /// A resolved place, however it was asked for.
@@ -47,17 +171,21 @@ struct Target {
     param: String,
     /// Slug if this is a pinned location, for highlighting the shortcut row.
     pin: Option<String>,
+    /// Miles from the point to the place it is named for, when the name was
+    /// looked up rather than given.
+    named_from_mi: Option<f64>,
 }
 
 impl Target {
-    fn from_pin(location: &'static locations::Location) -> Self {
+    fn from_pin(location: &locations::Location) -> Self {
         Target {
-            name: location.name.to_owned(),
-            detail: location.detail.to_owned(),
+            name: location.name.clone(),
+            detail: location.detail.clone(),
             latitude: location.latitude,
             longitude: location.longitude,
             param: format!("loc={}", location.slug),
-            pin: Some(location.slug.to_owned()),
+            pin: Some(location.slug.clone()),
+            named_from_mi: None,
         }
     }
 
@@ -69,6 +197,7 @@ impl Target {
             longitude: place.longitude,
             param: place_param(place),
             pin: None,
+            named_from_mi: None,
         }
     }
 }
@@ -79,15 +208,21 @@ fn place_param(place: &Place) -> String {
     coordinate_param(place.latitude, place.longitude, &place.name)
 }
 
+/// Coordinates, and a name unless there is none to give.
 fn coordinate_param(latitude: f64, longitude: f64, name: &str) -> String {
-    format!(
-        "lat={latitude:.4}&lon={longitude:.4}&name={}",
-        urlencode(name)
-    )
+    let param = format!("lat={latitude:.4}&lon={longitude:.4}");
+    if name.is_empty() {
+        param
+    } else {
+        format!("{param}&name={}", urlencode(name))
+    }
 }
 
 /// Resolves the query into a place, plus any other candidates worth offering.
-async fn resolve(query: &WeatherQuery) -> (Target, Vec<Alternate>, Option<String>) {
+async fn resolve(
+    weather: &dyn WeatherProvider,
+    query: &WeatherQuery,
+) -> (Target, Vec<Alternate>, Option<String>) {
     if let (Some(latitude), Some(longitude)) = (
         query
             .lat
@@ -99,23 +234,47 @@ async fn resolve(query: &WeatherQuery) -> (Target, Vec<Alternate>, Option<String
             .and_then(|value| value.parse::<f64>().ok()),
     ) {
         if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
-            let name = query
-                .name
-                .clone()
-                .filter(|name| !name.trim().is_empty())
-                .unwrap_or_else(|| format!("{latitude:.3}, {longitude:.3}"));
-            return (
-                Target {
-                    param: coordinate_param(latitude, longitude, &name),
-                    detail: format!("{latitude:.4}, {longitude:.4}"),
-                    name,
+            let given = query.name.clone().filter(|name| !name.trim().is_empty());
+            let nearest = match given {
+                Some(_) => None,
+                None => weather.nearest(latitude, longitude).await,
+            };
+            let target = match (given, nearest) {
+                // The link stays nameless, so it is named afresh each time
+                // and the distance is never lost. The coordinates are what
+                // was asked for, so they stay in sight beside its region.
+                (None, Some(place)) => Target {
+                    param: coordinate_param(latitude, longitude, ""),
+                    named_from_mi: Some(open_meteo::distance_miles(
+                        latitude,
+                        longitude,
+                        place.latitude,
+                        place.longitude,
+                    )),
+                    detail: if place.detail.is_empty() {
+                        format!("{latitude:.4}, {longitude:.4}")
+                    } else {
+                        format!("{}, {latitude:.4}, {longitude:.4}", place.detail)
+                    },
+                    name: place.name,
                     latitude,
                     longitude,
                     pin: None,
                 },
-                Vec::new(),
-                None,
-            );
+                (given, _) => {
+                    let name = given.unwrap_or_else(|| format!("{latitude:.3}, {longitude:.3}"));
+                    Target {
+                        param: coordinate_param(latitude, longitude, &name),
+                        detail: format!("{latitude:.4}, {longitude:.4}"),
+                        name,
+                        latitude,
+                        longitude,
+                        pin: None,
+                        named_from_mi: None,
+                    }
+                }
+            };
+            return (target, Vec::new(), None);
         }
     }
 
@@ -125,7 +284,7 @@ async fn resolve(query: &WeatherQuery) -> (Target, Vec<Alternate>, Option<String
         .map(|query| query.trim())
         .filter(|query| !query.is_empty())
     {
-        return match open_meteo::geocode(search).await {
+        return match weather.geocode(search).await {
             Ok(places) => {
                 let target = Target::from_place(&places[0]);
                 // Everything after the best match becomes a "did you mean".
@@ -144,18 +303,19 @@ async fn resolve(query: &WeatherQuery) -> (Target, Vec<Alternate>, Option<String
                 (target, alternates, None)
             }
             Err(err) => (
-                Target::from_pin(locations::home()),
+                Target::from_pin(locations::pinned().home()),
                 Vec::new(),
                 Some(err.to_string()),
             ),
         };
     }
 
+    let pins = locations::pinned();
     let pin = query
         .loc
         .as_deref()
-        .and_then(locations::find)
-        .unwrap_or_else(locations::home);
+        .and_then(|slug| pins.find(slug))
+        .unwrap_or_else(|| pins.home());
     (Target::from_pin(pin), Vec::new(), None)
 }
 
@@ -205,6 +365,26 @@ fn clock_label(iso: &str) -> String {
     format!("{display}:{minute} {suffix}")
 }
 
+/// `2026-08-02` -> `Sun`, by Sakamoto's method: the one thing here that is
+/// not pure string slicing, and still not worth a date library.
+fn weekday_of(date: &str) -> Option<&'static str> {
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    const NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+    let mut year: i32 = date.get(..4)?.parse().ok()?;
+    let month: usize = date.get(5..7)?.parse().ok()?;
+    let day: i32 = date.get(8..10)?.parse().ok()?;
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    if month < 3 {
+        year -= 1;
+    }
+    let index =
+        (year + year / 4 - year / 100 + year / 400 + OFFSETS[month - 1] + day).rem_euclid(7);
+    Some(NAMES[index as usize])
+}
+
 fn twelve_hour(hour: u32) -> (u32, &'static str) {
     match hour {
         0 => (12, "AM"),
@@ -216,32 +396,60 @@ fn twelve_hour(hour: u32) -> (u32, &'static str) {
 
 // ==================== Derived hourly data ====================
 
+/// One forecast hour in the comfort model's terms.
+fn conditions(raw: &Hour) -> Conditions {
+    Conditions {
+        air: raw.air,
+        relative_humidity: raw.relative_humidity,
+        wind: raw.wind,
+        direct_normal: raw.direct_normal,
+        direct_horizontal: raw.direct_horizontal,
+        diffuse: raw.diffuse,
+        cloud_cover: raw.cloud_cover,
+        sunlit_fraction: raw.sunshine_seconds / 3600.0,
+        solar_elevation: raw.sun.elevation_deg,
+    }
+}
+
 /// One hour, with the comfort model already applied.
 #[derive(Clone, Copy)]
 struct Modelled<'a> {
     raw: &'a Hour,
     hour: u32,
     felt: Felt,
+    /// The comfort scale the reader asked for.
+    scale: &'static Profile,
 }
 
 impl<'a> Modelled<'a> {
-    fn new(raw: &'a Hour) -> Option<Self> {
+    fn new(raw: &'a Hour, activity: Activity, scale: &'static Profile) -> Option<Self> {
         Some(Modelled {
             hour: hour_of(&raw.time)?,
-            felt: comfort::felt(&Conditions {
-                air: raw.air,
-                relative_humidity: raw.relative_humidity,
-                wind: raw.wind,
-                direct_normal: raw.direct_normal,
-                direct_horizontal: raw.direct_horizontal,
-                diffuse: raw.diffuse,
-                cloud_cover: raw.cloud_cover,
-                sunlit_fraction: raw.sunshine_seconds / 3600.0,
-            }),
+            felt: comfort::felt(&conditions(raw), activity),
             raw,
+            scale,
         })
     }
 
+    /// One reading of the hour, in degrees.
+    fn reading(&self, exposure: Exposure) -> Temperature {
+        match exposure {
+            Exposure::Sun => self.felt.sun,
+            Exposure::Shade => self.felt.shade,
+            Exposure::Typical => self.felt.typical,
+        }
+    }
+
+    /// One reading of the hour, placed on the reader's scale.
+    fn score(&self, exposure: Exposure) -> Score {
+        self.scale.score(self.reading(exposure))
+    }
+
+    /// The same hour by another model, for the cross-check.
+    fn felt_by(&self, model: &dyn ThermalModel, activity: Activity) -> Felt {
+        comfort::felt_with(model, &conditions(self.raw), activity)
+    }
+
     /// Whether there is enough beam for "in sun" to mean anything.
     fn sunlit(&self) -> bool {
         self.raw.direct_normal > 5.0
@@ -252,12 +460,8 @@ impl<'a> Modelled<'a> {
     /// The hourly table needs no probe: its own row already spells out the air
     /// temperature, wind, cloud and rain that produced the figure.
     fn probe(&self, exposure: Exposure) -> Probe {
-        let felt = match exposure {
-            Exposure::Sun => self.felt.sun,
-            Exposure::Shade => self.felt.shade,
-            Exposure::Typical => self.felt.typical,
-        };
-        let score = scale::score(felt);
+        let felt = self.reading(exposure);
+        let score = self.score(exposure);
         Probe {
             level: score.level(),
             score: score.to_string(),
@@ -282,12 +486,17 @@ enum Exposure {
 }
 
 /// Every modelled hour belonging to one local date.
-fn hours_on<'a>(forecast: &'a Forecast, date: &str) -> Vec<Modelled<'a>> {
+fn hours_on<'a>(
+    forecast: &'a Forecast,
+    date: &str,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Vec<Modelled<'a>> {
     forecast
         .hours
         .iter()
         .filter(|hour| date_of(&hour.time) == date)
-        .filter_map(Modelled::new)
+        .filter_map(|hour| Modelled::new(hour, activity, scale))
         .collect()
 }
 
@@ -303,12 +512,37 @@ fn daylight_window(day: &Day) -> (u32, u32) {
     (start, end)
 }
 
+/// A day's hours inside its daylight window, or every hour it has if none
+/// fall inside.
+fn daylight_hours<'a>(
+    forecast: &'a Forecast,
+    day: &Day,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Vec<Modelled<'a>> {
+    let (start, end) = daylight_window(day);
+    let all = hours_on(forecast, &day.date, activity, scale);
+    let visible: Vec<Modelled> = all
+        .iter()
+        .copied()
+        .filter(|hour| hour.hour >= start && hour.hour <= end)
+        .collect();
+    if visible.is_empty() {
+        all
+    } else {
+        visible
+    }
+}
+
 /// Summary statistics over the visible window.
 struct Extremes {
     peak_sun_score: Score,
     min_shade_score: Score,
     /// The middle of the day's exposure-weighted hours.
     typical_score: Score,
+    /// The warmest exposure-weighted hour: the peak a person actually meets,
+    /// where `peak_sun_score` is the sunlit ceiling.
+    warmest_score: Score,
     air_high: Temperature,
     max_wind: Speed,
     max_gust: Speed,
@@ -325,7 +559,7 @@ struct Extremes {
 /// not offer in reverse. Floored at the typical hour so a brief sunny peak
 /// cannot strip the day down to shorts.
 fn wear_for(typical: Score, warmest: Score) -> Score {
-    let one_below = Score::from_value(warmest.value() - 1.0);
+    let one_below = warmest.with_value(warmest.value() - 1.0);
     if typical > one_below {
         typical
     } else {
@@ -352,22 +586,37 @@ fn wind_phrase(wind: Speed, gust: Speed) -> String {
 /// rather than forecasting, and saying so would put a raincoat in every day.
 const RAIN_WORTH_MENTIONING: f64 = 30.0;
 
-/// When rain is likely, phrased the way it changes a plan: the hour it starts,
-/// and the hour it stops. A daily total answers a question nobody asked.
-fn rain_window(hours: &[Modelled]) -> Option<String> {
+/// The stretch of a day in which rain is worth planning around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RainSpell {
+    /// First and last local hours at or above [`RAIN_WORTH_MENTIONING`].
+    first: u32,
+    last: u32,
+    /// Highest chance anywhere in the hours given, in percent.
+    peak: i32,
+}
+
+fn rain_spell(hours: &[Modelled]) -> Option<RainSpell> {
     let wet: Vec<u32> = hours
         .iter()
         .filter(|hour| hour.raw.precipitation_probability >= RAIN_WORTH_MENTIONING)
         .map(|hour| hour.hour)
         .collect();
-    let first = *wet.first()?;
-    let last = *wet.last()?;
+    Some(RainSpell {
+        first: *wet.first()?,
+        last: *wet.last()?,
+        peak: hours
+            .iter()
+            .map(|hour| hour.raw.precipitation_probability)
+            .fold(0.0, f64::max)
+            .round() as i32,
+    })
+}
 
-    let peak = hours
-        .iter()
-        .map(|hour| hour.raw.precipitation_probability)
-        .fold(0.0, f64::max)
-        .round() as i32;
+/// When rain is likely, phrased the way it changes a plan: the hour it starts,
+/// and the hour it stops. A daily total answers a question nobody asked.
+fn rain_window(hours: &[Modelled]) -> Option<String> {
+    let RainSpell { first, last, peak } = rain_spell(hours)?;
 
     Some(if first == last {
         format!(
@@ -408,7 +657,7 @@ fn share_in_the_same_outfit(hours: &[Modelled], typical: Score) -> i32 {
     }
     let matching = hours
         .iter()
-        .filter(|hour| scale::score(hour.felt.typical).level() == typical.level())
+        .filter(|hour| hour.score(Exposure::Typical).level() == typical.level())
         .count();
     (matching as f64 / hours.len() as f64 * 100.0).round() as i32
 }
@@ -419,13 +668,17 @@ fn extremes(hours: &[Modelled]) -> Option<Extremes> {
         // mode here and short-circuits the whole struct.
         peak_sun_score: hours
             .iter()
-            .map(|h| scale::score(h.felt.sun))
+            .map(|h| h.score(Exposure::Sun))
             .reduce(Score::max)?,
         min_shade_score: hours
             .iter()
-            .map(|h| scale::score(h.felt.shade))
+            .map(|h| h.score(Exposure::Shade))
             .reduce(Score::min)?,
-        typical_score: median(hours.iter().map(|h| scale::score(h.felt.typical)))?,
+        typical_score: median(hours.iter().map(|h| h.score(Exposure::Typical)))?,
+        warmest_score: hours
+            .iter()
+            .map(|h| h.score(Exposure::Typical))
+            .reduce(Score::max)?,
         air_high: hours.iter().map(|h| h.raw.air).reduce(Temperature::max)?,
         max_wind: hours.iter().map(|h| h.raw.wind).reduce(Speed::max)?,
         max_gust: hours.iter().map(|h| h.raw.gust).reduce(Speed::max)?,
@@ -438,6 +691,61 @@ fn extremes(hours: &[Modelled]) -> Option<Extremes> {
     })
 }
 
+/// The hours a stretch of the day is summed up by, each a real hour so every
+/// headline number has data behind it.
+struct Pivots<'a> {
+    extremes: Extremes,
+    /// Coldest out of the sun.
+    coldest: Modelled<'a>,
+    /// Warmest exposure-weighted hour. Not the sunlit ceiling: under a solid
+    /// overcast that ceiling is a place nobody actually stands.
+    warmest: Modelled<'a>,
+    /// The hour nearest the typical score.
+    representative: Modelled<'a>,
+}
+
+impl<'a> Pivots<'a> {
+    fn of(hours: &[Modelled<'a>]) -> Option<Self> {
+        let extremes = extremes(hours)?;
+        let coldest = *hours
+            .iter()
+            .min_by(|a, b| a.felt.shade.partial_cmp(&b.felt.shade).expect("no NaN"))?;
+        let warmest = *hours
+            .iter()
+            .max_by(|a, b| a.felt.typical.partial_cmp(&b.felt.typical).expect("no NaN"))?;
+        let representative = *hours.iter().min_by(|a, b| {
+            let distance = |hour: &Modelled| {
+                (hour.score(Exposure::Typical).value() - extremes.typical_score.value()).abs()
+            };
+            distance(a).partial_cmp(&distance(b)).expect("no NaN")
+        })?;
+        Some(Pivots {
+            extremes,
+            coldest,
+            warmest,
+            representative,
+        })
+    }
+
+    /// The outfit to put on for these hours.
+    fn wear(&self) -> Score {
+        wear_for(self.extremes.typical_score, self.extremes.warmest_score)
+    }
+
+    fn coolest(&self) -> Score {
+        self.coldest.score(Exposure::Shade)
+    }
+
+    /// What to carry on top of [`Self::wear`], if the coolest hour calls for
+    /// more than it and there is anything that can be carried.
+    fn carry(&self) -> Option<&'static str> {
+        let coolest = self.coolest();
+        (coolest.level() < self.wear().level())
+            .then(|| coolest.layer())
+            .flatten()
+    }
+}
+
 // ==================== Chart ====================
 
 const CHART_WIDTH: f64 = 320.0;
@@ -518,20 +826,33 @@ struct ChartInput<'a> {
     now: &'a str,
 }
 
-impl Chart {
-    fn build(input: &ChartInput) -> Option<Chart> {
-        let (start, end) = input.window;
-        if end <= start || input.today.len() < 2 {
+/// The geometry every day chart shares: which hours run across and which
+/// slice of the 0-10 scale runs up. Anything drawn against the same `Axes`
+/// can be compared by eye, which is the whole point of putting two lines on
+/// one chart.
+struct Axes {
+    start: u32,
+    end: u32,
+    floor: f64,
+    ceiling: f64,
+}
+
+impl Axes {
+    /// Fits the vertical range to every `(low, high)` pair given, in scale
+    /// points, padded so a flat day does not look dramatic.
+    fn fit(window: (u32, u32), ranges: impl Iterator<Item = (f64, f64)>) -> Option<Axes> {
+        let (start, end) = window;
+        if end <= start {
             return None;
         }
-
-        // Both days share one scale, otherwise the comparison lies. The axis is
-        // the 0-10 comfort scale, the same units as the headline.
         let mut lowest = f64::MAX;
         let mut highest = f64::MIN;
-        for hour in input.today.iter().chain(input.yesterday.iter()) {
-            lowest = lowest.min(scale::score(hour.felt.shade).value());
-            highest = highest.max(scale::score(hour.felt.sun).value());
+        for (low, high) in ranges {
+            lowest = lowest.min(low);
+            highest = highest.max(high);
+        }
+        if lowest > highest {
+            return None;
         }
         let mut floor = (lowest - 0.3).floor();
         let mut ceiling = (highest + 0.3).ceil();
@@ -540,92 +861,133 @@ impl Chart {
             floor -= padding;
             ceiling += padding;
         }
-        floor = floor.max(0.0);
-        ceiling = ceiling.min(10.0);
+        Some(Axes {
+            start,
+            end,
+            floor: floor.max(0.0),
+            ceiling: ceiling.min(10.0),
+        })
+    }
 
-        let span = f64::from(end - start);
-        let x_at = |hour: f64| {
-            PLOT_LEFT
-                + ((hour - f64::from(start)) / span).clamp(0.0, 1.0) * (PLOT_RIGHT - PLOT_LEFT)
-        };
-        let y_at = |degrees: f64| {
-            PLOT_BOTTOM - ((degrees - floor) / (ceiling - floor)) * (PLOT_BOTTOM - PLOT_TOP)
-        };
+    fn x_at(&self, hour: f64) -> f64 {
+        let span = f64::from(self.end - self.start);
+        PLOT_LEFT
+            + ((hour - f64::from(self.start)) / span).clamp(0.0, 1.0) * (PLOT_RIGHT - PLOT_LEFT)
+    }
 
-        let trace = |hours: &[Modelled], pick: fn(&Modelled) -> Temperature| {
-            hours
-                .iter()
-                .map(|hour| {
-                    format!(
-                        "{},{}",
-                        round1(x_at(f64::from(hour.hour))),
-                        round1(y_at(scale::score(pick(hour)).value()))
-                    )
-                })
-                .collect::<Vec<String>>()
-                .join(" ")
-        };
-        // Sun edge left to right, shade edge back again: one closed outline.
-        // Only yesterday needs it -- today is drawn as two live edges.
-        let band = |hours: &[Modelled]| {
-            let mut shade: Vec<&str> = Vec::new();
-            let shade_trace = trace(hours, |hour| hour.felt.shade);
-            shade.extend(shade_trace.split(' ').rev());
-            format!("{} {}", trace(hours, |hour| hour.felt.sun), shade.join(" "))
-        };
+    fn y_at(&self, points: f64) -> f64 {
+        PLOT_BOTTOM
+            - ((points - self.floor) / (self.ceiling - self.floor)) * (PLOT_BOTTOM - PLOT_TOP)
+    }
 
-        let whole_points = floor.ceil() as i32..=ceiling.floor() as i32;
-        let grid = whole_points
-            .clone()
+    /// SVG `points` for one reading of each hour.
+    fn trace(&self, hours: &[Modelled], exposure: Exposure) -> String {
+        hours
+            .iter()
+            .map(|hour| {
+                format!(
+                    "{},{}",
+                    round1(self.x_at(f64::from(hour.hour))),
+                    round1(self.y_at(hour.score(exposure).value()))
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn whole_points(&self) -> std::ops::RangeInclusive<i32> {
+        self.floor.ceil() as i32..=self.ceiling.floor() as i32
+    }
+
+    fn grid(&self) -> Vec<GridLine> {
+        self.whole_points()
             .map(|point| GridLine {
-                y: round1(y_at(f64::from(point))),
+                y: round1(self.y_at(f64::from(point))),
                 label: point,
             })
-            .collect();
+            .collect()
+    }
 
-        // One stripe per whole point, clipped to the plot.
-        let bands = whole_points
+    /// One stripe per whole point, clipped to the plot.
+    fn bands(&self) -> Vec<FeelBand> {
+        self.whole_points()
             .map(|point| {
-                let top = y_at(f64::from(point) + 0.5).max(PLOT_TOP);
-                let bottom = y_at(f64::from(point) - 0.5).min(PLOT_BOTTOM);
+                let top = self.y_at(f64::from(point) + 0.5).max(PLOT_TOP);
+                let bottom = self.y_at(f64::from(point) - 0.5).min(PLOT_BOTTOM);
                 FeelBand {
                     y: round1(top),
                     height: round1((bottom - top).max(0.0)),
                     level: point.clamp(0, 10) as u8,
                 }
             })
-            .collect();
+            .collect()
+    }
 
-        let ticks = (start..=end)
+    fn ticks(&self) -> Vec<AxisTick> {
+        (self.start..=self.end)
             .filter(|hour| hour % 3 == 0)
             .map(|hour| AxisTick {
-                x: round1(x_at(f64::from(hour))),
+                x: round1(self.x_at(f64::from(hour))),
                 label: short_hour_label(hour),
             })
-            .collect();
+            .collect()
+    }
 
+    /// A vertical rule at a local time, if it falls inside the window.
+    fn marker(&self, iso: &str, label: String) -> Option<Marker> {
         // Captions are half a caption-width in from either edge so long labels
         // like "sunset 8:17 PM" stay inside the viewBox.
         const CAPTION_INSET: f64 = 46.0;
-        let marker_at = |iso: &str, label: String| {
-            fractional_hour(iso)
-                .filter(|hour| *hour >= f64::from(start) && *hour <= f64::from(end))
+        fractional_hour(iso)
+            .filter(|hour| *hour >= f64::from(self.start) && *hour <= f64::from(self.end))
+            .map(|hour| {
+                let x = round1(self.x_at(hour));
+                Marker {
+                    x,
+                    label_x: round1(x.clamp(CAPTION_INSET, CHART_WIDTH - CAPTION_INSET)),
+                    label,
+                    show_label: true,
+                }
+            })
+    }
+}
+
+impl Chart {
+    fn build(input: &ChartInput) -> Option<Chart> {
+        if input.today.len() < 2 {
+            return None;
+        }
+
+        // Both days share one scale, otherwise the comparison lies. The axis is
+        // the 0-10 comfort scale, the same units as the headline.
+        let axes = Axes::fit(
+            input.window,
+            input
+                .today
+                .iter()
+                .chain(input.yesterday.iter())
                 .map(|hour| {
-                    let x = round1(x_at(hour));
-                    Marker {
-                        x,
-                        label_x: round1(x.clamp(CAPTION_INSET, CHART_WIDTH - CAPTION_INSET)),
-                        label,
-                        show_label: true,
-                    }
-                })
+                    (
+                        hour.score(Exposure::Shade).value(),
+                        hour.score(Exposure::Sun).value(),
+                    )
+                }),
+        )?;
+
+        // Sun edge left to right, shade edge back again: one closed outline.
+        // Only yesterday needs it -- today is drawn as two live edges.
+        let band = |hours: &[Modelled]| {
+            let mut shade: Vec<&str> = Vec::new();
+            let shade_trace = axes.trace(hours, Exposure::Shade);
+            shade.extend(shade_trace.split(' ').rev());
+            format!("{} {}", axes.trace(hours, Exposure::Sun), shade.join(" "))
         };
 
-        let sunset = marker_at(
+        let sunset = axes.marker(
             input.sunset,
             format!("sunset {}", clock_label(input.sunset)),
         );
-        let now = marker_at(input.now, "now".to_owned()).map(|mut marker| {
+        let now = axes.marker(input.now, "now".to_owned()).map(|mut marker| {
             // Late in the day "now" and "sunset" sit on top of each other;
             // sunset is the one that changes the decision, so it wins.
             marker.show_label = sunset
@@ -645,15 +1007,15 @@ impl Chart {
             axis_label_y: AXIS_LABEL_Y,
             marker_label_y: MARKER_LABEL_Y,
             grid_label_x: PLOT_LEFT - 4.0,
-            sun_line: trace(input.today, |hour| hour.felt.sun),
-            shade_line: trace(input.today, |hour| hour.felt.shade),
+            sun_line: axes.trace(input.today, Exposure::Sun),
+            shade_line: axes.trace(input.today, Exposure::Shade),
             // A handful of stray hours would draw a misleading stub, so only
             // show yesterday when it covers a comparable stretch of the day.
             yesterday_band: (input.yesterday.len() >= input.today.len() / 2)
                 .then(|| band(input.yesterday)),
-            grid,
-            bands,
-            ticks,
+            grid: axes.grid(),
+            bands: axes.bands(),
+            ticks: axes.ticks(),
             sunset,
             now,
         })
@@ -701,6 +1063,8 @@ struct Probe {
 }
 
 struct HourRow {
+    /// Local `YYYY-MM-DDTHH:MM`, for the JSON view; the page shows `label`.
+    time: String,
     label: String,
     sun_level: u8,
     shade_level: u8,
@@ -716,6 +1080,11 @@ struct HourRow {
     humidity: i32,
     cloud: i32,
     rain_chance: i32,
+    /// Where the sun was over the hour, in whole degrees, for the JSON view.
+    sun_elevation_deg: i32,
+    sun_azimuth_deg: i32,
+    /// The radiation behind the scores was estimated from cloud cover.
+    estimated: bool,
     is_now: bool,
     past: bool,
     /// The sun goes down between this row and the next.
@@ -741,10 +1110,27 @@ struct Comparison {
     direction: &'static str,
 }
 
+/// The headline pair again by the Universal Thermal Climate Index, so the page
+/// can show where a second model lands on the same hours.
+struct CrossCheck {
+    model: &'static str,
+    low_f: i32,
+    high_f: i32,
+}
+
 struct Report {
+    /// Local `YYYY-MM-DD` of the day being reported.
+    date: String,
+    /// Local `YYYY-MM-DDTHH:MM` the forecast was read at.
+    as_of: String,
+
     // Headline: the range and swing, not the air temperature.
     /// `the rest of today` or `today`, depending on how much is left of it.
     headline_scope: &'static str,
+    /// Who the numbers are for. Every score below is already adjusted.
+    activity: Activity,
+    /// Whose ladder the scores are read on.
+    scale: &'static Profile,
     /// What the rest of the day mostly feels like, and how much of it that
     /// covers. This is the headline; the bounds are context for it.
     typical: Probe,
@@ -760,6 +1146,8 @@ struct Report {
     chart: Option<Chart>,
 
     hours: Vec<HourRow>,
+    /// Whether any of `hours` has estimated radiation, for the footnote.
+    hours_estimated: bool,
     sunrise_label: String,
     sunset_label: String,
 
@@ -772,10 +1160,27 @@ struct Report {
     comparisons: Vec<Comparison>,
     has_yesterday: bool,
 
+    /// Today and the days after it, one line each.
+    outlook: Vec<outlook::OutlookDay>,
+
+    cross_check: CrossCheck,
+
+    /// Local time minus UTC, for anything that has to leave local time.
+    utc_offset_seconds: i32,
+
     grid_distance_mi: String,
     grid_elevation_ft: i32,
     timezone: String,
     updated_label: String,
+
+    /// Whose forecast this is, e.g. `Open-Meteo`.
+    provider: &'static str,
+    /// Why it is not Open-Meteo's, when it is not.
+    fallback: Option<String>,
+    /// What that provider does not publish and was estimated here instead.
+    estimated: Vec<&'static str>,
+    /// The forecast is older than it should be; `updated_label` says how old.
+    stale: bool,
 }
 
 #[derive(Template, WebTemplate)]
@@ -784,9 +1189,23 @@ struct WeatherTemplate {
     path: String,
     place_name: String,
     place_detail: String,
+    /// Miles to the place the pin was named for, when it was named for the
+    /// nearest one.
+    place_distance_mi: Option<String>,
     place_param: String,
     search_query: String,
+    /// Whether the place is a pin, and so has its forecasts marked.
+    pinned: bool,
     pins: Vec<Pin>,
+    activities: Vec<Pin>,
+    activity_slug: &'static str,
+    /// `&activity=...` for links that should keep it, or empty.
+    activity_param: String,
+    scales: Vec<Pin>,
+    /// `&scale=...` for the calendar link, or empty.
+    scale_param: String,
+    /// Whether a feedback vote was just recorded.
+    noted: bool,
     alternates: Vec<Alternate>,
     report: Option<Report>,
     error: Option<String>,
@@ -855,7 +1274,7 @@ fn score_row(label: &str, today: Score, yesterday: Score) -> Comparison {
     }
 }
 
-/// The answer to the question the page exists for, in at most four sentences.
+/// The answer to the question the page exists for, in a handful of sentences.
 ///
 /// Two decisions, from the three numbers at the top.
 ///
@@ -927,6 +1346,26 @@ fn verdict(input: &VerdictInput) -> Vec<String> {
         sentences.push(rain.clone());
     }
 
+    // Every number above has already moved; this says why, so a run's
+    // reading is not mistaken for the day's.
+    let reference = Activity::default();
+    if input.activity != reference {
+        let direction = if input.activity.met() > reference.met() {
+            "warmer"
+        } else {
+            "cooler"
+        };
+        sentences.push(format!(
+            "Scored for {}, which runs {direction} than the walk the scale assumes{}.",
+            input.activity.name(),
+            if input.activity.relative_wind().meters_per_second() >= 2.0 {
+                " and makes its own wind"
+            } else {
+                ""
+            }
+        ));
+    }
+
     sentences
 }
 
@@ -943,9 +1382,15 @@ struct VerdictInput {
     max_wind: Speed,
     max_gust: Speed,
     rain: Option<String>,
+    activity: Activity,
 }
 
-fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
+fn build_report(
+    forecast: &Forecast,
+    target: &Target,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Option<Report> {
     let today_date = date_of(&forecast.current_time);
     let today_index = forecast
         .days
@@ -959,7 +1404,7 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
     let (start, end) = daylight_window(today);
     let in_window = |hour: &Modelled| hour.hour >= start && hour.hour <= end;
 
-    let all_today = hours_on(forecast, &today.date);
+    let all_today = hours_on(forecast, &today.date, activity, scale);
     let visible: Vec<Modelled> = all_today.iter().copied().filter(in_window).collect();
     // Fall back to whatever the day has, rather than rendering an empty page.
     let visible = if visible.is_empty() {
@@ -969,7 +1414,7 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
     };
 
     let all_yesterday = yesterday
-        .map(|day| hours_on(forecast, &day.date))
+        .map(|day| hours_on(forecast, &day.date, activity, scale))
         .unwrap_or_default();
     let yesterday_visible: Vec<Modelled> =
         all_yesterday.iter().copied().filter(in_window).collect();
@@ -994,7 +1439,8 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
     let looking_ahead = remaining.len() >= 3;
     let decision = if looking_ahead { &remaining } else { &visible };
 
-    let today_extremes = extremes(decision)?;
+    let pivots = Pivots::of(decision)?;
+    let today_extremes = &pivots.extremes;
     // The day-versus-day table compares whole days, so it keeps the full window.
     let full_day = extremes(&visible)?;
     let yesterday_extremes = extremes(&yesterday_visible);
@@ -1002,11 +1448,12 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
     let hours: Vec<HourRow> = visible
         .iter()
         .map(|hour| HourRow {
+            time: hour.raw.time.clone(),
             label: hour_label(hour.hour),
-            sun_level: scale::score(hour.felt.sun).level(),
-            shade_level: scale::score(hour.felt.shade).level(),
-            sun_score: scale::score(hour.felt.sun).to_string(),
-            shade_score: scale::score(hour.felt.shade).to_string(),
+            sun_level: hour.score(Exposure::Sun).level(),
+            shade_level: hour.score(Exposure::Shade).level(),
+            sun_score: hour.score(Exposure::Sun).to_string(),
+            shade_score: hour.score(Exposure::Shade).to_string(),
             sun_f: hour.felt.sun.round_fahrenheit(),
             shade_f: hour.felt.shade.round_fahrenheit(),
             has_sun: hour.sunlit(),
@@ -1015,6 +1462,9 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
             humidity: hour.raw.relative_humidity.round() as i32,
             cloud: hour.raw.cloud_cover.round() as i32,
             rain_chance: hour.raw.precipitation_probability.round() as i32,
+            sun_elevation_deg: hour.raw.sun.elevation_deg.round() as i32,
+            sun_azimuth_deg: hour.raw.sun.azimuth_deg.round() as i32,
+            estimated: hour.raw.radiation_estimated,
             is_now: now_hour == Some(hour.hour),
             past: now_hour.is_some_and(|now| hour.hour < now),
             sunset_follows: hour.hour == sunset_hour,
@@ -1096,24 +1546,19 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
 
     // The headline numbers each belong to a specific hour, and the probe shows
     // which one and what the air was doing at the time.
-    let coldest = decision
-        .iter()
-        .min_by(|a, b| a.felt.shade.partial_cmp(&b.felt.shade).expect("no NaN"))?;
-    // The peak is the exposure-weighted hour, not the sunlit ceiling: under a
-    // solid overcast that ceiling is a place nobody actually stands.
-    let warmest = decision
-        .iter()
-        .max_by(|a, b| a.felt.typical.partial_cmp(&b.felt.typical).expect("no NaN"))?;
-    // A real hour near the middle, so the headline number has data behind it.
-    let representative = decision.iter().min_by(|a, b| {
-        let distance = |hour: &Modelled| {
-            (scale::score(hour.felt.typical).value() - today_extremes.typical_score.value()).abs()
-        };
-        distance(a).partial_cmp(&distance(b)).expect("no NaN")
-    })?;
+    let Pivots {
+        coldest,
+        warmest,
+        representative,
+        ..
+    } = &pivots;
     let swing_f = warmest.felt.typical.round_fahrenheit() - coldest.felt.shade.round_fahrenheit();
 
     Some(Report {
+        date: today.date.clone(),
+        as_of: forecast.current_time.clone(),
+        activity,
+        scale,
         headline_scope: if looking_ahead {
             "the rest of today"
         } else {
@@ -1128,22 +1573,21 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
         // swing always equals the two numbers printed beside it.
         swing_f,
         verdict: verdict(&VerdictInput {
-            wear: wear_for(
-                today_extremes.typical_score,
-                scale::score(warmest.felt.typical),
-            ),
-            warmest: scale::score(warmest.felt.typical),
+            wear: pivots.wear(),
+            warmest: warmest.score(Exposure::Typical),
             warmest_degrees: warmest.felt.typical.round_fahrenheit(),
             warmest_hour: hour_label(warmest.hour),
-            coolest: scale::score(coldest.felt.shade),
+            coolest: pivots.coolest(),
             coolest_degrees: coldest.felt.shade.round_fahrenheit(),
             sunset: sunset_label.clone(),
             max_wind: today_extremes.max_wind,
             max_gust: today_extremes.max_gust,
             rain: rain_window(decision),
+            activity,
         }),
         now,
         chart,
+        hours_estimated: hours.iter().any(|hour| hour.estimated),
         hours,
         sunrise_label: clock_label(&today.sunrise),
         sunset_label,
@@ -1153,6 +1597,12 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
         rain_total_in: format!("{:.2}", full_day.total_rain_inches),
         comparisons,
         has_yesterday: yesterday_extremes.is_some(),
+        outlook: outlook::outlook(forecast, today_index, activity, scale),
+        cross_check: CrossCheck {
+            model: Utci.name(),
+            low_f: coldest.felt_by(&Utci, activity).shade.round_fahrenheit(),
+            high_f: warmest.felt_by(&Utci, activity).typical.round_fahrenheit(),
+        },
         grid_distance_mi: format!(
             "{:.1}",
             open_meteo::distance_miles(
@@ -1164,20 +1614,40 @@ fn build_report(forecast: &Forecast, target: &Target) -> Option<Report> {
         ),
         grid_elevation_ft: (forecast.grid_elevation * 3.280_84).round() as i32,
         timezone: forecast.timezone_abbreviation.clone(),
+        utc_offset_seconds: forecast.utc_offset_seconds,
         updated_label: clock_label(&forecast.current_time),
+        provider: forecast.provider,
+        fallback: forecast.fallback.clone(),
+        estimated: forecast.estimated.clone(),
+        stale: forecast.stale,
     })
 }
 
 // ==================== Handler ====================
 
-pub async fn weather(OriginalUri(uri): OriginalUri, Query(query): Query<WeatherQuery>) -> Response {
-    let (target, alternates, mut error) = resolve(&query).await;
-
-    let report = match open_meteo::forecast(target.latitude, target.longitude).await {
+/// The page, the same report as JSON when the request asks for it by name, or
+/// as plain text for a terminal that asked for neither.
+pub async fn weather(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<WeatherQuery>,
+    Query(options): Query<text::TextOptions>,
+) -> Response {
+    let (target, mut alternates, mut error) = resolve(&*state.weather, &query).await;
+    let activity = query.activity();
+    let scale = chosen_scale(query.scale.as_deref(), &headers);
+    let remembered = remember_scale(query.scale.as_deref());
+
+    let report = match state
+        .weather
+        .forecast(target.latitude, target.longitude)
+        .await
+    {
         Ok(forecast) => {
-            let report = build_report(&forecast, &target);
+            let report = build_report(&forecast, &target, activity, scale);
             if report.is_none() {
-                error = Some("Open-Meteo returned no usable hours for today.".to_owned());
+                error = Some(no_usable_hours(&forecast));
             }
             report
         }
@@ -1187,13 +1657,69 @@ pub async fn weather(OriginalUri(uri): OriginalUri, Query(query): Query<WeatherQ
         }
     };
 
-    let pins = locations::PINNED
+    // Same URL, different bodies, so a shared cache must key on the headers.
+    let caching = [
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(if report.is_some() {
+                CACHE_CONTROL
+            } else {
+                UNCACHED
+            }),
+        ),
+        (header::VARY, HeaderValue::from_static("accept, cookie")),
+    ];
+
+    if requested_json(&headers) {
+        let document = json::Document::new(&target, &alternates, report.as_ref(), error.as_deref());
+        return (caching, remembered, document).into_response();
+    }
+
+    if !requested_html(&headers) {
+        let body = text::render(&target, report.as_ref(), error.as_deref(), options.colour());
+        let content_type = [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        )];
+        return (caching, remembered, content_type, body).into_response();
+    }
+
+    // Changing place keeps the activity, and changing activity keeps the place.
+    let carried = activity_param(activity);
+    let pins = locations::pinned()
+        .all()
         .iter()
         .map(|location| Pin {
-            name: location.name.to_owned(),
-            detail: location.detail.to_owned(),
-            href: format!("/weather?loc={}", location.slug),
-            active: target.pin.as_deref() == Some(location.slug),
+            name: location.name.clone(),
+            detail: location.detail.clone(),
+            href: format!("/weather?loc={}{carried}", location.slug),
+            active: target.pin.as_ref() == Some(&location.slug),
+        })
+        .collect();
+    for alternate in &mut alternates {
+        alternate.href.push_str(&carried);
+    }
+    let activities = Activity::ALL
+        .into_iter()
+        .map(|choice| Pin {
+            name: choice.name().to_owned(),
+            detail: format!("{} met", choice.met()),
+            href: format!("/weather?{}{}", target.param, activity_param(choice)),
+            active: choice == activity,
+        })
+        .collect();
+    let scales = scale::profiles()
+        .all()
+        .iter()
+        .map(|choice| Pin {
+            name: choice.name().to_owned(),
+            detail: {
+                // The middle of the ladder says most about where it sits.
+                let (_, word, _, degrees) = choice.key()[5];
+                format!("{word} at {degrees}\u{b0}")
+            },
+            href: format!("/weather?{}{carried}&scale={}", target.param, choice.slug()),
+            active: std::ptr::eq(choice, scale),
         })
         .collect();
 
@@ -1201,13 +1727,22 @@ pub async fn weather(OriginalUri(uri): OriginalUri, Query(query): Query<WeatherQ
         path: uri.path().to_string(),
         place_name: target.name,
         place_detail: target.detail,
+        place_distance_mi: target.named_from_mi.map(|miles| format!("{miles:.1}")),
         place_param: target.param,
         search_query: query.q.unwrap_or_default(),
+        pinned: target.pin.is_some(),
         pins,
+        activities,
+        activity_slug: activity.slug(),
+        activity_param: carried,
+        scales,
+        scale_param: scale_param(scale),
+        noted: query.noted.is_some(),
         alternates,
         report,
         error,
-        key: scale::key()
+        key: scale
+            .key()
             .into_iter()
             .map(|(level, word, advice, degrees)| KeyStep {
                 level,
@@ -1218,19 +1753,17 @@ pub async fn weather(OriginalUri(uri): OriginalUri, Query(query): Query<WeatherQ
             .collect(),
     };
 
-    (
-        [(
-            header::CACHE_CONTROL,
-            HeaderValue::from_static(CACHE_CONTROL),
-        )],
-        page,
-    )
-        .into_response()
+    (caching, remembered, page).into_response()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::fixtures::Mode;
+    use crate::services::gazetteer::{Gazetteer, Order, WithGazetteer};
+    use crate::services::open_meteo::OpenMeteo;
+    use crate::solar::Position;
+    use std::sync::Arc;
 
     fn hour(time: &str, air_c: f64, direct_normal: f64) -> Hour {
         Hour {
@@ -1247,6 +1780,11 @@ mod tests {
             // A 58° solar elevation, near enough for a fixture.
             direct_horizontal: direct_normal * 0.85,
             diffuse: if direct_normal > 0.0 { 90.0 } else { 0.0 },
+            radiation_estimated: false,
+            sun: Position {
+                elevation_deg: if direct_normal > 0.0 { 58.0 } else { -20.0 },
+                azimuth_deg: 180.0,
+            },
         }
     }
 
@@ -1259,7 +1797,7 @@ mod tests {
 
     /// Two days of hourly data: yesterday fogged in, today clear, with the same
     /// air temperatures on both. That is the case the page exists to tell apart.
-    fn forecast() -> Forecast {
+    pub(super) fn forecast() -> Forecast {
         let mut hours = Vec::new();
         for date in ["2026-08-01", "2026-08-02"] {
             for (clock, air_c) in DIURNAL.iter().enumerate() {
@@ -1277,6 +1815,7 @@ mod tests {
             grid_longitude: -122.4457,
             grid_elevation: 65.0,
             timezone_abbreviation: "GMT-7".to_owned(),
+            utc_offset_seconds: -7 * 3600,
             current_time: "2026-08-02T13:15".to_owned(),
             hours,
             days: vec![
@@ -1295,15 +1834,41 @@ mod tests {
                     sunset: "2026-08-02T20:17".to_owned(),
                 },
             ],
+            provider: open_meteo::NAME,
+            estimated: Vec::new(),
+            fallback: None,
+            stale: false,
         }
     }
 
-    fn target() -> Target {
-        Target::from_pin(locations::home())
+    /// The shipped default scale, which every expectation here is written
+    /// against.
+    pub(super) fn standard() -> &'static Profile {
+        scale::profiles().default()
     }
 
-    fn report() -> Report {
-        build_report(&forecast(), &target()).expect("report")
+    pub(super) fn target() -> Target {
+        Target::from_pin(locations::pinned().home())
+    }
+
+    pub(super) fn report() -> Report {
+        build_report(&forecast(), &target(), Activity::default(), standard()).expect("report")
+    }
+
+    #[test]
+    fn no_usable_hours_names_whoever_made_the_forecast() {
+        assert_eq!(
+            no_usable_hours(&forecast()),
+            "Open-Meteo returned no usable hours for today."
+        );
+        let nws = Forecast {
+            provider: crate::services::nws::NAME,
+            ..forecast()
+        };
+        assert_eq!(
+            no_usable_hours(&nws),
+            "The National Weather Service returned no usable hours for today."
+        );
     }
 
     // ---- time helpers ----
@@ -1332,6 +1897,17 @@ mod tests {
         assert_eq!(clock_label("nonsense"), "nonsense");
     }
 
+    #[test]
+    fn names_the_day_of_the_week() {
+        assert_eq!(weekday_of("2026-08-02"), Some("Sun"));
+        assert_eq!(weekday_of("2026-08-04"), Some("Tue"));
+        // January and February count from the previous year.
+        assert_eq!(weekday_of("2027-01-01"), Some("Fri"));
+        assert_eq!(weekday_of("2028-02-29"), Some("Tue"));
+        assert_eq!(weekday_of("2026-13-01"), None);
+        assert_eq!(weekday_of("soon"), None);
+    }
+
     #[test]
     fn window_covers_sunrise_through_after_sunset() {
         let day = &forecast().days[1];
@@ -1380,7 +1956,12 @@ mod tests {
             hour.diffuse = 0.0;
             hour.sunshine_seconds = 0.0;
         }
-        assert_eq!(build_report(&flat, &target()).unwrap().typical_share, 100);
+        assert_eq!(
+            build_report(&flat, &target(), Activity::default(), standard())
+                .unwrap()
+                .typical_share,
+            100
+        );
     }
 
     #[test]
@@ -1406,7 +1987,8 @@ mod tests {
             hour.sunshine_seconds = 180.0;
         }
         let clear = report();
-        let socked_in = build_report(&overcast, &target()).unwrap();
+        let socked_in =
+            build_report(&overcast, &target(), Activity::default(), standard()).unwrap();
 
         assert!(
             socked_in.typical.degrees < clear.typical.degrees,
@@ -1479,7 +2061,8 @@ mod tests {
     fn late_in_the_day_the_headline_falls_back_to_the_whole_day() {
         let mut nearly_over = forecast();
         nearly_over.current_time = "2026-08-02T22:00".to_owned();
-        let report = build_report(&nearly_over, &target()).unwrap();
+        let report =
+            build_report(&nearly_over, &target(), Activity::default(), standard()).unwrap();
         assert_eq!(report.headline_scope, "today");
     }
 
@@ -1490,7 +2073,7 @@ mod tests {
         let midday = report();
         let mut evening = forecast();
         evening.current_time = "2026-08-02T19:00".to_owned();
-        let evening = build_report(&evening, &target()).unwrap();
+        let evening = build_report(&evening, &target(), Activity::default(), standard()).unwrap();
 
         // Midday still has the sunny peak ahead of it; 7 PM does not.
         assert!(midday.high.degrees > evening.high.degrees);
@@ -1531,7 +2114,7 @@ mod tests {
             hour.diffuse = 0.0;
             hour.sunshine_seconds = 0.0;
         }
-        let report = build_report(&steady, &target()).unwrap();
+        let report = build_report(&steady, &target(), Activity::default(), standard()).unwrap();
         assert!(
             report.verdict[0].starts_with("Wear "),
             "{:?}",
@@ -1564,18 +2147,18 @@ mod tests {
     fn a_brief_sunny_peak_does_not_strip_the_day_down_to_shorts() {
         // The Inner Sunset case: a cool day with one bright hour. Dressing for
         // that hour leaves you bare-legged at nine in the evening.
-        let typical = Score::from_value(5.3);
-        let peak = Score::from_value(8.1);
+        let typical = standard().point(5.3);
+        let peak = standard().point(8.1);
         assert_eq!(wear_for(typical, peak).level(), 7);
 
         // A genuinely warm day is still allowed to reach the top of the ladder.
         assert_eq!(
-            wear_for(Score::from_value(8.6), Score::from_value(9.3)).level(),
+            wear_for(standard().point(8.6), standard().point(9.3)).level(),
             9
         );
 
         // And a flat day wears its own outfit rather than one rung under it.
-        let flat = Score::from_value(6.0);
+        let flat = standard().point(6.0);
         assert_eq!(wear_for(flat, flat).level(), 6);
     }
 
@@ -1589,7 +2172,7 @@ mod tests {
             hour.cloud_cover = 100.0;
             hour.sunshine_seconds = 0.0;
         }
-        let report = build_report(&fogged, &target()).unwrap();
+        let report = build_report(&fogged, &target(), Activity::default(), standard()).unwrap();
         // With no beam at all, each hour's sun and shade readings collapse
         // together, so what range is left is the ordinary daily cycle rather
         // than anything the sun is doing.
@@ -1658,7 +2241,7 @@ mod tests {
                 5.0
             };
         }
-        let report = build_report(&showery, &target()).unwrap();
+        let report = build_report(&showery, &target(), Activity::default(), standard()).unwrap();
         let rain = report
             .verdict
             .iter()
@@ -1685,7 +2268,7 @@ mod tests {
             hour.wind = Speed::from_meters_per_second(6.0); // 13 mph
             hour.gust = Speed::from_meters_per_second(14.0); // 31 mph
         }
-        let report = build_report(&blustery, &target()).unwrap();
+        let report = build_report(&blustery, &target(), Activity::default(), standard()).unwrap();
         assert!(
             report
                 .verdict
@@ -1721,7 +2304,7 @@ mod tests {
             .hours
             .retain(|hour| date_of(&hour.time) == "2026-08-02");
         only_today.days.remove(0);
-        let report = build_report(&only_today, &target()).unwrap();
+        let report = build_report(&only_today, &target(), Activity::default(), standard()).unwrap();
         assert!(!report.has_yesterday);
         assert!(report.comparisons.is_empty());
         assert!(report.chart.expect("chart").yesterday_band.is_none());
@@ -1746,11 +2329,33 @@ mod tests {
         assert_eq!(report.grid_elevation_ft, 213);
     }
 
+    #[test]
+    fn hours_with_estimated_radiation_are_flagged() {
+        assert!(!report().hours_estimated);
+
+        let mut patchy = forecast();
+        let noon = patchy
+            .hours
+            .iter_mut()
+            .find(|hour| hour.time == "2026-08-02T12:00")
+            .unwrap();
+        noon.radiation_estimated = true;
+        let report = build_report(&patchy, &target(), Activity::default(), standard()).unwrap();
+        assert!(report.hours_estimated);
+        let flagged: Vec<&str> = report
+            .hours
+            .iter()
+            .filter(|hour| hour.estimated)
+            .map(|hour| hour.time.as_str())
+            .collect();
+        assert_eq!(flagged, ["2026-08-02T12:00"]);
+    }
+
     #[test]
     fn returns_nothing_when_today_is_missing_from_the_response() {
         let mut stale = forecast();
         stale.current_time = "2026-09-09T13:00".to_owned();
-        assert!(build_report(&stale, &target()).is_none());
+        assert!(build_report(&stale, &target(), Activity::default(), standard()).is_none());
     }
 
     // ---- chart ----
@@ -1806,7 +2411,7 @@ mod tests {
     fn chart_hides_the_now_caption_when_it_would_collide_with_sunset() {
         let mut dusk = forecast();
         dusk.current_time = "2026-08-02T20:00".to_owned();
-        let chart = build_report(&dusk, &target())
+        let chart = build_report(&dusk, &target(), Activity::default(), standard())
             .unwrap()
             .chart
             .expect("chart");
@@ -1881,12 +2486,98 @@ mod tests {
             lat: lat.map(str::to_owned),
             lon: lon.map(str::to_owned),
             name: None,
+            activity: None,
+            scale: None,
+            noted: None,
         }
     }
 
+    #[test]
+    fn the_asked_scale_beats_the_remembered_one() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(chosen_scale(None, &headers).slug(), "standard");
+        headers.insert(header::COOKIE, HeaderValue::from_static("scale=runs-hot"));
+        assert_eq!(chosen_scale(None, &headers).slug(), "runs-hot");
+        assert_eq!(
+            chosen_scale(Some("runs-cold"), &headers).slug(),
+            "runs-cold"
+        );
+        // A stale or mistyped slug falls through rather than failing.
+        assert_eq!(chosen_scale(Some("gone"), &headers).slug(), "runs-hot");
+        assert!(remember_scale(Some("gone")).is_none());
+        assert_eq!(scale_param(standard()), "");
+    }
+
+    #[test]
+    fn an_unknown_activity_is_a_walk() {
+        let mut asked = query(None, None, None);
+        assert_eq!(asked.activity(), Activity::Walking);
+        asked.activity = Some("cycling".to_owned());
+        assert_eq!(asked.activity(), Activity::Cycling);
+        asked.activity = Some("parkour".to_owned());
+        assert_eq!(asked.activity(), Activity::Walking);
+        assert_eq!(activity_param(Activity::Walking), "");
+        assert_eq!(activity_param(Activity::Running), "&activity=running");
+    }
+
+    #[test]
+    fn the_cross_check_reads_the_same_hours_by_utci() {
+        let report = report();
+        let check = &report.cross_check;
+        assert_eq!(check.model, Utci.name());
+        assert!(check.low_f <= check.high_f);
+        // A second opinion, not a different day.
+        assert!(
+            (check.low_f - report.low.degrees).abs() < 15,
+            "{}",
+            check.low_f
+        );
+        assert!(
+            (check.high_f - report.high.degrees).abs() < 15,
+            "{}",
+            check.high_f
+        );
+    }
+
+    #[test]
+    fn a_run_scores_the_same_day_warmer_and_says_so() {
+        let points = |printed: &str| printed.parse::<f64>().unwrap();
+        let walk = report();
+        let run = build_report(&forecast(), &target(), Activity::Running, standard()).unwrap();
+        assert!(points(&run.typical.score) > points(&walk.typical.score));
+        assert!(points(&run.low.score) > points(&walk.low.score));
+        assert_ne!(
+            run.chart.as_ref().unwrap().shade_line,
+            walk.chart.as_ref().unwrap().shade_line
+        );
+        assert!(
+            run.verdict
+                .last()
+                .is_some_and(|line| line.starts_with("Scored for running")),
+            "{:?}",
+            run.verdict
+        );
+        assert!(!walk
+            .verdict
+            .iter()
+            .any(|line| line.starts_with("Scored for")));
+
+        let standing =
+            build_report(&forecast(), &target(), Activity::Standing, standard()).unwrap();
+        assert!(points(&standing.typical.score) < points(&walk.typical.score));
+        assert!(standing
+            .verdict
+            .iter()
+            .any(|line| line.contains("runs cooler")));
+    }
+
+    fn replay() -> OpenMeteo {
+        OpenMeteo::new(Mode::Replay)
+    }
+
     #[tokio::test]
     async fn an_empty_query_resolves_to_home() {
-        let (target, alternates, error) = resolve(&query(None, None, None)).await;
+        let (target, alternates, error) = resolve(&replay(), &query(None, None, None)).await;
         assert_eq!(target.name, "Inner Sunset");
         assert_eq!(target.param, "loc=inner-sunset");
         assert!(alternates.is_empty() && error.is_none());
@@ -1894,37 +2585,82 @@ mod tests {
 
     #[tokio::test]
     async fn an_unknown_slug_falls_back_to_home() {
-        let (target, _, error) = resolve(&query(Some("atlantis"), None, None)).await;
+        let (target, _, error) = resolve(&replay(), &query(Some("atlantis"), None, None)).await;
         assert_eq!(target.pin.as_deref(), Some("inner-sunset"));
         assert!(error.is_none());
     }
 
     #[tokio::test]
     async fn a_pinned_slug_resolves_to_that_neighbourhood() {
-        let (target, _, _) = resolve(&query(Some("fidi"), None, None)).await;
+        let (target, _, _) = resolve(&replay(), &query(Some("fidi"), None, None)).await;
         assert_eq!(target.name, "Financial District");
         assert_eq!(target.latitude, 37.7946);
     }
 
     #[tokio::test]
     async fn explicit_coordinates_are_used_verbatim() {
-        let (target, _, _) = resolve(&WeatherQuery {
-            loc: None,
-            q: None,
-            lat: Some("45.5234".to_owned()),
-            lon: Some("-122.6762".to_owned()),
-            name: Some("Portland".to_owned()),
-        })
+        let (target, _, _) = resolve(
+            &replay(),
+            &WeatherQuery {
+                loc: None,
+                q: None,
+                lat: Some("45.5234".to_owned()),
+                lon: Some("-122.6762".to_owned()),
+                name: Some("Portland".to_owned()),
+                activity: None,
+                scale: None,
+                noted: None,
+            },
+        )
         .await;
         assert_eq!(target.name, "Portland");
         assert_eq!(target.latitude, 45.5234);
         assert_eq!(target.param, "lat=45.5234&lon=-122.6762&name=Portland");
     }
 
+    #[tokio::test]
+    async fn bare_coordinates_are_named_for_the_nearest_place() {
+        let gazetteer = Gazetteer::parse(
+            "5378538\tOakland\tOakland\t\t37.80437\t-122.2708\tP\tPPLA2\tUS\t\tCA\t001\t\t\t419267\t13\t14\tAmerica/Los_Angeles\t2024-01-01\n",
+            "US.CA\tCalifornia\tCalifornia\t5332921\n",
+            "",
+        )
+        .unwrap();
+        let weather = WithGazetteer::new(Arc::new(replay()), gazetteer, Order::Fallback);
+
+        let (target, _, _) = resolve(&weather, &query(None, Some("37.8"), Some("-122.2"))).await;
+        assert_eq!(target.name, "Oakland");
+        assert_eq!(target.detail, "California, US, 37.8000, -122.2000");
+        assert_eq!(target.latitude, 37.8);
+        assert_eq!(target.param, "lat=37.8000&lon=-122.2000");
+        let miles = target.named_from_mi.unwrap();
+        assert!((3.5..4.5).contains(&miles), "{miles}");
+
+        // Nowhere near it, or with nothing to look it up in, the coordinates
+        // are the name.
+        let (target, _, _) = resolve(&weather, &query(None, Some("45.5"), Some("-122.7"))).await;
+        assert_eq!(target.name, "45.500, -122.700");
+        assert!(target.named_from_mi.is_none());
+        let (target, _, _) = resolve(&replay(), &query(None, Some("37.8"), Some("-122.2"))).await;
+        assert_eq!(target.name, "37.800, -122.200");
+
+        // A name given with them is never looked up.
+        let (target, _, _) = resolve(
+            &weather,
+            &WeatherQuery {
+                name: Some("Lake Merritt".to_owned()),
+                ..query(None, Some("37.8"), Some("-122.2"))
+            },
+        )
+        .await;
+        assert_eq!(target.name, "Lake Merritt");
+        assert!(target.named_from_mi.is_none());
+    }
+
     #[tokio::test]
     async fn out_of_range_or_unparseable_coordinates_fall_back_to_home() {
         for (lat, lon) in [("999", "0"), ("abc", "-122.0"), ("37.7", "999")] {
-            let (target, _, _) = resolve(&query(None, Some(lat), Some(lon))).await;
+            let (target, _, _) = resolve(&replay(), &query(None, Some(lat), Some(lon))).await;
             assert_eq!(target.pin.as_deref(), Some("inner-sunset"), "{lat},{lon}");
         }
     }
@@ -0,0 +1,199 @@
+//! The week ahead, one line per day.
+//!
+//! Each day goes through exactly the machinery the headline does — the same
+//! daylight window, the same [`Pivots`], the same [`wear_for`](super::wear_for)
+//! rung and the same rain phrasing — so a day in the outlook and the same day
+//! as tomorrow's headline cannot disagree about what to wear. The only
+//! difference is scope: the outlook always takes the whole day, where today's
+//! headline narrows to the hours still ahead.
+
+use super::{
+    daylight_hours, rain_spell, rain_window, weekday_of, Exposure, Pivots, Probe, RainSpell,
+};
+use crate::comfort::Activity;
+use crate::scale::Profile;
+use crate::services::open_meteo::Forecast;
+
+/// Today and six more. Past a week the hourly model is guessing, and a
+/// forecast that cannot say which afternoon is sunny cannot choose a jacket.
+const OUTLOOK_DAYS: usize = 7;
+
+pub(super) struct OutlookDay {
+    /// Local `YYYY-MM-DD`.
+    pub(super) date: String,
+    /// `Today`, `Tomorrow`, then e.g. `Wed 5`.
+    pub(super) label: String,
+    pub(super) typical: Probe,
+    pub(super) low: Probe,
+    pub(super) high: Probe,
+    /// What to put on, e.g. `jeans and a light jacket`.
+    pub(super) wear: &'static str,
+    /// What to carry on top of it, e.g. `a warm jacket`.
+    pub(super) carry: Option<&'static str>,
+    /// When rain is likely, as the verdict phrases it.
+    pub(super) rain: Option<String>,
+    /// The same, as hours, for anything that needs to place it in time.
+    pub(super) rain_spell: Option<RainSpell>,
+    pub(super) air_high_f: i32,
+    pub(super) air_low_f: i32,
+}
+
+fn day_label(position: usize, date: &str) -> String {
+    match position {
+        0 => "Today".to_owned(),
+        1 => "Tomorrow".to_owned(),
+        _ => {
+            let day = date
+                .get(8..10)
+                .and_then(|day| day.parse::<u32>().ok())
+                .unwrap_or_default();
+            match weekday_of(date) {
+                Some(weekday) => format!("{weekday} {day}"),
+                None => date.to_owned(),
+            }
+        }
+    }
+}
+
+/// One line per day from `today_index`, skipping any day without enough
+/// hours to model.
+pub(super) fn outlook(
+    forecast: &Forecast,
+    today_index: usize,
+    activity: Activity,
+    scale: &'static Profile,
+) -> Vec<OutlookDay> {
+    forecast
+        .days
+        .iter()
+        .skip(today_index)
+        .take(OUTLOOK_DAYS)
+        .enumerate()
+        .filter_map(|(position, day)| {
+            let visible = daylight_hours(forecast, day, activity, scale);
+            let pivots = Pivots::of(&visible)?;
+
+            Some(OutlookDay {
+                date: day.date.clone(),
+                label: day_label(position, &day.date),
+                typical: pivots.representative.probe(Exposure::Typical),
+                low: pivots.coldest.probe(Exposure::Shade),
+                high: pivots.warmest.probe(Exposure::Typical),
+                wear: pivots.wear().advice(),
+                carry: pivots.carry(),
+                rain: rain_window(&visible),
+                rain_spell: rain_spell(&visible),
+                air_high_f: day.high.round_fahrenheit(),
+                air_low_f: day.low.round_fahrenheit(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{forecast, standard};
+    use super::*;
+    use crate::services::open_meteo::Day;
+    use crate::units::Temperature;
+
+    /// The fixture, extended with a copy of today for each of the next days.
+    fn week() -> Forecast {
+        let mut week = forecast();
+        let today: Vec<_> = week
+            .hours
+            .iter()
+            .filter(|hour| hour.time.starts_with("2026-08-02"))
+            .cloned()
+            .collect();
+        for date in ["2026-08-03", "2026-08-04", "2026-08-05"] {
+            for hour in &today {
+                let mut copy = hour.clone();
+                copy.time = format!("{date}{}", &hour.time[10..]);
+                week.hours.push(copy);
+            }
+            week.days.push(Day {
+                date: date.to_owned(),
+                high: Temperature::from_celsius(23.0),
+                low: Temperature::from_celsius(12.4),
+                sunrise: format!("{date}T06:15"),
+                sunset: format!("{date}T20:16"),
+            });
+        }
+        week
+    }
+
+    #[test]
+    fn starts_today_and_never_includes_yesterday() {
+        let days = outlook(&week(), 1, Activity::default(), standard());
+        assert_eq!(days.len(), 4);
+        assert_eq!(days[0].date, "2026-08-02");
+        assert_eq!(days[0].label, "Today");
+        assert_eq!(days[1].label, "Tomorrow");
+        assert_eq!(days[2].label, "Tue 4");
+        assert_eq!(days[3].label, "Wed 5");
+    }
+
+    #[test]
+    fn is_capped_at_a_week() {
+        let mut long = week();
+        for offset in 6..=12 {
+            let date = format!("2026-08-{offset:02}");
+            long.days.push(Day {
+                date: date.clone(),
+                ..long.days[1].clone()
+            });
+            for hour in forecast().hours.iter().skip(24) {
+                let mut copy = hour.clone();
+                copy.time = format!("{date}{}", &hour.time[10..]);
+                long.hours.push(copy);
+            }
+        }
+        assert_eq!(
+            outlook(&long, 1, Activity::default(), standard()).len(),
+            OUTLOOK_DAYS
+        );
+    }
+
+    #[test]
+    fn each_day_decides_its_own_outfit_the_way_the_headline_does() {
+        let days = outlook(&week(), 1, Activity::default(), standard());
+        // Identical days make identical decisions.
+        for day in &days[1..] {
+            assert_eq!(day.wear, days[0].wear);
+            assert_eq!(day.carry, days[0].carry);
+            assert_eq!(day.typical.score, days[0].typical.score);
+        }
+        // The fixture's clear day runs from a cool dawn to a sunny afternoon,
+        // so there is a layer worth carrying.
+        assert!(days[0].carry.is_some(), "{:?}", days[0].carry);
+        assert!(days[0].low.level < days[0].high.level);
+    }
+
+    #[test]
+    fn rain_is_reported_per_day() {
+        let mut wet = week();
+        for hour in &mut wet.hours {
+            if hour.time.starts_with("2026-08-04") && &hour.time[11..13] >= "15" {
+                hour.precipitation_probability = 80.0;
+            }
+        }
+        let days = outlook(&wet, 1, Activity::default(), standard());
+        assert!(days[0].rain.is_none());
+        let rain = days[2].rain.as_deref().expect("rain on the 4th");
+        assert!(rain.contains("Dry until about 3 PM"), "{rain}");
+    }
+
+    #[test]
+    fn skips_a_day_with_no_hours() {
+        let mut ragged = week();
+        ragged
+            .hours
+            .retain(|hour| !hour.time.starts_with("2026-08-03"));
+        let dates: Vec<String> = outlook(&ragged, 1, Activity::default(), standard())
+            .into_iter()
+            .map(|day| day.date)
+            .collect();
+        assert_eq!(dates, ["2026-08-02", "2026-08-04", "2026-08-05"]);
+    }
+}
@@ -0,0 +1,80 @@
+//! Place suggestions for the search box, as it is typed into.
+//!
+//! `/api/places?q=oak` answers with a JSON array of places, best first, each
+//! with the query string that opens its report. Past searches come first and
+//! may be all there is; see [`crate::services::places`]. Nothing matching is
+//! an empty array rather than an error, since most prefixes on the way to a
+//! name match nothing yet.
+
+use axum::extract::{Query, State};
+use axum::http::header::{self, HeaderValue};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use super::place_param;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct PlacesQuery {
+    q: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Suggestion {
+    name: String,
+    /// Region and country.
+    detail: String,
+    latitude: f64,
+    longitude: f64,
+    /// Query string for this place's report, e.g. `lat=…&lon=…&name=…`.
+    query: String,
+}
+
+#[derive(Serialize)]
+struct Failure {
+    error: String,
+}
+
+/// Suggestions for `?q=`, or a 502 when upstream failed and nothing
+/// remembered matches either.
+pub async fn places(State(state): State<AppState>, Query(query): Query<PlacesQuery>) -> Response {
+    let search = query.q.as_deref().map(str::trim).unwrap_or_default();
+    let found = if search.is_empty() {
+        Ok(Vec::new())
+    } else {
+        match state.weather.suggest(search).await {
+            Err(err) if err.no_such_place() => Ok(Vec::new()),
+            found => found,
+        }
+    };
+    // Suggestions change as searches are tallied; a browser may keep them
+    // briefly, a shared cache not at all.
+    let caching = [(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, max-age=60"),
+    )];
+    match found {
+        Ok(places) => {
+            let suggestions: Vec<Suggestion> = places
+                .iter()
+                .map(|place| Suggestion {
+                    name: place.name.clone(),
+                    detail: place.detail.clone(),
+                    latitude: place.latitude,
+                    longitude: place.longitude,
+                    query: place_param(place),
+                })
+                .collect();
+            (caching, Json(suggestions)).into_response()
+        }
+        Err(err) => (
+            StatusCode::BAD_GATEWAY,
+            Json(Failure {
+                error: err.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
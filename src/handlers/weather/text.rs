@@ -0,0 +1,373 @@
+//! The weather report as plain text, for a terminal.
+//!
+//! What `/weather` sends a client that asked for neither HTML nor JSON, which
+//! in practice means `curl`. It is the same [`Report`] the page renders, cut
+//! down to what answers the question from a prompt: the headline, the verdict,
+//! and the hourly table, in columns that line up in a monospace font.
+//!
+//! `?color=1` adds ANSI colour: each score becomes a chip in its level's
+//! colour, as on the page, and hours already gone are dimmed. It is opt-in
+//! because escape codes are noise in a file or a pipe, and the server cannot
+//! see which one it is writing to.
+
+use serde::Deserialize;
+
+use super::{HourRow, Probe, Report, Target};
+
+/// Where the verdict wraps. Narrow enough for a split terminal.
+const WIDTH: usize = 72;
+
+/// xterm-256 backgrounds nearest the page's `--weather-feel-*` steps, cold
+/// blue through off-white to hot red.
+const LEVEL_COLOURS: [u8; 11] = [68, 75, 111, 153, 189, 255, 224, 217, 210, 209, 167];
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+
+/// The text view's own switches, read alongside the [`super::WeatherQuery`].
+#[derive(Deserialize)]
+pub struct TextOptions {
+    color: Option<String>,
+}
+
+impl TextOptions {
+    /// Any value but an explicit no turns colour on, so `?color` alone works.
+    pub fn colour(&self) -> bool {
+        self.color
+            .as_deref()
+            .is_some_and(|value| !matches!(value, "0" | "false" | "no" | "off"))
+    }
+}
+
+/// The whole response body, newline-terminated.
+pub(super) fn render(
+    target: &Target,
+    report: Option<&Report>,
+    error: Option<&str>,
+    colour: bool,
+) -> String {
+    let style = Style { colour };
+    let mut out = Vec::new();
+
+    out.push(style.bold(&target.name));
+    if !target.detail.is_empty() {
+        out.push(target.detail.clone());
+    }
+    if let Some(miles) = target.named_from_mi {
+        out.push(format!(
+            "{miles:.1} mi from {}, the nearest named place",
+            target.name
+        ));
+    }
+    if let Some(message) = error {
+        out.push(message.to_owned());
+    }
+
+    if let Some(today) = report {
+        out.push(String::new());
+        out.extend(headline(today, &style));
+        out.push(String::new());
+        for sentence in &today.verdict {
+            out.extend(wrap(sentence, WIDTH));
+        }
+        out.push(String::new());
+        out.extend(table(&today.hours, &style));
+        if today.hours_estimated {
+            out.extend(wrap(
+                "* No radiation published; sun and shade are estimated from cloud cover.",
+                WIDTH,
+            ));
+        }
+        out.push(String::new());
+        out.push(format!(
+            "Sunrise {}, sunset {}. Updated {} {}, from {}.",
+            today.sunrise_label,
+            today.sunset_label,
+            today.updated_label,
+            today.timezone,
+            today.provider
+        ));
+        if let Some(reason) = &today.fallback {
+            let mut note = format!("{reason} This is {}'s forecast instead", today.provider);
+            if !today.estimated.is_empty() {
+                note.push_str(&format!(
+                    ", with {} estimated here",
+                    today.estimated.join(" and ")
+                ));
+            }
+            note.push('.');
+            out.extend(wrap(&note, WIDTH));
+        }
+        if today.stale {
+            out.extend(wrap(
+                &format!(
+                    "This is the forecast as of {}. A newer one is being fetched.",
+                    today.updated_label
+                ),
+                WIDTH,
+            ));
+        }
+    }
+
+    let mut body = out.join("\n");
+    body.push('\n');
+    body
+}
+
+/// The typical feel and the two bounds around it.
+fn headline(today: &Report, style: &Style) -> Vec<String> {
+    let walking = today.activity == crate::comfort::Activity::default();
+    let mut scope = if walking {
+        format!("How {} feels", today.headline_scope)
+    } else {
+        format!(
+            "How {} feels {}",
+            today.headline_scope,
+            today.activity.name()
+        )
+    };
+    if !std::ptr::eq(today.scale, crate::scale::profiles().default()) {
+        scope.push_str(&format!(", on the {} scale", today.scale.name()));
+    }
+    let bound = |name: &str, probe: &Probe| {
+        format!(
+            "  {name:<24}{} {:>3}\u{b0}",
+            style.chip(probe),
+            probe.degrees
+        )
+    };
+    vec![
+        scope,
+        format!(
+            "  {} {:>3}\u{b0}  {}, {}% of the day",
+            style.chip(&today.typical),
+            today.typical.degrees,
+            today.typical.label,
+            today.typical_share
+        ),
+        bound("coolest, out of the sun", &today.low),
+        bound(&format!("warmest, {}", today.high_hour), &today.high),
+        format!(
+            "  {}\u{b0} to {}\u{b0}, a {}\u{b0} swing.",
+            today.low.degrees, today.high.degrees, today.swing_f
+        ),
+    ]
+}
+
+/// One row per hour: the pair of scores, then the air that produced them.
+fn table(hours: &[HourRow], style: &Style) -> Vec<String> {
+    let mut rows = vec![format!(
+        "  {:<6}  {:<12}{:<12}{:>3} {:>8}{:>6}",
+        "", "sun", "shade", "air", "wind", "rain"
+    )];
+    for hour in hours {
+        let sun = if hour.has_sun {
+            format!(
+                "{} {:>3}\u{b0} ",
+                style.level_chip(&hour.sun_score, hour.sun_level),
+                hour.sun_f
+            )
+        } else {
+            " ".repeat(12)
+        };
+        let shade = format!(
+            "{} {:>3}\u{b0} ",
+            style.level_chip(&hour.shade_score, hour.shade_level),
+            hour.shade_f
+        );
+        let marker = if hour.is_now { '>' } else { ' ' };
+        let label = if hour.estimated {
+            format!("{}*", hour.label)
+        } else {
+            hour.label.clone()
+        };
+        let row = format!(
+            "{marker} {label:<6} {sun}{shade}{:>4}\u{b0}{:>4} mph{:>5}%",
+            hour.air_f, hour.wind_mph, hour.rain_chance
+        );
+        rows.push(if hour.past { style.dim(&row) } else { row });
+    }
+    rows
+}
+
+/// Greedy word wrap. Words longer than the line are left whole.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Whether to decorate, and how. Every method returns text of the same visible
+/// width either way, so the columns line up with or without colour.
+struct Style {
+    colour: bool,
+}
+
+impl Style {
+    fn chip(&self, probe: &Probe) -> String {
+        self.level_chip(&probe.score, probe.level)
+    }
+
+    /// A score padded to a fixed width, on its level's colour when colouring.
+    fn level_chip(&self, score: &str, level: u8) -> String {
+        let padded = format!(" {score:>4} ");
+        if !self.colour {
+            return padded;
+        }
+        let background = LEVEL_COLOURS[usize::from(level.min(10))];
+        // Black text: every step of the palette is light enough to carry it.
+        format!("\x1b[48;5;{background}m\x1b[38;5;16m{padded}{RESET}")
+    }
+
+    fn bold(&self, text: &str) -> String {
+        if self.colour {
+            format!("{BOLD}{text}{RESET}")
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn dim(&self, text: &str) -> String {
+        if self.colour {
+            // Chips reset inside the row, so the dimming is reapplied after
+            // each one rather than wrapped once around the lot.
+            format!(
+                "{DIM}{}{RESET}",
+                text.replace(RESET, &format!("{RESET}{DIM}"))
+            )
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{report, target};
+    use super::*;
+
+    /// Text with every escape sequence removed, as a terminal would show it.
+    fn visible(text: &str) -> String {
+        let mut shown = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for end in chars.by_ref() {
+                    if end == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                shown.push(c);
+            }
+        }
+        shown
+    }
+
+    #[test]
+    fn says_how_far_a_looked_up_name_is() {
+        let mut named = target();
+        named.named_from_mi = Some(3.87);
+        let text = render(&named, None, None, false);
+        assert!(text.contains("3.9 mi from Inner Sunset, the nearest named place"));
+        assert!(!render(&target(), None, None, false).contains("nearest named place"));
+    }
+
+    #[test]
+    fn plain_by_default() {
+        let text = render(&target(), Some(&report()), None, false);
+        assert!(!text.contains('\x1b'));
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn leads_with_the_headline_and_the_verdict() {
+        let report = report();
+        let text = render(&target(), Some(&report), None, false);
+        assert!(text.contains(&format!("How {} feels", report.headline_scope)));
+        assert!(text.contains(&report.typical.label));
+        let first_words: String = report.verdict[0]
+            .split_whitespace()
+            .take(4)
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert!(text.contains(&first_words), "{text}");
+    }
+
+    #[test]
+    fn hourly_rows_line_up_with_or_without_colour() {
+        let report = report();
+        for colour in [false, true] {
+            let rows = table(&report.hours, &Style { colour });
+            let widths: Vec<usize> = rows
+                .iter()
+                .skip(1)
+                .map(|row| visible(row).trim_end().chars().count())
+                .collect();
+            assert!(
+                widths.windows(2).all(|pair| pair[0] == pair[1]),
+                "{widths:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn colour_paints_each_score_in_its_level() {
+        let report = report();
+        let text = render(&target(), Some(&report), None, true);
+        let level = LEVEL_COLOURS[usize::from(report.typical.level)];
+        assert!(text.contains(&format!("\x1b[48;5;{level}m")));
+        assert_eq!(
+            visible(&text),
+            render(&target(), Some(&report), None, false)
+        );
+    }
+
+    #[test]
+    fn marks_the_current_hour() {
+        let text = render(&target(), Some(&report()), None, false);
+        assert_eq!(
+            text.lines().filter(|line| line.starts_with("> ")).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn says_why_when_there_is_no_report() {
+        let text = render(&target(), None, Some("Could not reach Open-Meteo."), false);
+        assert!(text.contains("Could not reach Open-Meteo."));
+        assert_eq!(text.lines().count(), 3);
+    }
+
+    #[test]
+    fn wraps_on_words() {
+        let lines = wrap("one two three four five", 9);
+        assert_eq!(lines, vec!["one two", "three", "four five"]);
+        assert!(wrap("", 10).is_empty());
+    }
+
+    #[test]
+    fn colour_is_on_unless_refused() {
+        let options = |color: Option<&str>| TextOptions {
+            color: color.map(str::to_owned),
+        };
+        assert!(!options(None).colour());
+        assert!(options(Some("")).colour());
+        assert!(options(Some("1")).colour());
+        assert!(!options(Some("0")).colour());
+        assert!(!options(Some("false")).colour());
+    }
+}
@@ -1,7 +1,14 @@
 //! Utility functions used across handlers.
 
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
 use axum::http::header::{self, HeaderMap};
 use multimap::MultiMap;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
 
 /// Percent-encodes one query-string value.
@@ -47,6 +54,88 @@ pub fn requested_html(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
+/// Checks if the request asks for JSON by name.
+///
+/// A wildcard does not count: `curl` sends `*/*`, and a page that answers that
+/// with JSON is a page nobody can read in a terminal. Nor does
+/// `application/json;q=0`, which names JSON only to refuse it.
+pub fn requested_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| {
+            accept.split(',').any(|range| {
+                let mut parts = range.split(';');
+                let media = parts.next().unwrap_or_default().trim();
+                media.eq_ignore_ascii_case("application/json")
+                    && parts
+                        .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                        .all(|q| q.trim().parse::<f64>().map_or(true, |q| q > 0.0))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// The value of one request cookie, if the browser sent it.
+///
+/// Browsers may send several `Cookie` headers over HTTP/2, so all are read.
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+}
+
+/// Serialises appends from concurrent requests, so two never interleave
+/// within a line.
+static APPENDING: Mutex<()> = Mutex::new(());
+
+/// Adds one value to the end of a JSON-lines file, creating it if need be.
+/// The site's few local records are kept this way: append-only, one object
+/// per line, readable with `jq`.
+pub fn append_json_line(path: &Path, value: &impl Serialize) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    let _guard = APPENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Every value in a JSON-lines file's contents, and how many lines were not
+/// one. A crash mid-append can leave a torn last line, which should cost that
+/// one line and not the whole file.
+pub fn parse_json_lines<T: DeserializeOwned>(contents: &str) -> (Vec<T>, usize) {
+    let mut values = Vec::new();
+    let mut unreadable = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(value) => values.push(value),
+            Err(_) => unreadable += 1,
+        }
+    }
+    (values, unreadable)
+}
+
+/// Every value in a JSON-lines file, as [`parse_json_lines`]. A file that is
+/// not there yet has nothing in it.
+pub fn read_json_lines<T: DeserializeOwned>(path: &Path) -> std::io::Result<(Vec<T>, usize)> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(parse_json_lines(&contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok((Vec::new(), 0)),
+        Err(err) => Err(err),
+    }
+}
+
 /// Converts a MultiMap to a JSON-friendly format, merging duplicate keys into arrays.
 pub fn pretty_multimap(map: &MultiMap<String, String>) -> serde_json::Map<String, Value> {
     let mut pretty_map = serde_json::Map::new();
@@ -137,6 +226,46 @@ mod tests {
         assert!(requested_html(&headers));
     }
 
+    #[test]
+    fn requested_json_detects_json_accept_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json;q=0.9, text/plain"),
+        );
+        assert!(requested_json(&headers));
+    }
+
+    #[test]
+    fn requested_json_ignores_wildcards_and_html() {
+        let mut headers = HeaderMap::new();
+        assert!(!requested_json(&headers));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+        assert!(!requested_json(&headers));
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/html,application/xhtml+xml"),
+        );
+        assert!(!requested_json(&headers));
+    }
+
+    #[test]
+    fn requested_json_takes_q_zero_as_a_refusal() {
+        let mut headers = HeaderMap::new();
+        for refusal in [
+            "application/json;q=0",
+            "text/plain, application/json; q=0.000",
+        ] {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(refusal));
+            assert!(!requested_json(&headers), "{refusal}");
+        }
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json;q=0.1"),
+        );
+        assert!(requested_json(&headers));
+    }
+
     #[test]
     fn pretty_multimap_merges_duplicate_keys_into_arrays() {
         let mut multimap = MultiMap::new();
@@ -193,4 +322,18 @@ mod tests {
         let result = get_user_agent("");
         assert_eq!(result.name, "UNKNOWN");
     }
+
+    #[test]
+    fn cookie_finds_one_pair_among_many() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(cookie(&headers, "scale"), None);
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; scale=runs-cold"),
+        );
+        headers.append(header::COOKIE, HeaderValue::from_static("other=1"));
+        assert_eq!(cookie(&headers, "scale"), Some("runs-cold"));
+        assert_eq!(cookie(&headers, "other"), Some("1"));
+        assert_eq!(cookie(&headers, "scal"), None);
+    }
 }
@@ -1,109 +1,253 @@
-//! Saved locations, committed to the repo.
+//! Saved locations, read from a file at startup.
 //!
 //! There is no database on this box and no user accounts, so the shortcuts live
-//! here. Anything not on this list is reached by search, and the browser
-//! remembers the last one picked in `localStorage`.
+//! in `locations.json` beside the binary (or wherever `LOCATIONS_FILE` points).
+//! Anything not on this list is reached by search, and the browser remembers
+//! the last one picked in `localStorage`.
+//!
+//! The file is checked whole before it is used: slugs unique and URL-safe,
+//! coordinates on the globe, and a home that is one of the pins. A bad file
+//! stops the server at startup. `SIGHUP` reads it again; if the new file is
+//! bad, the error is logged and the old list stays, so a typo never takes the
+//! page down.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::Deserialize;
+
+use crate::config::get_config;
 
 /// A pinned place. Coordinates are a specific corner of the neighbourhood, not
 /// a city centroid — in San Francisco those are different weather.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Location {
     /// URL-safe identifier used as `?loc=`.
-    pub slug: &'static str,
+    pub slug: String,
     /// Short label for the shortcut row.
-    pub name: &'static str,
+    pub name: String,
     /// What the coordinates actually point at.
-    pub detail: &'static str,
+    pub detail: String,
     pub latitude: f64,
     pub longitude: f64,
 }
 
-/// The shortcut row, in display order. The first entry is home.
-pub static PINNED: [Location; 3] = [
-    Location {
-        slug: "inner-sunset",
-        name: "Inner Sunset",
-        detail: "Inner Sunset, San Francisco",
-        latitude: 37.7601,
-        longitude: -122.4661,
-    },
-    Location {
-        slug: "fidi",
-        name: "Financial District",
-        detail: "Financial District, San Francisco",
-        latitude: 37.7946,
-        longitude: -122.3999,
-    },
-    Location {
-        slug: "nyc",
-        name: "New York City",
-        detail: "Midtown Manhattan, New York",
-        latitude: 40.7549,
-        longitude: -73.984,
+/// The file as written, before validation.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PinsFile {
+    /// Slug of the location shown when nothing else is asked for.
+    home: String,
+    /// The shortcut row, in display order.
+    pins: Vec<Location>,
+}
+
+/// A validated list of pins.
+#[derive(Debug)]
+pub struct Pins {
+    locations: Vec<Location>,
+    home: usize,
+}
+
+impl Pins {
+    /// Parses and validates the contents of a pins file.
+    pub fn parse(json: &str) -> Result<Pins, Error> {
+        let file: PinsFile = serde_json::from_str(json)?;
+
+        for (index, location) in file.pins.iter().enumerate() {
+            let slug = &location.slug;
+            if slug.is_empty()
+                || !slug
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            {
+                return Err(Error::Invalid(format!(
+                    "slug \u{201c}{slug}\u{201d} is not URL-safe; use a-z, 0-9 and -"
+                )));
+            }
+            if file.pins[..index].iter().any(|other| other.slug == *slug) {
+                return Err(Error::Invalid(format!(
+                    "slug \u{201c}{slug}\u{201d} is used twice"
+                )));
+            }
+            if !(-90.0..=90.0).contains(&location.latitude)
+                || !(-180.0..=180.0).contains(&location.longitude)
+            {
+                return Err(Error::Invalid(format!(
+                    "{slug} is at {}, {}, which is not on the globe",
+                    location.latitude, location.longitude
+                )));
+            }
+        }
+
+        let home = file
+            .pins
+            .iter()
+            .position(|location| location.slug == file.home)
+            .ok_or_else(|| {
+                Error::Invalid(format!("home \u{201c}{}\u{201d} is not a pin", file.home))
+            })?;
+
+        Ok(Pins {
+            locations: file.pins,
+            home,
+        })
+    }
+
+    /// Reads and validates a pins file.
+    pub fn read(path: &Path) -> Result<Pins, Error> {
+        let json = std::fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        Pins::parse(&json)
+    }
+
+    /// The location shown when nothing else is asked for.
+    pub fn home(&self) -> &Location {
+        &self.locations[self.home]
+    }
+
+    /// The default second place when two are compared: the first pin that is
+    /// not home, or home itself if it is the only one.
+    pub fn away(&self) -> &Location {
+        self.locations
+            .iter()
+            .enumerate()
+            .find(|&(index, _)| index != self.home)
+            .map_or_else(|| self.home(), |(_, location)| location)
+    }
+
+    /// Looks up a pinned location by its `?loc=` slug.
+    pub fn find(&self, slug: &str) -> Option<&Location> {
+        self.locations.iter().find(|location| location.slug == slug)
+    }
+
+    /// Every pin, in display order.
+    pub fn all(&self) -> &[Location] {
+        &self.locations
+    }
+}
+
+/// Why a pins file was refused.
+#[derive(Debug)]
+pub enum Error {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
     },
-];
+    /// Not JSON, or not the shape of a pins file.
+    Parse(serde_json::Error),
+    /// Well-formed, but breaks one of the rules above.
+    Invalid(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "could not read {}: {source}", path.display())
+            }
+            Error::Parse(err) => write!(f, "pins file is malformed: {err}"),
+            Error::Invalid(why) => write!(f, "pins file is invalid: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } => Some(source),
+            Error::Parse(err) => Some(err),
+            Error::Invalid(_) => None,
+        }
+    }
+}
 
-/// The location shown when nothing else is asked for.
-pub fn home() -> &'static Location {
-    &PINNED[0]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err)
+    }
 }
 
-/// Looks up a pinned location by its `?loc=` slug.
-pub fn find(slug: &str) -> Option<&'static Location> {
-    PINNED.iter().find(|location| location.slug == slug)
+static PINNED: OnceLock<RwLock<Arc<Pins>>> = OnceLock::new();
+
+/// The current pins. A request holds on to one snapshot throughout, so a
+/// reload mid-request cannot hand it half of each list.
+///
+/// Reads the file on first use if [`reload`] has not already; `main` calls
+/// that first so a bad file is reported before the server listens.
+pub fn pinned() -> Arc<Pins> {
+    let lock = PINNED.get_or_init(|| {
+        let pins = Pins::read(&get_config().locations_path)
+            .unwrap_or_else(|err| panic!("no pinned locations: {err}"));
+        RwLock::new(Arc::new(pins))
+    });
+    Arc::clone(&lock.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Reads the pins file again and swaps it in, keeping the old list on error.
+pub fn reload() -> Result<Arc<Pins>, Error> {
+    let pins = Arc::new(Pins::read(&get_config().locations_path)?);
+    match PINNED.get() {
+        Some(lock) => {
+            *lock
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::clone(&pins);
+        }
+        None => {
+            // If a first `pinned()` got in between, it read the same file, so
+            // either copy will do.
+            let _ = PINNED.set(RwLock::new(Arc::clone(&pins)));
+        }
+    }
+    Ok(pins)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn home_is_the_inner_sunset() {
-        assert_eq!(home().slug, "inner-sunset");
-        assert_eq!(home().name, "Inner Sunset");
+    /// The file that ships with the site.
+    fn shipped() -> Pins {
+        Pins::parse(include_str!("../locations.json")).expect("locations.json is valid")
+    }
+
+    fn rejection(json: &str) -> String {
+        Pins::parse(json).unwrap_err().to_string()
     }
 
     #[test]
-    fn every_pin_is_findable_by_slug() {
-        for location in &PINNED {
-            assert_eq!(find(location.slug).map(|f| f.slug), Some(location.slug));
-        }
-        assert!(find("nowhere").is_none());
+    fn home_is_the_inner_sunset() {
+        let pins = shipped();
+        assert_eq!(pins.home().slug, "inner-sunset");
+        assert_eq!(pins.home().name, "Inner Sunset");
     }
 
     #[test]
-    fn slugs_are_unique_and_url_safe() {
-        let mut slugs: Vec<&str> = PINNED.iter().map(|location| location.slug).collect();
-        slugs.sort_unstable();
-        let count = slugs.len();
-        slugs.dedup();
-        assert_eq!(slugs.len(), count, "duplicate slug");
-
-        for location in &PINNED {
-            assert!(
-                location
-                    .slug
-                    .chars()
-                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
-                "{} is not url-safe",
-                location.slug
+    fn every_pin_is_findable_by_slug() {
+        let pins = shipped();
+        for location in pins.all() {
+            assert_eq!(
+                pins.find(&location.slug).map(|f| &f.slug),
+                Some(&location.slug)
             );
         }
+        assert!(pins.find("nowhere").is_none());
     }
 
     #[test]
-    fn coordinates_are_plausible() {
-        for location in &PINNED {
-            assert!(
-                (-90.0..=90.0).contains(&location.latitude),
-                "{}",
-                location.slug
-            );
-            assert!(
-                (-180.0..=180.0).contains(&location.longitude),
-                "{}",
-                location.slug
-            );
-        }
+    fn away_is_the_first_pin_that_is_not_home() {
+        assert_eq!(shipped().away().slug, "fidi");
+
+        let alone = Pins::parse(
+            r#"{"home": "here", "pins": [
+                {"slug": "here", "name": "Here", "detail": "", "latitude": 0, "longitude": 0}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(alone.away().slug, "here");
     }
 
     #[test]
@@ -111,11 +255,80 @@ mod tests {
         // Inner Sunset and the Financial District resolve to different model
         // grid cells; if these ever collapse onto one point the page would be
         // claiming a distinction the data cannot make.
-        let sunset = find("inner-sunset").unwrap();
-        let fidi = find("fidi").unwrap();
+        let pins = shipped();
+        let sunset = pins.find("inner-sunset").unwrap();
+        let fidi = pins.find("fidi").unwrap();
         let degrees = ((sunset.latitude - fidi.latitude).powi(2)
             + (sunset.longitude - fidi.longitude).powi(2))
         .sqrt();
         assert!(degrees > 0.03, "pins are only {degrees}° apart");
     }
+
+    #[test]
+    fn refuses_duplicate_slugs() {
+        let message = rejection(
+            r#"{"home": "a", "pins": [
+                {"slug": "a", "name": "A", "detail": "", "latitude": 0, "longitude": 0},
+                {"slug": "a", "name": "B", "detail": "", "latitude": 1, "longitude": 1}
+            ]}"#,
+        );
+        assert!(message.contains("used twice"), "{message}");
+    }
+
+    #[test]
+    fn refuses_slugs_that_are_not_url_safe() {
+        for slug in ["", "Inner Sunset", "fidi?x=1", "caf\u{e9}"] {
+            let json = format!(
+                r#"{{"home": "{slug}", "pins": [
+                    {{"slug": "{slug}", "name": "A", "detail": "", "latitude": 0, "longitude": 0}}
+                ]}}"#
+            );
+            let message = rejection(&json);
+            assert!(message.contains("URL-safe"), "{slug}: {message}");
+        }
+    }
+
+    #[test]
+    fn refuses_coordinates_off_the_globe() {
+        for (latitude, longitude) in [(91.0, 0.0), (0.0, -181.0), (-122.4, 37.7)] {
+            let json = format!(
+                r#"{{"home": "a", "pins": [
+                    {{"slug": "a", "name": "A", "detail": "", "latitude": {latitude}, "longitude": {longitude}}}
+                ]}}"#
+            );
+            let message = rejection(&json);
+            assert!(message.contains("not on the globe"), "{message}");
+        }
+    }
+
+    #[test]
+    fn refuses_a_home_that_is_not_pinned() {
+        let message = rejection(
+            r#"{"home": "b", "pins": [
+                {"slug": "a", "name": "A", "detail": "", "latitude": 0, "longitude": 0}
+            ]}"#,
+        );
+        assert!(message.contains("home"), "{message}");
+        assert!(rejection(r#"{"home": "a", "pins": []}"#).contains("home"));
+    }
+
+    #[test]
+    fn refuses_malformed_files() {
+        assert!(rejection("[]").contains("malformed"));
+        // A misspelt key would otherwise be silently ignored.
+        let message = rejection(
+            r#"{"home": "a", "pins": [
+                {"slug": "a", "name": "A", "detail": "", "lat": 0, "longitude": 0}
+            ]}"#,
+        );
+        assert!(message.contains("malformed"), "{message}");
+    }
+
+    #[test]
+    fn read_names_the_missing_file() {
+        let message = Pins::read(Path::new("/nonexistent/locations.json"))
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("/nonexistent/locations.json"), "{message}");
+    }
 }
@@ -1,25 +1,66 @@
 //! Personal website backend server.
 
+mod accuracy;
+mod clock;
 mod comfort;
 mod config;
 mod extractors;
+mod feedback;
 mod handlers;
 mod helpers;
 mod locations;
+mod pmv;
+mod prefetch;
 mod router;
 mod scale;
 mod services;
+mod solar;
+mod state;
 mod units;
 
 use std::net::SocketAddr;
 
+use tokio::sync::watch;
 use tracing_subscriber::{fmt, EnvFilter};
 
 use crate::config::get_config;
 use crate::router::create_app_router;
+use crate::state::AppState;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
+    // Offline jobs, which print and exit without starting the server.
+    match std::env::args().nth(1).as_deref() {
+        // Re-solve the comfort scale's anchors after an outfit or an
+        // assumption in `scale::OUTFITS` changes.
+        Some("derive-scale") => {
+            print!("{}", scale::derivation());
+            return Ok(());
+        }
+        // Fit each reader's offset from the votes recorded so far.
+        Some("fit-feedback") => {
+            let profiles = match scale::load() {
+                Ok(profiles) => profiles,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(1);
+                }
+            };
+            let path = &get_config().feedback_path;
+            let contents = std::fs::read_to_string(path).or_else(|err| match err.kind() {
+                std::io::ErrorKind::NotFound => Ok(String::new()),
+                _ => Err(err),
+            })?;
+            let (records, unreadable) = feedback::parse(&contents);
+            print!(
+                "{}",
+                feedback::report(&feedback::fit(&records, profiles), unreadable)
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // Configure log level from RUST_LOG, with a fallback
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
@@ -27,21 +68,70 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Starting server");
 
     let config = get_config();
+    match locations::reload() {
+        Ok(pins) => tracing::info!(
+            "Loaded {} pinned locations from {}",
+            pins.all().len(),
+            config.locations_path.display()
+        ),
+        Err(err) => {
+            tracing::error!("{err}");
+            std::process::exit(1);
+        }
+    }
+    match scale::load() {
+        Ok(profiles) => tracing::info!(
+            "Loaded {} comfort scales from {}",
+            profiles.all().len(),
+            config.scales_path.display()
+        ),
+        Err(err) => {
+            tracing::error!("{err}");
+            std::process::exit(1);
+        }
+    }
+    tokio::spawn(reload_on_hangup());
+    let state = AppState::production();
+    // A replayed forecast is the same week forever, with nothing to mark.
+    if config.upstream != services::fixtures::Mode::Replay {
+        tokio::spawn(handlers::keep_snapshots(state.weather.clone()));
+    }
+    let (stop, stopped) = watch::channel(false);
+    let prefetch = tokio::spawn(prefetch::keep_warm(state.weather.clone(), stopped));
+
     let addr: SocketAddr = format!("0.0.0.0:{}", config.port).parse().unwrap();
 
-    let app = create_app_router();
+    let app = create_app_router(state);
     let service = app.into_make_service_with_connect_info::<SocketAddr>();
 
     tracing::info!("Listening on {addr}");
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, service)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            stop.send_replace(true);
+        })
         .await?;
+    let _ = prefetch.await;
 
     tracing::info!("Server finished");
     Ok(())
 }
 
+/// Rereads the pinned locations on `SIGHUP`, which is what `systemctl reload`
+/// sends.
+async fn reload_on_hangup() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup()).expect("failed to install SIGHUP handler");
+    while hangups.recv().await.is_some() {
+        match locations::reload() {
+            Ok(pins) => tracing::info!("Reloaded {} pinned locations", pins.all().len()),
+            Err(err) => tracing::error!("Kept the previous pinned locations: {err}"),
+        }
+    }
+}
+
 async fn shutdown_signal() {
     tokio::signal::ctrl_c()
         .await
@@ -57,13 +147,21 @@ mod tests {
     use axum::http::{Method, Request, StatusCode};
     use axum::Router;
     use http_body_util::BodyExt;
-    use std::net::SocketAddr;
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::Arc;
     use tower::ServiceExt;
 
     use crate::router::create_app_router;
+    use crate::services::fallback::Fallback;
+    use crate::services::fixtures::Mode;
+    use crate::services::nws::Nws;
+    use crate::services::open_meteo::{self, Forecast, Place};
+    use crate::services::private_relay::EgressRange;
+    use crate::services::{BoxFuture, Error, RelayRangeSource, WeatherProvider};
+    use crate::state::AppState;
 
     fn test_app() -> Router {
-        create_app_router()
+        create_app_router(AppState::production())
     }
 
     async fn body_string(body: Body) -> String {
@@ -553,6 +651,348 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn weather_answers_json_when_asked_for_it() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi")
+                    .header(header::ACCEPT, "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().contains("application/json"));
+        assert_eq!(
+            response
+                .headers()
+                .get(header::VARY)
+                .and_then(|value| value.to_str().ok()),
+            Some("accept, cookie")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&body_string(response.into_body()).await).unwrap();
+        assert_eq!(body["version"], 1);
+        assert_eq!(body["place"]["pin"], "fidi");
+        assert!(body["report"].is_object(), "{body}");
+    }
+
+    #[tokio::test]
+    async fn weather_answers_curl_in_plain_text() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi")
+                    .header(header::ACCEPT, "*/*")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok()),
+            Some("text/plain; charset=utf-8")
+        );
+        let body = body_string(response.into_body()).await;
+        assert!(body.starts_with("Financial District"), "{body}");
+        assert!(body.contains("\nWear "), "{body}");
+        assert!(!body.contains('<'));
+    }
+
+    #[tokio::test]
+    async fn weather_searches_and_offers_the_other_matches() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?q=Oakland")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains(r#"<h2 class="weather-place-name">Oakland</h2>"#));
+        assert!(body.contains("Oakland, Tennessee, United States"));
+        assert!(!body.contains("weather-error"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn best_time_defaults_to_json_and_renders_for_browsers() {
+        let app = test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/best?loc=fidi&minutes=90")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().contains("application/json"));
+        let body: serde_json::Value =
+            serde_json::from_str(&body_string(response.into_body()).await).unwrap();
+        assert_eq!(body["version"], 1);
+        assert_eq!(body["minutes"], 90);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/best?loc=fidi")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("Best Time to Go Out"));
+        assert!(body.contains(r#"name="loc" value="fidi""#));
+
+        // Without a forecast, browsers and scripts hear the same status, and
+        // neither answer is kept.
+        for accept in ["application/json", "text/html"] {
+            let response = broken_app()
+                .oneshot(
+                    Request::builder()
+                        .uri("/weather/best?loc=fidi")
+                        .header(header::ACCEPT, accept)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_GATEWAY, "{accept}");
+            assert_eq!(
+                response
+                    .headers()
+                    .get(header::CACHE_CONTROL)
+                    .and_then(|value| value.to_str().ok()),
+                Some("no-store"),
+                "{accept}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn compare_names_both_places() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/compare?a=inner-sunset&b=fidi")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("Inner Sunset or Financial District"));
+    }
+
+    #[tokio::test]
+    async fn commute_keeps_the_times_it_was_given() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/commute?from=inner-sunset&leave=7:30&to=fidi&back=17")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains(r#"name="leave""#));
+        assert!(body.contains(r#"value="7""#));
+        assert!(body.contains(r#"value="17""#));
+    }
+
+    #[tokio::test]
+    async fn calendar_feed_is_a_calendar_or_an_honest_failure() {
+        let app = test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather.ics?loc=fidi")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().starts_with("text/calendar"));
+        assert_eq!(
+            response
+                .headers()
+                .get(header::VARY)
+                .and_then(|value| value.to_str().ok()),
+            Some("cookie")
+        );
+        let body = body_string(response.into_body()).await;
+        assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+
+        // An empty calendar would wipe a subscriber's week, so a place with no
+        // forecast must not be a 200. Nothing is recorded for the open ocean.
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather.ics?lat=0&lon=0&name=Null%20Island")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn weather_links_keep_the_chosen_activity() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi&activity=running")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("/weather?loc=nyc&#38;activity=running"));
+        assert!(body.contains(r#"name="activity" value="running""#));
+    }
+
+    #[tokio::test]
+    async fn weather_remembers_a_chosen_scale() {
+        let app = test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi&scale=runs-cold")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let cookie = response
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap();
+        assert!(cookie.starts_with("scale=runs-cold;"), "{cookie}");
+        // A page that set a cookie must not be shared from a cache.
+        assert!(response
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .unwrap()
+            .starts_with("private"));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi&scale=nonsense")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+    }
+
+    #[tokio::test]
+    async fn feedback_refuses_a_vote_it_does_not_know() {
+        // A real vote would append to the feedback file, so only the refusal
+        // is exercised here.
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/weather/feedback?loc=fidi")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("vote=lukewarm"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_client_error(), "{}", response.status());
+    }
+
+    #[tokio::test]
+    async fn feedback_only_takes_posts() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/feedback?loc=fidi&vote=right")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn accuracy_page_lists_the_pins() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/accuracy?loc=fidi")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response.into_body()).await;
+        assert!(
+            body.contains("/weather/accuracy?loc=inner-sunset"),
+            "{body}"
+        );
+        assert!(body.contains("Back to the forecast"));
+    }
+
+    #[tokio::test]
+    async fn accuracy_page_only_marks_pins() {
+        let app = test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/weather/accuracy?lat=51.5&lon=-0.12&name=London")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("Only pinned places are tracked."), "{body}");
+    }
+
     #[tokio::test]
     async fn static_js_file_returns_200() {
         let app = test_app();
@@ -819,21 +1259,232 @@ mod tests {
 
     // ==================== icloud_private_relay Tests ====================
 
-    // This route calls out to mask-api.icloud.com, so it is the one route that
-    // cannot be exercised offline. Assert only that it is wired up; the logic
-    // behind it is covered by the unit tests in `extractors` (client IP) and
-    // `services::private_relay` (egress range matching).
-    #[tokio::test]
-    async fn icloud_private_relay_route_is_registered() {
-        let app = test_app();
+    /// One London range, standing in for Apple's list.
+    struct OneRange;
+
+    impl RelayRangeSource for OneRange {
+        fn range_for<'a>(
+            &'a self,
+            ip_addr: &'a IpAddr,
+        ) -> BoxFuture<'a, Result<Option<EgressRange>, reqwest::Error>> {
+            let range = EgressRange {
+                subnet: "172.224.226.0/27".parse().unwrap(),
+                country: "GB".to_owned(),
+                region: Some("GB-EN".to_owned()),
+                city: Some("London".to_owned()),
+            };
+            Box::pin(async move { Ok(range.subnet.contains(ip_addr).then_some(range)) })
+        }
+    }
+
+    async fn relay_answer(forwarded_for: &str) -> (StatusCode, String) {
+        let mut state = AppState::production();
+        state.relay = Arc::new(OneRange);
         let request = Request::builder()
             .uri("/icloud-private-relay")
+            .header("x-forwarded-for", forwarded_for)
             .body(Body::empty())
             .unwrap();
-        let response = send_with_connect_info(app, request).await;
-        assert_ne!(response.status(), StatusCode::NOT_FOUND);
-
+        let response = send_with_connect_info(create_app_router(state), request).await;
+        let status = response.status();
         let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
         assert!(content_type.to_str().unwrap().contains("text/plain"));
+        (status, body_string(response.into_body()).await)
+    }
+
+    #[tokio::test]
+    async fn icloud_private_relay_names_the_matching_range() {
+        let (status, body) = relay_answer("172.224.226.5").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            "172.224.226.5 is iCloud Private Relay (172.224.226.0/27, London, GB-EN, GB)"
+        );
+    }
+
+    #[tokio::test]
+    async fn icloud_private_relay_says_so_when_nothing_matches() {
+        let (status, body) = relay_answer("203.0.113.50").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "203.0.113.50 is not iCloud Private Relay");
+    }
+
+    // ==================== Places Endpoint Tests ====================
+
+    async fn suggestions(app: Router, q: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/places?q={q}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = serde_json::from_str(&body_string(response.into_body()).await).unwrap();
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn places_suggests_matches_with_their_report_links() {
+        let (status, body) = suggestions(test_app(), "oak").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 3);
+        assert_eq!(body[0]["name"], "Oakland");
+        assert_eq!(body[0]["detail"], "California, United States");
+        assert_eq!(body[0]["query"], "lat=37.8044&lon=-122.2708&name=Oakland");
+    }
+
+    #[tokio::test]
+    async fn places_is_empty_for_nothing_or_no_match() {
+        for q in ["", "nowhere%20at%20all"] {
+            let (status, body) = suggestions(test_app(), q).await;
+            assert_eq!(status, StatusCode::OK, "{q}");
+            assert_eq!(body, serde_json::json!([]), "{q}");
+        }
+    }
+
+    #[tokio::test]
+    async fn places_offers_past_searches_from_the_first_letters() {
+        let app = test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?q=Oakland")
+                    .header(header::ACCEPT, "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Too short to ask upstream, so only the past search can answer.
+        let (_, body) = suggestions(app, "oa").await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["name"], "Oakland");
+    }
+
+    // ==================== Failing upstream Tests ====================
+
+    /// An upstream that answers every forecast with nothing usable, though
+    /// it can still name a point.
+    struct Broken;
+
+    impl WeatherProvider for Broken {
+        fn forecast(&self, _: f64, _: f64) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+            Box::pin(async { Err(open_meteo::Error::Incomplete("hourly data").into()) })
+        }
+
+        fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+            Box::pin(async move { Err(open_meteo::Error::NoSuchPlace(query.to_owned()).into()) })
+        }
+
+        fn nearest(&self, _: f64, _: f64) -> BoxFuture<'_, Option<Place>> {
+            Box::pin(async {
+                Some(Place {
+                    name: "Oakland".to_owned(),
+                    detail: "California, US".to_owned(),
+                    latitude: 37.80437,
+                    longitude: -122.2708,
+                })
+            })
+        }
+    }
+
+    fn broken_app() -> Router {
+        let mut state = AppState::production();
+        state.weather = Arc::new(Broken);
+        create_app_router(state)
+    }
+
+    #[tokio::test]
+    async fn weather_page_credits_the_fallback_and_what_it_estimated() {
+        let mut state = AppState::production();
+        state.weather = Arc::new(Fallback::new(
+            Arc::new(Broken),
+            Arc::new(Nws::new(Mode::Replay, "localhost")),
+        ));
+        let response = create_app_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi")
+                    .header(header::ACCEPT, "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_str(&body_string(response.into_body()).await).unwrap();
+        let source = &body["report"]["source"];
+        assert_eq!(source["provider"], "the National Weather Service");
+        assert_eq!(source["fallback"], "Open-Meteo returned no hourly data.");
+        assert_eq!(source["estimated"][0], "solar radiation");
+    }
+
+    #[tokio::test]
+    async fn weather_json_is_a_502_when_the_forecast_fails() {
+        let response = broken_app()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi")
+                    .header(header::ACCEPT, "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        // A shared cache must not go on serving the failure.
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CACHE_CONTROL)
+                .and_then(|value| value.to_str().ok()),
+            Some("no-store")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&body_string(response.into_body()).await).unwrap();
+        assert_eq!(body["error"], "Open-Meteo returned no hourly data.");
+        assert!(body["report"].is_null());
+    }
+
+    #[tokio::test]
+    async fn weather_page_explains_a_failed_forecast() {
+        let response = broken_app()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?loc=fidi")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("Open-Meteo returned no hourly data."));
+        assert!(body.contains("Financial District"));
+    }
+
+    #[tokio::test]
+    async fn a_named_point_keeps_its_coordinates_and_distance_without_a_forecast() {
+        let response = broken_app()
+            .oneshot(
+                Request::builder()
+                    .uri("/weather?lat=37.8&lon=-122.2")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = body_string(response.into_body()).await;
+        assert!(body.contains("Open-Meteo returned no hourly data."));
+        assert!(body.contains("37.8000, -122.2000"));
+        assert!(body.contains("3.9 mi from Oakland, the nearest named place."));
     }
 }
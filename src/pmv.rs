@@ -0,0 +1,217 @@
+//! Fanger's Predicted Mean Vote, for deriving the comfort scale.
+//!
+//! The page never runs this on the weather. Felt temperature comes from
+//! [`crate::comfort`]; this is the model the [`crate::scale`] anchors were
+//! solved with, kept so they can be solved again when an outfit or an
+//! assumption changes rather than taken on trust.
+//!
+//! # The model
+//!
+//! PMV predicts the mean vote of a large group on the seven-point ASHRAE
+//! sensation scale, -3 cold to +3 hot, from a steady-state heat balance of the
+//! body: metabolic heat in, and out through skin diffusion, sweat, breathing,
+//! and radiation and convection from the clothing surface. PPD turns that mean
+//! into the share of the group who would call it uncomfortable. Both follow
+//! the computer program in ISO 7730:2005 Annex D line for line, including its
+//! iteration for clothing surface temperature, and reproduce the standard's
+//! Table D.1.
+//!
+//! Fanger, P.O. (1970), *Thermal Comfort*, Danish Technical Press.
+//!
+//! # Range
+//!
+//! ISO 7730 vouches for PMV between -2 and +2, at 0.8 to 4 met, 0 to 2 clo,
+//! 10 to 30°C and air under 1 m/s. The scale leans on it outside most of that,
+//! which is fine for ranking outfits against each other and not for much else.
+//! The hot end is where it gives out first: see [`crate::scale`].
+
+use crate::units::{Speed, Temperature};
+
+/// W/m² of metabolic heat per met.
+const MET_WATTS: f64 = 58.15;
+
+/// m²K/W of insulation per clo.
+const CLO_INSULATION: f64 = 0.155;
+
+/// Where [`felt_for`] looks, in °C. Wider than anything the scale anchors.
+const SEARCH_C: (f64, f64) = (-60.0, 60.0);
+
+/// One person in one steady environment.
+#[derive(Clone, Copy, Debug)]
+pub struct Inputs {
+    pub air: Temperature,
+    /// Mean radiant temperature.
+    pub radiant: Temperature,
+    /// Air speed relative to the body.
+    pub air_speed: Speed,
+    /// Percent.
+    pub relative_humidity: f64,
+    /// Metabolic rate, in met. No external work is assumed.
+    pub met: f64,
+    /// Clothing insulation of the whole ensemble, in clo.
+    pub clo: f64,
+}
+
+/// Predicted Mean Vote, -3 cold through 0 neutral to +3 hot. Unbounded: a
+/// result past ±3 means the model has run off its own scale.
+pub fn vote(inputs: &Inputs) -> f64 {
+    let ta = inputs.air.celsius();
+    let tr = inputs.radiant.celsius();
+    let m = inputs.met * MET_WATTS;
+    let icl = inputs.clo * CLO_INSULATION;
+
+    // Water vapour partial pressure (Pa).
+    let pa = inputs.relative_humidity * 10.0 * (16.6536 - 4030.183 / (ta + 235.0)).exp();
+    let fcl = if icl <= 0.078 {
+        1.0 + 1.29 * icl
+    } else {
+        1.05 + 0.645 * icl
+    };
+    let hcf = 12.1 * inputs.air_speed.meters_per_second().sqrt();
+    let taa = ta + 273.0;
+    let tra = tr + 273.0;
+
+    // Clothing surface temperature, by the standard's fixed-point iteration
+    // in hundreds of kelvin.
+    let p1 = icl * fcl;
+    let p2 = p1 * 3.96;
+    let p3 = p1 * 100.0;
+    let p4 = p1 * taa;
+    let p5 = 308.7 - 0.028 * m + p2 * (tra / 100.0).powi(4);
+    let mut xn = (taa + (35.5 - ta) / (3.5 * icl + 0.1)) / 100.0;
+    let mut xf = xn * 2.0;
+    let mut hc = hcf;
+    for _ in 0..150 {
+        if (xn - xf).abs() <= 0.000_15 {
+            break;
+        }
+        xf = (xf + xn) / 2.0;
+        let hcn = 2.38 * (100.0 * xf - taa).abs().powf(0.25);
+        hc = hcf.max(hcn);
+        xn = (p5 + p4 * hc - p2 * xf.powi(4)) / (100.0 + p3 * hc);
+    }
+    let tcl = 100.0 * xn - 273.0;
+
+    let skin_diffusion = 3.05e-3 * (5733.0 - 6.99 * m - pa);
+    let sweat = if m > MET_WATTS {
+        0.42 * (m - MET_WATTS)
+    } else {
+        0.0
+    };
+    let latent_breath = 1.7e-5 * m * (5867.0 - pa);
+    let dry_breath = 0.0014 * m * (34.0 - ta);
+    let radiation = 3.96 * fcl * (xn.powi(4) - (tra / 100.0).powi(4));
+    let convection = fcl * hc * (tcl - ta);
+
+    (0.303 * (-0.036 * m).exp() + 0.028)
+        * (m - skin_diffusion - sweat - latent_breath - dry_breath - radiation - convection)
+}
+
+/// Predicted Percentage Dissatisfied at a given vote. Never below 5: even at
+/// neutral, someone is unhappy.
+pub fn dissatisfied(vote: f64) -> f64 {
+    100.0 - 95.0 * (-0.033_53 * vote.powi(4) - 0.2179 * vote.powi(2)).exp()
+}
+
+/// The temperature at which `sensation` is the predicted vote, with air and
+/// radiant temperature equal — an operative temperature, which is what the
+/// page's felt temperature stands in for.
+///
+/// `None` if no temperature in a generous range gets there, which at high
+/// metabolic rates is most of the hot side of the scale.
+pub fn felt_for(
+    sensation: f64,
+    clo: f64,
+    met: f64,
+    air_speed: Speed,
+    relative_humidity: f64,
+) -> Option<Temperature> {
+    let vote_at = |celsius: f64| {
+        let temperature = Temperature::from_celsius(celsius);
+        vote(&Inputs {
+            air: temperature,
+            radiant: temperature,
+            air_speed,
+            relative_humidity,
+            met,
+            clo,
+        })
+    };
+
+    // PMV rises with temperature, so bisect.
+    let (mut low, mut high) = SEARCH_C;
+    if !(vote_at(low)..=vote_at(high)).contains(&sensation) {
+        return None;
+    }
+    for _ in 0..60 {
+        let middle = (low + high) / 2.0;
+        if vote_at(middle) < sensation {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(Temperature::from_celsius((low + high) / 2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn still(air: f64, radiant: f64, speed: f64, humidity: f64, met: f64, clo: f64) -> f64 {
+        vote(&Inputs {
+            air: Temperature::from_celsius(air),
+            radiant: Temperature::from_celsius(radiant),
+            air_speed: Speed::from_meters_per_second(speed),
+            relative_humidity: humidity,
+            met,
+            clo,
+        })
+    }
+
+    #[test]
+    fn reproduces_iso_7730_table_d1() {
+        // (ta, tr, va, RH, met, clo) → PMV, PPD, from ISO 7730:2005 Table D.1.
+        let cases = [
+            ((22.0, 22.0, 0.1, 60.0, 1.2, 0.5), -0.75, 17.0),
+            ((27.0, 27.0, 0.1, 60.0, 1.2, 0.5), 0.77, 17.0),
+            ((27.0, 27.0, 0.3, 60.0, 1.2, 0.5), 0.44, 9.0),
+            ((23.5, 25.5, 0.1, 60.0, 1.2, 0.5), -0.01, 5.0),
+            ((19.0, 19.0, 0.1, 40.0, 1.2, 1.0), -0.60, 13.0),
+        ];
+        for ((ta, tr, va, rh, met, clo), pmv, ppd) in cases {
+            let got = still(ta, tr, va, rh, met, clo);
+            assert!((got - pmv).abs() < 0.02, "{ta}/{tr}°C: {got:.2} vs {pmv}");
+            assert_eq!(dissatisfied(got).round(), ppd, "{ta}/{tr}°C");
+        }
+    }
+
+    #[test]
+    fn neutral_leaves_five_percent_unhappy() {
+        assert!((dissatisfied(0.0) - 5.0).abs() < 1e-9);
+        assert!(dissatisfied(-1.0) > 20.0 && dissatisfied(1.0) > 20.0);
+    }
+
+    #[test]
+    fn solving_inverts_the_vote() {
+        let speed = Speed::from_meters_per_second(0.1);
+        let felt = felt_for(0.5, 1.0, 1.2, speed, 50.0).unwrap();
+        let back = still(felt.celsius(), felt.celsius(), 0.1, 50.0, 1.2, 1.0);
+        assert!((back - 0.5).abs() < 1e-6, "{back}");
+    }
+
+    #[test]
+    fn more_clothing_is_neutral_somewhere_colder() {
+        let speed = Speed::from_meters_per_second(0.5);
+        let light = felt_for(0.0, 0.5, 2.4, speed, 50.0).unwrap();
+        let heavy = felt_for(0.0, 1.5, 2.4, speed, 50.0).unwrap();
+        assert!(heavy < light, "{heavy:?} vs {light:?}");
+    }
+
+    #[test]
+    fn says_so_when_a_sensation_is_out_of_reach() {
+        // Past the top of any scale anyone votes on.
+        let speed = Speed::from_meters_per_second(0.5);
+        assert!(felt_for(50.0, 0.5, 1.2, speed, 50.0).is_none());
+    }
+}
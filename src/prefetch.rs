@@ -0,0 +1,200 @@
+//! Keeping the pinned locations' forecasts warm.
+//!
+//! The pins are the pages actually visited, mostly first thing in the
+//! morning, and each would otherwise wait on upstream whenever its cached
+//! forecast had lapsed overnight. [`keep_warm`] refreshes every pin a little
+//! before [`CACHE_TTL`] runs out, so those pages always render from memory.
+//!
+//! Rounds are jittered so two servers behind one address (blue and green,
+//! briefly, during a deploy) drift apart rather than call upstream in step.
+//! When a round fails, the next waits twice as long, up to
+//! [`LONGEST_BACKOFF`]: an outage is not shortened by asking more often, and
+//! a page will still fetch for itself if asked.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::locations;
+use crate::services::open_meteo::CACHE_TTL;
+use crate::services::WeatherProvider;
+
+/// How long before a forecast lapses the round refreshing it starts. Long
+/// enough for every pin to be fetched in turn, each within its own timeout.
+const LEAD: Duration = Duration::from_secs(90);
+
+/// The most a round may start early or late, either way.
+const JITTER: Duration = Duration::from_secs(20);
+
+/// The longest wait after failed rounds.
+const LONGEST_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// Refreshes every pinned location, round after round, until `stop` turns
+/// true. A round under way when it does is abandoned; any fetch it started
+/// still lands in the cache.
+pub async fn keep_warm(weather: Arc<dyn WeatherProvider>, mut stop: watch::Receiver<bool>) {
+    let mut failures = 0;
+    loop {
+        let round = async {
+            failures = if refresh_pins(weather.as_ref()).await {
+                0
+            } else {
+                failures + 1
+            };
+            tokio::time::sleep(delay(failures, jitter())).await;
+        };
+        tokio::select! {
+            _ = stop.wait_for(|&stop| stop) => break,
+            () = round => {}
+        }
+    }
+    tracing::info!("Stopped refreshing pinned locations");
+}
+
+/// Refreshes each pin in turn, logging any that fail. True if none did.
+async fn refresh_pins(weather: &dyn WeatherProvider) -> bool {
+    let mut ok = true;
+    for location in locations::pinned().all() {
+        if let Err(err) = weather.refresh(location.latitude, location.longitude).await {
+            tracing::warn!("Could not refresh {}: {err}", location.slug);
+            ok = false;
+        }
+    }
+    ok
+}
+
+/// The wait before the next round, after `failures` failed rounds in a row,
+/// shifted by `jitter` between -1 and 1 of [`JITTER`].
+fn delay(failures: u32, jitter: f64) -> Duration {
+    let every = CACHE_TTL - LEAD;
+    let wait = if failures == 0 {
+        every
+    } else {
+        every
+            .saturating_mul(1 << failures.min(16))
+            .min(LONGEST_BACKOFF)
+    };
+    if jitter < 0.0 {
+        wait.saturating_sub(JITTER.mul_f64(-jitter))
+    } else {
+        wait + JITTER.mul_f64(jitter)
+    }
+}
+
+/// A number between -1 and 1, different each call. Scheduling needs nothing
+/// better than the randomly keyed hasher the standard library already has.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::Mode;
+    use crate::services::open_meteo::{self, Forecast, OpenMeteo, Place};
+    use crate::services::{BoxFuture, Error};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Replayed Open-Meteo, counting refreshes and failing every one if told
+    /// to.
+    struct Counting {
+        inner: OpenMeteo,
+        refreshes: AtomicUsize,
+        down: bool,
+    }
+
+    impl Default for Counting {
+        fn default() -> Self {
+            Counting {
+                inner: OpenMeteo::new(Mode::Replay),
+                refreshes: AtomicUsize::new(0),
+                down: false,
+            }
+        }
+    }
+
+    impl WeatherProvider for Counting {
+        fn forecast(
+            &self,
+            latitude: f64,
+            longitude: f64,
+        ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+            WeatherProvider::forecast(&self.inner, latitude, longitude)
+        }
+
+        fn refresh(
+            &self,
+            latitude: f64,
+            longitude: f64,
+        ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+            self.refreshes.fetch_add(1, Ordering::SeqCst);
+            if self.down {
+                return Box::pin(async {
+                    Err(open_meteo::Error::Incomplete("hourly data").into())
+                });
+            }
+            WeatherProvider::refresh(&self.inner, latitude, longitude)
+        }
+
+        fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+            WeatherProvider::geocode(&self.inner, query)
+        }
+    }
+
+    #[tokio::test]
+    async fn a_round_refreshes_every_pin() {
+        let weather = Counting::default();
+        assert!(refresh_pins(&weather).await);
+        assert_eq!(
+            weather.refreshes.load(Ordering::SeqCst),
+            locations::pinned().all().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn a_round_with_a_failure_is_reported() {
+        let weather = Counting {
+            down: true,
+            ..Counting::default()
+        };
+        assert!(!refresh_pins(&weather).await);
+    }
+
+    #[test]
+    fn rounds_start_before_the_cache_lapses() {
+        assert_eq!(delay(0, 0.0), CACHE_TTL - LEAD);
+        assert_eq!(delay(0, 1.0), CACHE_TTL - LEAD + JITTER);
+        assert_eq!(delay(0, -1.0), CACHE_TTL - LEAD - JITTER);
+        assert!(delay(0, 1.0) < CACHE_TTL);
+    }
+
+    #[test]
+    fn failed_rounds_back_off_up_to_a_limit() {
+        assert_eq!(delay(1, 0.0), 2 * (CACHE_TTL - LEAD));
+        assert_eq!(delay(2, 0.0), 4 * (CACHE_TTL - LEAD));
+        assert_eq!(delay(10, 0.0), LONGEST_BACKOFF);
+        assert_eq!(delay(u32::MAX, 0.0), LONGEST_BACKOFF);
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        for _ in 0..1000 {
+            assert!((-1.0..1.0).contains(&jitter()));
+        }
+    }
+
+    #[tokio::test]
+    async fn stops_when_told() {
+        let (stop, stopped) = watch::channel(false);
+        let task = tokio::spawn(keep_warm(Arc::new(Counting::default()), stopped));
+        stop.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("still running")
+            .unwrap();
+    }
+}
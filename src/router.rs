@@ -3,7 +3,7 @@
 use axum::http::header::{HeaderName, HeaderValue};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::routing::{any, get};
+use axum::routing::{any, get, post};
 use axum::Router;
 use std::convert::Infallible;
 use tower::service_fn;
@@ -13,8 +13,10 @@ use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, Tr
 use tracing::Level;
 
 use crate::handlers::{
-    echo, icloud_private_relay, index, microwave, sha, slot, uuid_route, weather,
+    best_time, calendar, commute, compare, echo, forecast_accuracy, icloud_private_relay, index,
+    microwave, places, record_feedback, sha, slot, uuid_route, weather,
 };
+use crate::state::AppState;
 
 /// Returns a 404 Not Found response.
 fn not_found() -> Response {
@@ -29,8 +31,9 @@ fn not_found() -> Response {
         .into_response()
 }
 
-/// Creates the main application router with all routes and middleware.
-pub fn create_app_router() -> Router {
+/// Creates the main application router with all routes and middleware, serving
+/// from the given services.
+pub fn create_app_router(state: AppState) -> Router {
     // Static files carry no version in their names, so a browser that caches
     // `/styles.css` keeps serving it after a deploy changes it. With no
     // `Cache-Control` at all the browser is free to invent a freshness lifetime
@@ -57,7 +60,15 @@ pub fn create_app_router() -> Router {
         .route("/slot", get(slot))
         .route("/microwave", get(microwave))
         .route("/weather", get(weather))
+        .route("/weather.ics", get(calendar))
+        .route("/weather/best", get(best_time))
+        .route("/weather/compare", get(compare))
+        .route("/weather/commute", get(commute))
+        .route("/weather/feedback", post(record_feedback))
+        .route("/weather/accuracy", get(forecast_accuracy))
+        .route("/api/places", get(places))
         .route("/echo", any(echo))
+        .with_state(state)
         .fallback_service(static_files)
         // Security headers
         .layer(SetResponseHeaderLayer::if_not_present(
@@ -8,8 +8,8 @@
 //! The calibration is deliberately personal, not a published index: 5 is
 //! neutral — neither cold nor warm, which for this site's owner is also the
 //! weather they like best — and every other point is described relative to
-//! that. It is committed to the repo for the same reason the home location is:
-//! there is nowhere else to put it.
+//! that. It lives in `scales.json` beside the pinned locations, for the same
+//! reason they do: there is nowhere else to put it.
 //!
 //! Spacing is uneven because the clothing is. Each temperature is solved, not
 //! chosen: it is the felt temperature at which that point's outfit produces the
@@ -45,127 +45,423 @@
 //!
 //! # Where the temperatures come from
 //!
-//! Not from taste. Each outfit is priced in ASHRAE 55 garment insulation, and
-//! the temperature is then solved so that Fanger's PMV returns the sensation
-//! the point claims, for a walker at 2.4 met in a light breeze:
+//! Mostly not from taste. Each outfit is priced in clothing insulation, and
+//! the temperature is then solved with Fanger's PMV ([`crate::pmv`]) so that
+//! the outfit produces the sensation the point claims, for a walker at 2.4 met
+//! with 2.5 m/s of air going past — a walking pace into a light breeze:
 //!
 //! ```text
-//!   9  shorts and a light t-shirt        +2.2  warm, barely bearable    94F
-//!   8  shorts and a t-shirt               0.0  comfortable              78F
-//!   7  jeans and a t-shirt                0.0  comfortable              73F
-//!   6  jeans and a long-sleeve t-shirt    0.0  comfortable              70F
-//!   5  jeans and a light jacket           0.0  comfortable              61F
-//!   4  jeans and a warm jacket            0.0  comfortable              53F
-//!   3  coat                               0.0  comfortable              46F
-//!   2  winter coat                        0.0  comfortable              37F
-//!   1  heavy winter coat, hat and gloves -0.5  slightly cool            16F
+//!      outfit                              clo   PMV                       solved shipped
+//!   9  shorts and a light t-shirt           -   +2.2  warm, barely bearable     -     94F
+//!   8  shorts and a t-shirt               0.22   0.0  comfortable           77.9F    78F
+//!   7  jeans and a t-shirt                0.44   0.0  comfortable           73.3F    73F
+//!   6  jeans and a long-sleeve t-shirt    0.60   0.0  comfortable           70.0F    70F
+//!   5  jeans and a light jacket           0.95   0.0  comfortable           62.9F    61F
+//!   4  jeans and a warm jacket            1.45   0.0  comfortable           53.3F    53F
+//!   3  coat                               1.85   0.0  comfortable           45.9F    46F
+//!   2  winter coat                        2.35   0.0  comfortable           36.9F    37F
+//!   1  heavy winter coat, hat and gloves  2.85  -0.5  slightly cool         15.6F    16F
 //! ```
 //!
+//! The table is [`OUTFITS`], and `website derive-scale` solves it again. The
+//! shipped temperatures in `scales.json` are older than this solver: they came
+//! from a calculation that was never committed, and it reproduces them only so
+//! far. Every point but 5 lands within half a degree. 5, priced at its
+//! documented garments, solves 1.9°F warmer than the 61°F it ships at. That
+//! gap is recorded rather than closed, since moving 5 would move every reading
+//! between 4 and 6, and a test holds the solver to it, so a change to an
+//! outfit or an assumption that widens it does not go unnoticed.
+//!
+//! The agreement is weaker evidence than it looks. 4 to 1 are priced by
+//! judgement (below), and 2.5 m/s is this solver's assumption where the
+//! original said only "a light breeze". Where those are involved, agreement
+//! shows the assumptions fit the shipped anchors, not that they produce them.
+//! Only 8 to 5 are lookups, and of those 5 is the one that disagrees.
+//!
+//! 8 down to 5 are sums of ISO 7730 Annex C garment values: briefs, socks and
+//! shoes (0.07) under a t-shirt (0.09) and shorts (0.06), or jeans taken as
+//! heavy trousers (0.28); a long-sleeve shirt (0.25) in place of the t-shirt;
+//! and a jacket (0.35) over both. Below that the garment tables run out —
+//! they stop at a single indoor-weight coat — so 4 to 1 are whole-ensemble
+//! estimates, and are the part of the calibration that is judgement rather
+//! than lookup.
+//!
 //! Only 9 is deliberately uncomfortable, because that is the one end clothing
 //! cannot rescue: below a t-shirt there is nothing left to take off. The cold
 //! end can always be dressed for, so it is solved to comfortable and only the
 //! last rung is allowed to bite.
 //!
+//! 9 is also the one rung PMV cannot place. At 2.4 met it passes +3 well
+//! before 94°F in any clothing, which is the overstatement of warmth at high
+//! activity noted below, so 94°F is set by hand: the hottest day that is still
+//! bearable at a walk in the least there is to wear.
+//!
 //! 8 carries a second constraint that the solve satisfies without being asked:
-//! at 78°F shorts come out at 0.00 and jeans at +0.57, comfortable and slightly
-//! warm respectively, which is what that point is supposed to mean.
+//! at 78°F shorts come out at 0.03 and jeans at +0.78, comfortable and slightly
+//! warm respectively, which is what that point is supposed to mean. (The
+//! original calculation gave 0.00 and +0.57, which this one does not reproduce
+//! at any air speed.)
 //!
 //! Two sensitivities worth knowing. The anchors encode a pace as well as a
 //! preference — at a brisk 2.9 met the whole scale reads about half a point
 //! warm, at a stroll half a point cool. And PMV is known to overstate warmth
 //! above roughly 2 met (Humphreys & Nicol, 2002), which is the direction that
-//! matters most at 9.
+//! matters most at 9. [`crate::comfort::Activity`] applies the pace half of
+//! that to the felt temperature before it reaches this scale, so a run reads
+//! warm on the same anchors rather than needing its own.
+//!
+//! # Other people's scales
+//!
+//! The ladder above is one person's. Someone who runs cold needs the same coat
+//! at a warmer temperature, so `scales.json` holds any number of named
+//! profiles, each a full set of eleven points, chosen per request with
+//! `?scale=`. A profile keeps the shape of this one — eleven points, each
+//! warmer than the last, no sensation word used twice — and the file is
+//! refused whole if any profile breaks it, so the server will not start on a
+//! ladder the page cannot render.
+//!
+//! A [`Score`] remembers which profile produced it, so its word, advice and
+//! layer always come from the same ladder as its number.
 
-use crate::units::Temperature;
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-/// `(felt °F, score)`, ascending. Interpolated between, clamped outside.
-const ANCHORS: [(f64, f64); 11] = [
-    (-34.0, 0.0),
-    (16.0, 1.0),
-    (37.0, 2.0),
-    (46.0, 3.0),
-    (53.0, 4.0),
-    (61.0, 5.0),
-    (70.0, 6.0),
-    (73.0, 7.0),
-    (78.0, 8.0),
-    (94.0, 9.0),
-    (105.0, 10.0),
-];
+use serde::Deserialize;
 
-/// `(what it feels like, what to wear)` for each whole point.
-///
-/// The second half is the point of the scale: one number, one outfit, no
-/// hedging about what you might get away with. Deciding whether to accommodate
-/// more than one of these is what the three numbers at the top of the page are
-/// for — a single score should never have to say "or".
+use crate::config::get_config;
+use crate::pmv;
+use crate::units::{Speed, Temperature};
+
+/// Points on every scale, 0 through 10.
+const POINTS: usize = 11;
+
+/// One whole point of a profile.
 ///
-/// Every sensation word appears exactly once and every outfit differs from its
-/// neighbours', so both halves identify the point on their own. Read bottom to
-/// top it is a ladder of insulation, roughly even in `clo` per step, which is
-/// why the temperatures below are unevenly spaced: adding a jacket buys more
-/// degrees than swapping a t-shirt for a long-sleeve shirt.
-const LABELS: [(&str, &str, Option<&str>); 11] = [
-    ("dangerous cold", "avoid outdoors", None),
-    (
-        "brutal",
-        "heavy winter coat, hat and gloves",
-        Some("a heavy winter coat, hat and gloves"),
-    ),
-    ("freezing", "winter coat", Some("a winter coat")),
-    ("cold", "coat", Some("a coat")),
-    ("chilly", "jeans and a warm jacket", Some("a warm jacket")),
-    (
-        "neutral",
-        "jeans and a light jacket",
-        Some("a light jacket"),
-    ),
-    (
-        "mild",
-        "jeans and a long-sleeve t-shirt",
-        Some("a long-sleeve t-shirt"),
-    ),
-    // From here up there is nothing left to carry: swapping jeans for shorts
-    // is not something anyone does halfway through a walk.
-    ("pleasant", "jeans and a t-shirt", None),
-    ("warm", "shorts and a t-shirt", None),
-    ("hot", "shorts and a light t-shirt", None),
-    ("dangerous heat", "avoid outdoors", None),
-];
+/// The advice is the point of the scale: one number, one outfit, no hedging
+/// about what you might get away with. Deciding whether to accommodate more
+/// than one of these is what the three numbers at the top of the page are for
+/// — a single score should never have to say "or".
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Point {
+    /// The felt temperature that lands exactly on this point. Scores are
+    /// interpolated between neighbours and clamped outside the ends.
+    felt_f: f64,
+    /// What it feels like, in one word.
+    word: String,
+    /// What to wear.
+    advice: String,
+    /// The item this point adds over the one above it, or nothing when there
+    /// is nothing left to take off.
+    carry: Option<String>,
+}
 
-/// A point on the comfort scale, 0 through 10.
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
-pub struct Score(f64);
+/// A named calibration: eleven points, each warmer than the last.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// URL-safe identifier used as `?scale=`.
+    slug: String,
+    /// Label for the picker.
+    name: String,
+    points: Vec<Point>,
+}
 
-impl Score {
-    /// Only for arithmetic on an existing score, such as stepping down a rung.
-    /// Felt temperatures come in through [`score`].
-    pub fn from_value(points: f64) -> Self {
-        Score(points.clamp(0.0, 10.0))
+impl Profile {
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Places a felt temperature on this scale.
+    pub fn score(&'static self, felt: Temperature) -> Score {
+        let degrees = felt.fahrenheit();
+        let point = |index: usize| (self.points[index].felt_f, index as f64);
+
+        let (mut low_f, mut low_score) = point(0);
+        if degrees <= low_f {
+            return self.point(low_score);
+        }
+
+        for index in 1..POINTS {
+            let (high_f, high_score) = point(index);
+            if degrees <= high_f {
+                let position = (degrees - low_f) / (high_f - low_f);
+                return self.point(low_score + position * (high_score - low_score));
+            }
+            (low_f, low_score) = (high_f, high_score);
+        }
+
+        self.point(10.0)
+    }
+
+    /// A number on this scale directly, for arithmetic such as stepping down
+    /// a rung. Felt temperatures come in through [`Profile::score`].
+    pub fn point(&'static self, points: f64) -> Score {
+        Score {
+            points: points.clamp(0.0, 10.0),
+            profile: self,
+        }
+    }
+
+    /// The felt temperature that lands on a given number: [`Profile::score`]
+    /// run backwards. Outside 0 to 10 it gives the end anchor.
+    pub fn felt_at(&self, points: f64) -> Temperature {
+        let points = points.clamp(0.0, 10.0);
+        let low = (points.floor() as usize).min(POINTS - 2);
+        let (from, to) = (self.points[low].felt_f, self.points[low + 1].felt_f);
+        let fahrenheit = from + (points - low as f64) * (to - from);
+        Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0)
+    }
+
+    /// How many degrees warmer this profile's anchors sit than another's, on
+    /// average over 1 to 9. The ends are where going out stops being an
+    /// option, which does not move with preference.
+    pub fn offset_from(&self, other: &Profile) -> f64 {
+        let inner = 1..POINTS - 1;
+        let total: f64 = self.points[inner.clone()]
+            .iter()
+            .zip(&other.points[inner.clone()])
+            .map(|(mine, theirs)| mine.felt_f - theirs.felt_f)
+            .sum();
+        total / inner.len() as f64
+    }
+
+    /// The whole scale, for the key printed on the page. Without it the colours
+    /// and the numbers are both undecodable.
+    ///
+    /// Hottest first, the way a thermometer is drawn.
+    pub fn key(&self) -> Vec<(u8, &str, &str, i32)> {
+        self.points
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(level, point)| {
+                (
+                    level as u8,
+                    point.word.as_str(),
+                    point.advice.as_str(),
+                    point.felt_f as i32,
+                )
+            })
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |why: String| Err(Error::Invalid(format!("{}: {why}", self.slug)));
+        if self.slug.is_empty()
+            || !self
+                .slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return invalid("the slug is not URL-safe; use a-z, 0-9 and -".to_owned());
+        }
+        if self.points.len() != POINTS {
+            return invalid(format!(
+                "has {} points, where a scale has {POINTS}",
+                self.points.len()
+            ));
+        }
+        for (level, pair) in self.points.windows(2).enumerate() {
+            if pair[1].felt_f <= pair[0].felt_f {
+                return invalid(format!(
+                    "{} is {}\u{b0}F, no warmer than {} at {}\u{b0}F",
+                    level + 1,
+                    pair[1].felt_f,
+                    level,
+                    pair[0].felt_f
+                ));
+            }
+        }
+        for (level, point) in self.points.iter().enumerate() {
+            // If a word repeats, the extra resolution is fake: the reader
+            // cannot tell which point they are looking at from the text.
+            if self.points[..level]
+                .iter()
+                .any(|other| other.word == point.word)
+            {
+                return invalid(format!("\u{201c}{}\u{201d} is used twice", point.word));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The file as written, before validation.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfilesFile {
+    /// Slug of the profile used when none is asked for.
+    default: String,
+    /// The picker, in display order.
+    profiles: Vec<Profile>,
+}
+
+/// Every profile, validated.
+#[derive(Debug)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+    default: usize,
+}
+
+impl Profiles {
+    /// Parses and validates the contents of a scales file.
+    pub fn parse(json: &str) -> Result<Profiles, Error> {
+        let file: ProfilesFile = serde_json::from_str(json)?;
+        for (index, profile) in file.profiles.iter().enumerate() {
+            profile.validate()?;
+            if file.profiles[..index]
+                .iter()
+                .any(|other| other.slug == profile.slug)
+            {
+                return Err(Error::Invalid(format!(
+                    "slug \u{201c}{}\u{201d} is used twice",
+                    profile.slug
+                )));
+            }
+        }
+        let default = file
+            .profiles
+            .iter()
+            .position(|profile| profile.slug == file.default)
+            .ok_or_else(|| {
+                Error::Invalid(format!(
+                    "default \u{201c}{}\u{201d} is not a profile",
+                    file.default
+                ))
+            })?;
+        Ok(Profiles {
+            profiles: file.profiles,
+            default,
+        })
     }
 
+    /// Reads and validates a scales file.
+    pub fn read(path: &Path) -> Result<Profiles, Error> {
+        let json = std::fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        Profiles::parse(&json)
+    }
+
+    /// The scale used when none is asked for.
+    pub fn default(&self) -> &Profile {
+        &self.profiles[self.default]
+    }
+
+    /// Looks up a profile by its `?scale=` slug.
+    pub fn find(&self, slug: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.slug == slug)
+    }
+
+    /// Every profile, in display order.
+    pub fn all(&self) -> &[Profile] {
+        &self.profiles
+    }
+}
+
+/// Why a scales file was refused.
+#[derive(Debug)]
+pub enum Error {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Not JSON, or not the shape of a scales file.
+    Parse(serde_json::Error),
+    /// Well-formed, but a profile is not a usable ladder.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "could not read {}: {source}", path.display())
+            }
+            Error::Parse(err) => write!(f, "scales file is malformed: {err}"),
+            Error::Invalid(why) => write!(f, "scales file is invalid: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } => Some(source),
+            Error::Parse(err) => Some(err),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+static PROFILES: OnceLock<Profiles> = OnceLock::new();
+
+/// Reads the scales file, once. `main` calls this before listening so a bad
+/// file stops the server instead of the first request.
+pub fn load() -> Result<&'static Profiles, Error> {
+    if let Some(profiles) = PROFILES.get() {
+        return Ok(profiles);
+    }
+    let profiles = Profiles::read(&get_config().scales_path)?;
+    Ok(PROFILES.get_or_init(|| profiles))
+}
+
+/// Every profile. Reads the file on first use if [`load`] has not already.
+pub fn profiles() -> &'static Profiles {
+    load().unwrap_or_else(|err| panic!("no comfort scales: {err}"))
+}
+
+/// A point on a comfort scale, 0 through 10, and the profile it belongs to.
+#[derive(Clone, Copy)]
+pub struct Score {
+    points: f64,
+    profile: &'static Profile,
+}
+
+impl Score {
     pub fn value(self) -> f64 {
-        self.0
+        self.points
+    }
+
+    /// Another number on the same scale.
+    pub fn with_value(self, points: f64) -> Self {
+        self.profile.point(points)
+    }
+
+    fn point(self) -> &'static Point {
+        &self.profile.points[usize::from(self.level())]
     }
 
     /// What the nearest whole point feels like, in one word.
     pub fn word(self) -> &'static str {
-        LABELS[self.level() as usize].0
+        &self.point().word
     }
 
     /// What to wear at the nearest whole point.
     pub fn advice(self) -> &'static str {
-        LABELS[self.level() as usize].1
+        &self.point().advice
     }
 
     /// The item this point adds over the one above it — what you would carry to
     /// be ready for it, rather than wear the whole time.
     ///
-    /// `None` from 8 up: at a t-shirt there is nothing left to take off, so a
+    /// `None` where the profile says there is nothing left to take off, so a
     /// warmer point cannot be reached by carrying something.
     pub fn layer(self) -> Option<&'static str> {
-        LABELS[self.level() as usize].2
+        self.point().carry.as_deref()
     }
 
     /// Both together, for a caption that has room for them.
@@ -183,73 +479,290 @@ impl Score {
     /// formatter and `round` disagree, because the decimal is not representable
     /// and each breaks the tie its own way.
     pub fn level(self) -> u8 {
-        let printed: f64 = self.to_string().parse().unwrap_or(self.0);
+        let printed: f64 = self.to_string().parse().unwrap_or(self.points);
         printed.round().clamp(0.0, 10.0) as u8
     }
 
     pub fn min(self, other: Self) -> Self {
-        Score(self.0.min(other.0))
+        if other.points < self.points {
+            other
+        } else {
+            self
+        }
     }
 
     pub fn max(self, other: Self) -> Self {
-        Score(self.0.max(other.0))
+        if other.points > self.points {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Scores compare by number alone; every score in one request comes from the
+/// same profile.
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.points == other.points
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.points.partial_cmp(&other.points)
+    }
+}
+
+impl fmt::Debug for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Score({} on {})", self.points, self.profile.slug)
     }
 }
 
 /// One decimal, always — `5` and `5.0` should not both appear on the page.
 impl fmt::Display for Score {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:.1}", self.0)
+        write!(f, "{:.1}", self.points)
     }
 }
 
-/// The whole scale, for the key printed on the page. Without it the colours
-/// and the numbers are both undecodable.
-///
-/// Hottest first, the way a thermometer is drawn.
-pub fn key() -> Vec<(u8, &'static str, &'static str, i32)> {
-    ANCHORS
+/// How the standard profile's anchors were derived: who is wearing what, and
+/// how it should feel to them.
+#[derive(Clone, Copy, Debug)]
+pub struct Outfit {
+    /// The point this outfit anchors.
+    pub point: u8,
+    pub wear: &'static str,
+    /// Whole-ensemble insulation, underwear and shoes included.
+    pub clo: f64,
+    /// The PMV the point claims for it.
+    pub sensation: f64,
+}
+
+/// The walk the anchors are solved for, in met. The same as
+/// [`crate::comfort::Activity::Walking`], which is the point.
+pub const CALIBRATION_MET: f64 = 2.4;
+
+/// Air past the body: a walking pace into a light breeze.
+pub const CALIBRATION_AIR_SPEED: Speed = Speed::from_meters_per_second(2.5);
+
+/// Percent. Humidity moves the solved anchors by well under a degree.
+pub const CALIBRATION_HUMIDITY: f64 = 50.0;
+
+/// Points 1 through 8, coldest first. 0, 9 and 10 are not solved; see the
+/// module docs for why.
+pub const OUTFITS: [Outfit; 8] = [
+    Outfit {
+        point: 1,
+        wear: "heavy winter coat, hat and gloves",
+        clo: 2.85,
+        sensation: -0.5,
+    },
+    Outfit {
+        point: 2,
+        wear: "winter coat",
+        clo: 2.35,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 3,
+        wear: "coat",
+        clo: 1.85,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 4,
+        wear: "jeans and a warm jacket",
+        clo: 1.45,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 5,
+        wear: "jeans and a light jacket",
+        clo: 0.95,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 6,
+        wear: "jeans and a long-sleeve t-shirt",
+        clo: 0.60,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 7,
+        wear: "jeans and a t-shirt",
+        clo: 0.44,
+        sensation: 0.0,
+    },
+    Outfit {
+        point: 8,
+        wear: "shorts and a t-shirt",
+        clo: 0.22,
+        sensation: 0.0,
+    },
+];
+
+impl Outfit {
+    /// The felt temperature at which this outfit gives its sensation on the
+    /// calibration walk.
+    pub fn solve(&self) -> Option<Temperature> {
+        pmv::felt_for(
+            self.sensation,
+            self.clo,
+            CALIBRATION_MET,
+            CALIBRATION_AIR_SPEED,
+            CALIBRATION_HUMIDITY,
+        )
+    }
+}
+
+/// [`OUTFITS`] solved again, one line per point, for `website derive-scale`:
+/// the outfit, the vote it is solved for and the share of a crowd that vote
+/// leaves unhappy, and the felt temperature that comes out.
+pub fn derivation() -> String {
+    OUTFITS
         .iter()
-        .enumerate()
-        .rev()
-        .map(|(level, (degrees, _))| {
-            let (word, advice, _) = LABELS[level];
-            (level as u8, word, advice, *degrees as i32)
+        .map(|outfit| {
+            let felt = outfit.solve().map_or_else(
+                || "out of reach".to_owned(),
+                |felt| format!("{:.1}\u{b0}F", felt.fahrenheit()),
+            );
+            format!(
+                "{:>2}  {:<34}{:>5.2} clo  PMV {:>+4.1}  PPD {:>3.0}%  {felt}\n",
+                outfit.point,
+                outfit.wear,
+                outfit.clo,
+                outfit.sensation,
+                pmv::dissatisfied(outfit.sensation)
+            )
         })
         .collect()
 }
 
-/// Places a felt temperature on the scale.
-pub fn score(felt: Temperature) -> Score {
-    let degrees = felt.fahrenheit();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standard() -> &'static Profile {
+        profiles()
+            .find("standard")
+            .expect("the shipped scales have a standard")
+    }
+
+    fn at(fahrenheit: f64) -> Score {
+        standard().score(Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0))
+    }
+
+    /// A profile file with one profile, its points given as `(°F, word)`.
+    fn one_profile(points: &[(f64, &str)]) -> String {
+        let points: Vec<String> = points
+            .iter()
+            .map(|(felt_f, word)| {
+                format!(r#"{{"felt_f": {felt_f}, "word": "{word}", "advice": "x", "carry": null}}"#)
+            })
+            .collect();
+        format!(
+            r#"{{"default": "me", "profiles": [{{"slug": "me", "name": "Me", "points": [{}]}}]}}"#,
+            points.join(", ")
+        )
+    }
+
+    /// Eleven valid points, two degrees apart.
+    fn ladder() -> Vec<(f64, String)> {
+        (0..11)
+            .map(|level| (f64::from(level) * 2.0, format!("word{level}")))
+            .collect()
+    }
 
-    let (mut low_f, mut low_score) = ANCHORS[0];
-    if degrees <= low_f {
-        return Score(low_score);
+    fn rejection(points: &[(f64, String)]) -> String {
+        let points: Vec<(f64, &str)> = points
+            .iter()
+            .map(|(felt_f, word)| (*felt_f, word.as_str()))
+            .collect();
+        Profiles::parse(&one_profile(&points))
+            .unwrap_err()
+            .to_string()
     }
 
-    for (high_f, high_score) in ANCHORS.into_iter().skip(1) {
-        if degrees <= high_f {
-            let position = (degrees - low_f) / (high_f - low_f);
-            return Score(low_score + position * (high_score - low_score));
+    #[test]
+    fn felt_at_undoes_score() {
+        for fahrenheit in [-10.0, 20.0, 52.0, 61.0, 75.5, 99.0] {
+            let felt = Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0);
+            let back = standard().felt_at(standard().score(felt).value());
+            assert!(
+                (back.fahrenheit() - fahrenheit).abs() < 1e-9,
+                "{fahrenheit}"
+            );
         }
-        (low_f, low_score) = (high_f, high_score);
+        assert_eq!(standard().felt_at(10.0).round_fahrenheit(), 105);
     }
 
-    Score(10.0)
-}
+    #[test]
+    fn runs_cold_sits_five_degrees_up() {
+        let runs_cold = profiles().find("runs-cold").unwrap();
+        assert!((runs_cold.offset_from(standard()) - 5.0).abs() < 1e-9);
+        assert_eq!(standard().offset_from(standard()), 0.0);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn outfits_solve_to_the_standard_anchors_but_for_the_recorded_gap() {
+        for outfit in OUTFITS {
+            let solved = outfit.solve().expect("every outfit is solvable");
+            let anchor = standard().points[usize::from(outfit.point)].felt_f;
+            // 5 ships cooler than its garments solve to; see the module docs.
+            let gap = if outfit.point == 5 { 1.9 } else { 0.0 };
+            assert!(
+                (solved.fahrenheit() - anchor - gap).abs() < 0.5,
+                "{} solves to {:.1}\u{b0}F against {anchor}\u{b0}F",
+                outfit.wear,
+                solved.fahrenheit()
+            );
+        }
+    }
 
-    fn at(fahrenheit: f64) -> Score {
-        score(Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0))
+    #[test]
+    fn at_eight_shorts_are_comfortable_and_jeans_are_warm() {
+        let at_eight = Temperature::from_celsius((78.0 - 32.0) * 5.0 / 9.0);
+        let vote = |clo: f64| {
+            pmv::vote(&pmv::Inputs {
+                air: at_eight,
+                radiant: at_eight,
+                air_speed: CALIBRATION_AIR_SPEED,
+                relative_humidity: CALIBRATION_HUMIDITY,
+                met: CALIBRATION_MET,
+                clo,
+            })
+        };
+        let shorts = vote(OUTFITS[7].clo);
+        let jeans = vote(OUTFITS[6].clo);
+        assert!(shorts.abs() < 0.1, "{shorts:.2}");
+        assert!((0.5..1.5).contains(&jeans), "{jeans:.2}");
+    }
+
+    #[test]
+    fn pmv_cannot_place_the_hot_end() {
+        let nine = Temperature::from_celsius((94.0 - 32.0) * 5.0 / 9.0);
+        let vote = pmv::vote(&pmv::Inputs {
+            air: nine,
+            radiant: nine,
+            air_speed: CALIBRATION_AIR_SPEED,
+            relative_humidity: CALIBRATION_HUMIDITY,
+            met: CALIBRATION_MET,
+            clo: 0.0,
+        });
+        assert!(vote > 3.0, "{vote:.2}");
+    }
+
+    #[test]
+    fn outfits_are_solved_for_the_default_walk() {
+        assert_eq!(CALIBRATION_MET, crate::comfort::Activity::Walking.met());
     }
 
     #[test]
     fn every_anchor_lands_on_its_whole_number() {
-        for (degrees, expected) in ANCHORS {
+        for (expected, point) in standard().points.iter().enumerate() {
+            let (degrees, expected) = (point.felt_f, expected as f64);
             let scored = at(degrees).value();
             assert!(
                 (scored - expected).abs() < 1e-9,
@@ -329,14 +842,17 @@ mod tests {
     fn no_two_points_read_the_same() {
         // If a word or a garment line repeats, the extra resolution is fake:
         // the reader cannot tell which point they are looking at from the text.
+        let points = &standard().points;
         let words: std::collections::HashSet<&str> =
-            LABELS.iter().map(|(word, _, _)| *word).collect();
-        assert_eq!(words.len(), LABELS.len(), "a sensation word repeats");
+            points.iter().map(|point| point.word.as_str()).collect();
+        assert_eq!(words.len(), points.len(), "a sensation word repeats");
 
         // The two ends share "don't go out" by design; everything between is
         // a distinct instruction.
-        let middle: std::collections::HashSet<&str> =
-            LABELS[1..10].iter().map(|(_, advice, _)| *advice).collect();
+        let middle: std::collections::HashSet<&str> = points[1..10]
+            .iter()
+            .map(|point| point.advice.as_str())
+            .collect();
         assert_eq!(middle.len(), 9, "a garment line repeats");
     }
 
@@ -359,7 +875,7 @@ mod tests {
     fn the_colour_band_always_matches_the_number_printed_on_it() {
         // 4.45 prints as "4.5", so it must wear 5's colour, not 4's.
         for tenth in -400..1400 {
-            let scored = Score(f64::from(tenth) / 100.0);
+            let scored = standard().point(f64::from(tenth) / 100.0);
             let printed: f64 = scored.to_string().parse().unwrap();
             assert_eq!(
                 u32::from(scored.level()),
@@ -383,7 +899,8 @@ mod tests {
 
     #[test]
     fn the_key_covers_every_point_in_order() {
-        let key = key();
+        let key = standard().key();
+        let points = &standard().points;
         assert_eq!(key.len(), 11);
         // Hottest first: 10 at the top of the list, 0 at the bottom.
         assert_eq!(key[0].0, 10);
@@ -391,7 +908,10 @@ mod tests {
         for (position, (level, word, advice, degrees)) in key.iter().enumerate() {
             let index = 10 - position;
             assert_eq!(usize::from(*level), index);
-            assert_eq!((*word, *advice), (LABELS[index].0, LABELS[index].1));
+            assert_eq!(
+                (*word, *advice),
+                (points[index].word.as_str(), points[index].advice.as_str())
+            );
             // The temperature shown beside each point is a felt temperature,
             // and must be one that actually lands on that point.
             assert_eq!(at(f64::from(*degrees)).level(), *level);
@@ -419,4 +939,73 @@ mod tests {
         assert_eq!(cool.min(warm), cool);
         assert_eq!(cool.max(warm), warm);
     }
+
+    #[test]
+    fn the_shipped_file_defaults_to_the_standard_scale() {
+        let shipped =
+            Profiles::parse(include_str!("../scales.json")).expect("scales.json is valid");
+        assert_eq!(shipped.default().slug(), "standard");
+        assert!(shipped.all().len() > 1);
+    }
+
+    #[test]
+    fn running_cold_reaches_each_outfit_at_a_warmer_temperature() {
+        let cold = profiles().find("runs-cold").expect("runs-cold");
+        let felt = Temperature::from_celsius((61.0 - 32.0) * 5.0 / 9.0);
+        assert!(cold.score(felt) < standard().score(felt));
+        // And the key shows that profile's own degrees and words.
+        assert_ne!(cold.key()[5].3, standard().key()[5].3);
+    }
+
+    #[test]
+    fn a_score_speaks_its_own_profiles_words() {
+        let json = r#"{"default": "me", "profiles": [{"slug": "me", "name": "Me", "points": [
+            {"felt_f": 0, "word": "w0", "advice": "a0", "carry": null},
+            {"felt_f": 1, "word": "w1", "advice": "a1", "carry": "c1"},
+            {"felt_f": 2, "word": "w2", "advice": "a2", "carry": null},
+            {"felt_f": 3, "word": "w3", "advice": "a3", "carry": null},
+            {"felt_f": 4, "word": "w4", "advice": "a4", "carry": null},
+            {"felt_f": 5, "word": "w5", "advice": "a5", "carry": null},
+            {"felt_f": 6, "word": "w6", "advice": "a6", "carry": null},
+            {"felt_f": 7, "word": "w7", "advice": "a7", "carry": null},
+            {"felt_f": 8, "word": "w8", "advice": "a8", "carry": null},
+            {"felt_f": 9, "word": "w9", "advice": "a9", "carry": null},
+            {"felt_f": 10, "word": "w10", "advice": "a10", "carry": null}
+        ]}]}"#;
+        let mine: &'static Profiles = Box::leak(Box::new(Profiles::parse(json).unwrap()));
+        let score = mine.default().point(1.2);
+        assert_eq!(score.label(), "w1 \u{2014} a1");
+        assert_eq!(score.layer(), Some("c1"));
+        assert_eq!(score.with_value(9.0).word(), "w9");
+    }
+
+    #[test]
+    fn refuses_anchors_that_do_not_rise() {
+        let mut points = ladder();
+        points[6].0 = points[5].0;
+        let message = rejection(&points);
+        assert!(message.contains("no warmer than"), "{message}");
+    }
+
+    #[test]
+    fn refuses_a_repeated_word() {
+        let mut points = ladder();
+        points[7].1 = points[3].1.clone();
+        let message = rejection(&points);
+        assert!(message.contains("used twice"), "{message}");
+    }
+
+    #[test]
+    fn refuses_the_wrong_number_of_points() {
+        let mut points = ladder();
+        points.pop();
+        let message = rejection(&points);
+        assert!(message.contains("has 10 points"), "{message}");
+    }
+
+    #[test]
+    fn refuses_an_unknown_default() {
+        let json = one_profile(&[]).replace(r#""default": "me""#, r#""default": "you""#);
+        assert!(Profiles::parse(&json).is_err());
+    }
 }
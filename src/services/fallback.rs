@@ -0,0 +1,141 @@
+//! One weather provider standing in for another.
+//!
+//! Open-Meteo is the only free source with the radiation components the page
+//! is built on, which also makes it a single point of failure. When it errors
+//! or times out, [`Fallback`] asks a second provider instead and marks the
+//! forecast with why, so the page can say whose forecast it is showing and
+//! what had to be estimated. If both fail, the first provider's error is the
+//! one reported: it is the one that usually answers.
+
+use std::sync::Arc;
+
+use crate::services::open_meteo::{Forecast, Place};
+use crate::services::{BoxFuture, Error, WeatherProvider};
+
+pub struct Fallback {
+    primary: Arc<dyn WeatherProvider>,
+    secondary: Arc<dyn WeatherProvider>,
+}
+
+impl Fallback {
+    pub fn new(primary: Arc<dyn WeatherProvider>, secondary: Arc<dyn WeatherProvider>) -> Self {
+        Fallback { primary, secondary }
+    }
+
+    async fn forecast(&self, latitude: f64, longitude: f64) -> Result<Arc<Forecast>, Error> {
+        let err = match self.primary.forecast(latitude, longitude).await {
+            Ok(forecast) => return Ok(forecast),
+            Err(err) => err,
+        };
+        match self.secondary.forecast(latitude, longitude).await {
+            Ok(forecast) => {
+                tracing::warn!(
+                    "Fell back to {} for {latitude},{longitude}: {err}",
+                    forecast.provider
+                );
+                let mut forecast = Forecast::clone(&forecast);
+                forecast.fallback = Some(err.to_string());
+                Ok(Arc::new(forecast))
+            }
+            Err(second) => {
+                tracing::warn!("No fallback for {latitude},{longitude} either: {second}");
+                Err(err)
+            }
+        }
+    }
+}
+
+impl WeatherProvider for Fallback {
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+        Box::pin(Fallback::forecast(self, latitude, longitude))
+    }
+
+    /// Refreshing warms the primary's cache, which is what every page reads
+    /// first; when it cannot answer there is nothing to warm.
+    fn refresh(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+        self.primary.refresh(latitude, longitude)
+    }
+
+    /// Searches only ever go to the primary: the fallback exists for
+    /// forecasts, and may have no place search at all.
+    fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+        self.primary.geocode(query)
+    }
+
+    /// As are the reverse.
+    fn nearest(&self, latitude: f64, longitude: f64) -> BoxFuture<'_, Option<Place>> {
+        self.primary.nearest(latitude, longitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::Mode;
+    use crate::services::nws::{self, Nws};
+    use crate::services::open_meteo::{self, OpenMeteo};
+
+    /// Fails every forecast the way an unreachable upstream would.
+    struct Down;
+
+    impl WeatherProvider for Down {
+        fn forecast(&self, _: f64, _: f64) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+            Box::pin(async { Err(open_meteo::Error::Incomplete("hourly data").into()) })
+        }
+
+        fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+            Box::pin(async move { Err(open_meteo::Error::NoSuchPlace(query.to_owned()).into()) })
+        }
+    }
+
+    fn nws() -> Arc<dyn WeatherProvider> {
+        Arc::new(Nws::new(Mode::Replay, "localhost"))
+    }
+
+    #[tokio::test]
+    async fn the_primary_answers_when_it_can() {
+        let both = Fallback::new(Arc::new(OpenMeteo::new(Mode::Replay)), nws());
+        let forecast = both.forecast(37.7946, -122.3999).await.unwrap();
+        assert_eq!(forecast.provider, open_meteo::NAME);
+        assert!(forecast.fallback.is_none());
+    }
+
+    #[tokio::test]
+    async fn the_secondary_answers_when_the_primary_cannot_and_says_why() {
+        let both = Fallback::new(Arc::new(Down), nws());
+        let forecast = both.forecast(37.7946, -122.3999).await.unwrap();
+        assert_eq!(forecast.provider, nws::NAME);
+        assert_eq!(
+            forecast.fallback.as_deref(),
+            Some("Open-Meteo returned no hourly data.")
+        );
+        assert!(forecast.estimated.contains(&"solar radiation"));
+    }
+
+    #[tokio::test]
+    async fn the_primary_error_is_reported_when_neither_answers() {
+        let both = Fallback::new(Arc::new(Down), nws());
+        let err = both.forecast(0.0, 0.0).await.unwrap_err();
+        assert_eq!(err.to_string(), "Open-Meteo returned no hourly data.");
+    }
+
+    #[tokio::test]
+    async fn refreshes_go_to_the_primary() {
+        let both = Fallback::new(Arc::new(Down), nws());
+        assert!(both.refresh(37.7946, -122.3999).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn searches_go_to_the_primary() {
+        let both = Fallback::new(Arc::new(OpenMeteo::new(Mode::Replay)), Arc::new(Down));
+        assert_eq!(both.geocode("Oakland").await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,96 @@
+//! Recorded upstream responses, for working without a network.
+//!
+//! `UPSTREAM=record` fetches as usual and also writes each raw response body
+//! under `fixtures/` (or wherever `FIXTURES_DIR` points), one file per
+//! request. `UPSTREAM=replay` reads those files instead of making the request
+//! at all, and a request with no file fails the way an unreachable upstream
+//! would. Anything else is live.
+//!
+//! The bodies are kept raw rather than parsed, so replay exercises the same
+//! decoding a live response does, and a fixture can be refreshed by recording
+//! again without anything here changing.
+//!
+//! Tests replay by default: they run offline and see the same weather every
+//! time. `UPSTREAM=live cargo test` checks the pages against the real thing.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::get_config;
+
+/// Where upstream responses come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Live,
+    /// Live, and every response written to a fixture.
+    Record,
+    /// Fixtures only; nothing leaves the machine.
+    Replay,
+}
+
+impl Mode {
+    pub fn from_name(name: &str) -> Option<Mode> {
+        match name {
+            "live" => Some(Mode::Live),
+            "record" => Some(Mode::Record),
+            "replay" => Some(Mode::Replay),
+            _ => None,
+        }
+    }
+}
+
+/// The fixture for one request: `{service}/{kind}/{key}.json` under the
+/// fixtures directory. Anything in `key` that would not be safe in a file
+/// name becomes `-`, so it can be built straight from the request.
+pub fn path(service: &str, kind: &str, key: &str) -> PathBuf {
+    let key: String = key
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | ',' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    get_config()
+        .fixtures_path
+        .join(service)
+        .join(kind)
+        .join(format!("{key}.json"))
+}
+
+/// Writes a response body to its fixture, replacing any older recording.
+pub fn save(path: &Path, body: &[u8]) -> std::io::Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_only_the_three_modes() {
+        assert_eq!(Mode::from_name("replay"), Some(Mode::Replay));
+        assert_eq!(Mode::from_name("record"), Some(Mode::Record));
+        assert_eq!(Mode::from_name("live"), Some(Mode::Live));
+        assert_eq!(Mode::from_name("Replay"), None);
+    }
+
+    #[test]
+    fn a_key_cannot_leave_its_directory() {
+        let path = path("open-meteo", "geocode", "../../etc/passwd");
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(name, "..-..-etc-passwd.json");
+        assert_eq!(path.parent().unwrap().file_name().unwrap(), "geocode");
+    }
+
+    #[test]
+    fn keeps_coordinates_readable() {
+        let path = path("open-meteo", "forecast", "37.7946,-122.3999");
+        assert!(path.ends_with("open-meteo/forecast/37.7946,-122.3999.json"));
+    }
+}
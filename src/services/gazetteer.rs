@@ -0,0 +1,578 @@
+//! Place search from a file on disk, without asking upstream.
+//!
+//! The file is a GeoNames dump in its usual tab-separated shape, normally
+//! `cities15000.txt` from <https://download.geonames.org/export/dump/>: every
+//! town of fifteen thousand people or more, about thirty thousand of them.
+//! Open-Meteo's geocoder is built on the same data, so a search answered from
+//! here lands on the same coordinates. If `admin1CodesASCII.txt` and
+//! `countryInfo.txt` from the same page sit beside it, each place is described
+//! the way Open-Meteo describes it (`California, United States`); without them
+//! the detail is the bare codes (`CA, US`).
+//!
+//! Nothing is read unless `GAZETTEER_FILE` names the dump. `GAZETTEER_ORDER`
+//! says whether it is asked first (`first`), saving the upstream call for
+//! anything it knows, or only when Open-Meteo fails (`fallback`, the
+//! default). A file that will not load is logged and the server runs without
+//! it, searching upstream as before. On the server the files live in
+//! `geonames/`, which deploys leave alone.
+//!
+//! # Matching
+//!
+//! Names are matched on their beginning, so `oak` finds Oakland, and then, if
+//! nothing begins that way, on shared trigrams, so a misspelling like
+//! `sacremento` still finds Sacramento. Either way the largest places come
+//! first, as they do upstream. Anything after a comma narrows the search by
+//! region or country: `portland, maine`.
+//!
+//! It also names bare coordinates: a link with a `lat` and `lon` but no
+//! `name` is labelled with the nearest place within [`NEAREST_WITHIN_MILES`],
+//! whichever its size.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::services::open_meteo::{distance_miles, Forecast, Place};
+use crate::services::places::normalise;
+use crate::services::{BoxFuture, Error as ServiceError, WeatherProvider};
+
+/// As many as Open-Meteo returns for one search.
+const RESULTS: usize = 5;
+
+/// The least share of trigrams a misspelling must have in common with a name.
+const MIN_SIMILARITY: f64 = 0.4;
+
+/// Farther than this from every place in the dump, a point keeps its
+/// coordinates for a name. Cities of fifteen thousand are rarely farther apart
+/// than this where anyone lives, and a name from beyond it would mislead.
+const NEAREST_WITHIN_MILES: f64 = 25.0;
+
+/// When the gazetteer is asked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Before upstream; upstream only for what it does not know.
+    First,
+    /// Only when upstream fails to answer at all.
+    Fallback,
+}
+
+impl Order {
+    pub fn from_name(name: &str) -> Option<Order> {
+        match name {
+            "first" => Some(Order::First),
+            "fallback" => Some(Order::Fallback),
+            _ => None,
+        }
+    }
+}
+
+type Trigram = [char; 3];
+
+struct City {
+    place: Place,
+    /// Distinct trigrams across its names, for scoring a fuzzy match.
+    trigrams: usize,
+}
+
+/// Every place in a dump, indexed for search.
+pub struct Gazetteer {
+    /// Most populous first, so a lower index is a better match.
+    cities: Vec<City>,
+    /// Each city's normalised names, sorted, for prefix search.
+    names: Vec<(String, usize)>,
+    /// The cities each trigram appears in, most populous first.
+    trigrams: HashMap<Trigram, Vec<usize>>,
+}
+
+impl Gazetteer {
+    /// Reads a dump, and the region and country names beside it if present.
+    pub fn read(path: &Path) -> Result<Gazetteer, Error> {
+        let read = |path: PathBuf| {
+            std::fs::read_to_string(&path).map_err(|source| Error::Read { path, source })
+        };
+        let optional = |name: &str| match path.parent().map(|directory| directory.join(name)) {
+            Some(path) if path.exists() => read(path),
+            _ => Ok(String::new()),
+        };
+        Gazetteer::parse(
+            &read(path.to_owned())?,
+            &optional("admin1CodesASCII.txt")?,
+            &optional("countryInfo.txt")?,
+        )
+    }
+
+    /// Builds the index from a dump's contents, naming regions and countries
+    /// from `admin1` and `countries` where they say.
+    pub fn parse(dump: &str, admin1: &str, countries: &str) -> Result<Gazetteer, Error> {
+        // `US.CA` -> `California`.
+        let regions: HashMap<&str, &str> = admin1
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.split('\t');
+                Some((columns.next()?, columns.next()?))
+            })
+            .collect();
+        // `US` -> `United States`.
+        let countries: HashMap<&str, &str> = countries
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split('\t').collect();
+                Some((*columns.first()?, *columns.get(4)?))
+            })
+            .collect();
+
+        let mut rows = Vec::new();
+        for (index, line) in dump.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let columns: Vec<&str> = line.split('\t').collect();
+            let invalid = |why: &str| Error::Invalid {
+                line: index + 1,
+                why: why.to_owned(),
+            };
+            if columns.len() < 15 {
+                return Err(invalid("fewer than 15 columns"));
+            }
+            let number = |column: usize| columns[column].parse::<f64>().ok();
+            let (Some(latitude), Some(longitude)) = (number(4), number(5)) else {
+                return Err(invalid("coordinates are not numbers"));
+            };
+            let population = columns[14].parse::<u64>().unwrap_or(0);
+            let country = columns[8];
+            let region = format!("{country}.{}", columns[10]);
+            let detail = [
+                regions.get(region.as_str()).copied().unwrap_or(columns[10]),
+                countries.get(country).copied().unwrap_or(country),
+            ]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+            let place = Place {
+                name: columns[1].to_owned(),
+                detail,
+                latitude,
+                longitude,
+            };
+            rows.push((population, place, columns[2]));
+        }
+        // Stable, so places of equal size keep the file's order.
+        rows.sort_by_key(|row| Reverse(row.0));
+
+        let mut cities = Vec::with_capacity(rows.len());
+        let mut names = Vec::new();
+        let mut trigrams: HashMap<Trigram, Vec<usize>> = HashMap::new();
+        for (index, (_, place, ascii)) in rows.into_iter().enumerate() {
+            let mut keys = vec![normalise(&place.name)];
+            let ascii = normalise(ascii);
+            if !ascii.is_empty() && ascii != keys[0] {
+                keys.push(ascii);
+            }
+            let own: HashSet<Trigram> = keys.iter().flat_map(|key| trigrams_of(key)).collect();
+            for trigram in &own {
+                trigrams.entry(*trigram).or_default().push(index);
+            }
+            names.extend(keys.into_iter().map(|key| (key, index)));
+            cities.push(City {
+                place,
+                trigrams: own.len(),
+            });
+        }
+        names.sort();
+
+        Ok(Gazetteer {
+            cities,
+            names,
+            trigrams,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cities.len()
+    }
+
+    /// The place nearest a point, if any is within [`NEAREST_WITHIN_MILES`].
+    pub fn nearest(&self, latitude: f64, longitude: f64) -> Option<Place> {
+        // A degree of latitude is 69 miles everywhere, so anything farther
+        // north or south than that can be skipped before the trigonometry.
+        let band = NEAREST_WITHIN_MILES / 69.0;
+        self.cities
+            .iter()
+            .filter(|city| (city.place.latitude - latitude).abs() <= band)
+            .map(|city| {
+                let miles = distance_miles(
+                    latitude,
+                    longitude,
+                    city.place.latitude,
+                    city.place.longitude,
+                );
+                (miles, &city.place)
+            })
+            .filter(|(miles, _)| *miles <= NEAREST_WITHIN_MILES)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, place)| place.clone())
+    }
+
+    /// Places whose names begin with `query`, or failing that resemble it,
+    /// best first. Empty when nothing is close.
+    pub fn search(&self, query: &str) -> Vec<Place> {
+        let (name, within) = query.split_once(',').unwrap_or((query, ""));
+        let name = normalise(name);
+        let within = normalise(within);
+        if name.is_empty() {
+            return Vec::new();
+        }
+        let fits = |index: &usize| {
+            within.is_empty() || normalise(&self.cities[*index].place.detail).contains(&within)
+        };
+
+        let mut found = self.beginning(&name);
+        found.retain(fits);
+        if found.is_empty() {
+            found = self.resembling(&name);
+            found.retain(fits);
+        }
+        found
+            .into_iter()
+            .take(RESULTS)
+            .map(|index| self.cities[index].place.clone())
+            .collect()
+    }
+
+    /// Cities with a name beginning `prefix`: exact names first, then by size.
+    fn beginning(&self, prefix: &str) -> Vec<usize> {
+        let start = self
+            .names
+            .partition_point(|(name, _)| name.as_str() < prefix);
+        let mut found: Vec<(bool, usize)> = self.names[start..]
+            .iter()
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .map(|(name, index)| (name != prefix, *index))
+            .collect();
+        found.sort();
+        let mut seen = HashSet::new();
+        found
+            .into_iter()
+            .filter(|(_, index)| seen.insert(*index))
+            .map(|(_, index)| index)
+            .collect()
+    }
+
+    /// Cities sharing enough trigrams with `name`, closest first, then by
+    /// size.
+    fn resembling(&self, name: &str) -> Vec<usize> {
+        let wanted: HashSet<Trigram> = trigrams_of(name).collect();
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for trigram in &wanted {
+            for index in self.trigrams.get(trigram).into_iter().flatten() {
+                *shared.entry(*index).or_default() += 1;
+            }
+        }
+        let mut found: Vec<(f64, usize)> = shared
+            .into_iter()
+            .map(|(index, shared)| {
+                let union = wanted.len() + self.cities[index].trigrams - shared;
+                (shared as f64 / union as f64, index)
+            })
+            .filter(|(similarity, _)| *similarity >= MIN_SIMILARITY)
+            .collect();
+        found.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        found.into_iter().map(|(_, index)| index).collect()
+    }
+}
+
+/// Every run of three characters in a name padded the usual way, two spaces
+/// in front and one behind, so its start counts for more than its middle.
+fn trigrams_of(name: &str) -> impl Iterator<Item = Trigram> {
+    let padded: Vec<char> = "  ".chars().chain(name.chars()).chain([' ']).collect();
+    (0..padded.len().saturating_sub(2))
+        .map(move |start| [padded[start], padded[start + 1], padded[start + 2]])
+}
+
+/// Why a dump was refused.
+#[derive(Debug)]
+pub enum Error {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Not the shape of a GeoNames dump.
+    Invalid { line: usize, why: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "could not read {}: {source}", path.display())
+            }
+            Error::Invalid { line, why } => {
+                write!(f, "gazetteer line {line} is malformed: {why}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } => Some(source),
+            Error::Invalid { .. } => None,
+        }
+    }
+}
+
+/// Another provider, with searches answered from a gazetteer first or as a
+/// fallback. Forecasts pass straight through.
+pub struct WithGazetteer {
+    inner: Arc<dyn WeatherProvider>,
+    gazetteer: Gazetteer,
+    order: Order,
+}
+
+impl WithGazetteer {
+    pub fn new(inner: Arc<dyn WeatherProvider>, gazetteer: Gazetteer, order: Order) -> Self {
+        WithGazetteer {
+            inner,
+            gazetteer,
+            order,
+        }
+    }
+
+    async fn geocode(&self, query: &str) -> Result<Vec<Place>, ServiceError> {
+        if self.order == Order::First {
+            let found = self.gazetteer.search(query);
+            if !found.is_empty() {
+                return Ok(found);
+            }
+            return self.inner.geocode(query).await;
+        }
+        match self.inner.geocode(query).await {
+            Err(err) if !err.no_such_place() => {
+                let found = self.gazetteer.search(query);
+                if found.is_empty() {
+                    return Err(err);
+                }
+                tracing::warn!("Searched the gazetteer for {query:?}: {err}");
+                Ok(found)
+            }
+            answered => answered,
+        }
+    }
+
+    async fn nearest(&self, latitude: f64, longitude: f64) -> Option<Place> {
+        if self.order == Order::First {
+            if let Some(place) = self.gazetteer.nearest(latitude, longitude) {
+                return Some(place);
+            }
+            return self.inner.nearest(latitude, longitude).await;
+        }
+        match self.inner.nearest(latitude, longitude).await {
+            Some(place) => Some(place),
+            None => self.gazetteer.nearest(latitude, longitude),
+        }
+    }
+}
+
+impl WeatherProvider for WithGazetteer {
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, ServiceError>> {
+        self.inner.forecast(latitude, longitude)
+    }
+
+    fn refresh(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, ServiceError>> {
+        self.inner.refresh(latitude, longitude)
+    }
+
+    fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, ServiceError>> {
+        Box::pin(WithGazetteer::geocode(self, query))
+    }
+
+    fn nearest(&self, latitude: f64, longitude: f64) -> BoxFuture<'_, Option<Place>> {
+        Box::pin(WithGazetteer::nearest(self, latitude, longitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::Mode;
+    use crate::services::open_meteo::{self, OpenMeteo};
+
+    /// Rows in the dump's own layout, out of population order on purpose.
+    const DUMP: &str = "\
+5378538\tOakland\tOakland\t\t37.80437\t-122.2708\tP\tPPLA2\tUS\t\tCA\t001\t\t\t419267\t13\t14\tAmerica/Los_Angeles\t2024-01-01
+4904381\tOak Park\tOak Park\t\t41.88503\t-87.7845\tP\tPPL\tUS\t\tIL\t031\t\t\t52233\t189\t190\tAmerica/Chicago\t2024-01-01
+5389489\tSacramento\tSacramento\t\t38.58157\t-121.4944\tP\tPPLA\tUS\t\tCA\t067\t\t\t490712\t9\t8\tAmerica/Los_Angeles\t2024-01-01
+6092122\tOakville\tOakville\t\t43.45011\t-79.68292\tP\tPPL\tCA\t\t08\t\t\t\t193832\t94\t95\tAmerica/Toronto\t2024-01-01
+5746545\tPortland\tPortland\t\t45.52345\t-122.67621\tP\tPPLA2\tUS\t\tOR\t051\t\t\t652503\t15\t16\tAmerica/Los_Angeles\t2024-01-01
+4975802\tPortland\tPortland\t\t43.66147\t-70.25533\tP\tPPLA2\tUS\t\tME\t005\t\t\t68408\t12\t13\tAmerica/New_York\t2024-01-01
+2657896\tZürich\tZurich\t\t47.36667\t8.55\tP\tPPLA\tCH\t\tZH\t112\t261\t\t341730\t408\t410\tEurope/Zurich\t2024-01-01
+";
+
+    const ADMIN1: &str = "\
+US.CA\tCalifornia\tCalifornia\t5332921
+US.ME\tMaine\tMaine\t4971068
+US.OR\tOregon\tOregon\t5744337
+";
+
+    const COUNTRIES: &str = "\
+# ISO\tISO3\tISO-Numeric\tfips\tCountry
+US\tUSA\t840\tUS\tUnited States
+";
+
+    fn gazetteer() -> Gazetteer {
+        Gazetteer::parse(DUMP, ADMIN1, COUNTRIES).unwrap()
+    }
+
+    fn names(places: &[Place]) -> Vec<&str> {
+        places.iter().map(|place| place.name.as_str()).collect()
+    }
+
+    #[test]
+    fn a_prefix_finds_every_name_it_begins_largest_first() {
+        let found = gazetteer().search("Oak");
+        assert_eq!(names(&found), ["Oakland", "Oakville", "Oak Park"]);
+        assert_eq!(found[0].latitude, 37.80437);
+    }
+
+    #[test]
+    fn a_whole_name_comes_before_longer_ones() {
+        let gazetteer = Gazetteer::parse(
+            "1\tOaklandia\tOaklandia\t\t0\t0\tP\tPPL\tUS\t\tCA\t\t\t\t900000\t\t\t\t\n\
+             2\tOakland\tOakland\t\t1\t1\tP\tPPL\tUS\t\tCA\t\t\t\t1000\t\t\t\t\n",
+            "",
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            names(&gazetteer.search("oakland")),
+            ["Oakland", "Oaklandia"]
+        );
+    }
+
+    #[test]
+    fn places_are_described_as_upstream_describes_them() {
+        let found = gazetteer().search("oakland");
+        assert_eq!(found[0].detail, "California, United States");
+        // Codes, where the files beside the dump do not say.
+        let found = gazetteer().search("oakville");
+        assert_eq!(found[0].detail, "08, CA");
+    }
+
+    #[test]
+    fn a_misspelling_still_finds_the_place() {
+        assert_eq!(names(&gazetteer().search("sacremento")), ["Sacramento"]);
+        assert!(gazetteer().search("qqqq").is_empty());
+    }
+
+    #[test]
+    fn accents_match_either_way() {
+        assert_eq!(names(&gazetteer().search("zurich")), ["Zürich"]);
+        assert_eq!(names(&gazetteer().search("ZÜRICH")), ["Zürich"]);
+    }
+
+    #[test]
+    fn text_after_a_comma_narrows_by_region() {
+        let found = gazetteer().search("Portland, Maine");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].detail, "Maine, United States");
+        assert_eq!(gazetteer().search("portland").len(), 2);
+    }
+
+    #[test]
+    fn a_point_is_named_for_the_closest_place_not_the_largest() {
+        // Chicago is not in the dump, so a point in the Loop is named for Oak
+        // Park, eight miles west.
+        let nearest = gazetteer().nearest(41.8781, -87.6298).unwrap();
+        assert_eq!(nearest.name, "Oak Park");
+        // Between Oakland and Sacramento, but nearer the first.
+        let nearest = gazetteer().nearest(37.9, -122.1).unwrap();
+        assert_eq!(nearest.name, "Oakland");
+    }
+
+    #[test]
+    fn a_point_far_from_everywhere_is_not_named() {
+        assert!(gazetteer().nearest(30.0, -140.0).is_none());
+        assert!(gazetteer().nearest(37.8, 8.55).is_none());
+    }
+
+    #[test]
+    fn a_malformed_dump_is_refused() {
+        let err = Gazetteer::parse("1\tOakland\n", "", "").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "gazetteer line 1 is malformed: fewer than 15 columns"
+        );
+    }
+
+    /// Fails every search the way an unreachable upstream would.
+    struct Unreachable;
+
+    impl WeatherProvider for Unreachable {
+        fn forecast(&self, _: f64, _: f64) -> BoxFuture<'_, Result<Arc<Forecast>, ServiceError>> {
+            Box::pin(async { Err(open_meteo::Error::Incomplete("hourly data").into()) })
+        }
+
+        fn geocode<'a>(&'a self, _: &'a str) -> BoxFuture<'a, Result<Vec<Place>, ServiceError>> {
+            Box::pin(async { Err(open_meteo::Error::NotRecorded(PathBuf::new()).into()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn as_a_fallback_it_answers_when_upstream_cannot() {
+        let searched = WithGazetteer::new(Arc::new(Unreachable), gazetteer(), Order::Fallback);
+        let found = WeatherProvider::geocode(&searched, "Sacramento")
+            .await
+            .unwrap();
+        assert_eq!(names(&found), ["Sacramento"]);
+        assert!(WeatherProvider::geocode(&searched, "qqqq").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn as_a_fallback_it_defers_to_upstream_when_it_answers() {
+        let searched = WithGazetteer::new(
+            Arc::new(OpenMeteo::new(Mode::Replay)),
+            gazetteer(),
+            Order::Fallback,
+        );
+        let found = WeatherProvider::geocode(&searched, "Oakland")
+            .await
+            .unwrap();
+        // Upstream's three Oaklands, not the gazetteer's one.
+        assert_eq!(found.len(), 3);
+        let err = WeatherProvider::geocode(&searched, "nowhere at all")
+            .await
+            .unwrap_err();
+        assert!(err.no_such_place());
+    }
+
+    #[tokio::test]
+    async fn coordinates_are_named_from_the_gazetteer() {
+        let searched = WithGazetteer::new(Arc::new(Unreachable), gazetteer(), Order::Fallback);
+        let nearest = WeatherProvider::nearest(&searched, 38.5, -121.5).await;
+        assert_eq!(nearest.unwrap().name, "Sacramento");
+    }
+
+    #[tokio::test]
+    async fn asked_first_it_saves_the_upstream_call() {
+        let searched = WithGazetteer::new(Arc::new(Unreachable), gazetteer(), Order::First);
+        let found = WeatherProvider::geocode(&searched, "Oak").await.unwrap();
+        assert_eq!(found.len(), 3);
+        // Upstream is still asked for what the gazetteer does not know.
+        let err = WeatherProvider::geocode(&searched, "qqqq")
+            .await
+            .unwrap_err();
+        assert!(!err.no_such_place());
+    }
+}
@@ -1,4 +1,128 @@
 //! External service integrations.
+//!
+//! Handlers reach each service through a trait held in
+//! [`AppState`](crate::state::AppState), never through the module directly,
+//! so a test can hand the router an in-memory stand-in. The production
+//! implementations own their HTTP client and cache; Open-Meteo's cache is the
+//! only one that outlives the process.
+//!
+//! Forecasts come from Open-Meteo, and from the National Weather Service when
+//! Open-Meteo cannot answer; see [`fallback`]. Searches are cached and
+//! tallied in front of both; see [`places`]. A local [`gazetteer`] can answer
+//! them too.
 
+pub mod fallback;
+pub mod fixtures;
+pub mod gazetteer;
+pub mod nws;
 pub mod open_meteo;
+pub mod places;
 pub mod private_relay;
+
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// What a trait method returns in place of `async fn`, which a trait object
+/// cannot have.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Why a weather provider could not answer. Each provider's own error names
+/// it, so the message says which one failed.
+#[derive(Debug)]
+pub enum Error {
+    OpenMeteo(open_meteo::Error),
+    Nws(nws::Error),
+}
+
+impl Error {
+    /// Whether a search was answered, just with no places: the one failure
+    /// that asking again, or asking elsewhere, will not fix.
+    pub fn no_such_place(&self) -> bool {
+        matches!(self, Error::OpenMeteo(open_meteo::Error::NoSuchPlace(_)))
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::OpenMeteo(err) => err.fmt(f),
+            Error::Nws(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::OpenMeteo(err) => Some(err),
+            Error::Nws(err) => Some(err),
+        }
+    }
+}
+
+impl From<open_meteo::Error> for Error {
+    fn from(err: open_meteo::Error) -> Self {
+        Error::OpenMeteo(err)
+    }
+}
+
+impl From<nws::Error> for Error {
+    fn from(err: nws::Error) -> Self {
+        Error::Nws(err)
+    }
+}
+
+/// Forecasts and place search.
+pub trait WeatherProvider: Send + Sync {
+    /// Yesterday and the week ahead for a point, or as much of it as the
+    /// provider has. See [`open_meteo::OpenMeteo::forecast`].
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<open_meteo::Forecast>, Error>>;
+
+    /// A new forecast for a point, replacing any cached one however fresh.
+    /// By default just a [`forecast`](Self::forecast), for providers whose
+    /// cache nothing keeps warm.
+    fn refresh(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<open_meteo::Forecast>, Error>> {
+        self.forecast(latitude, longitude)
+    }
+
+    /// Places matching a name, best first. Never empty on success.
+    fn geocode<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<open_meteo::Place>, Error>>;
+
+    /// Places to offer while `query` is still being typed. By default just a
+    /// [`geocode`](Self::geocode); see [`places`] for the one that does more.
+    fn suggest<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<open_meteo::Place>, Error>> {
+        self.geocode(query)
+    }
+
+    /// The named place nearest a point, if there is one close enough to be
+    /// worth naming and the provider can say. Neither Open-Meteo nor NWS
+    /// can, so by default nothing; see [`gazetteer`].
+    fn nearest(&self, _latitude: f64, _longitude: f64) -> BoxFuture<'_, Option<open_meteo::Place>> {
+        Box::pin(async { None })
+    }
+}
+
+/// Apple's list of iCloud Private Relay egress ranges.
+pub trait RelayRangeSource: Send + Sync {
+    /// The range `ip_addr` falls in, or `None` if it is not a relay address.
+    fn range_for<'a>(
+        &'a self,
+        ip_addr: &'a IpAddr,
+    ) -> BoxFuture<'a, Result<Option<private_relay::EgressRange>, reqwest::Error>>;
+}
@@ -0,0 +1,785 @@
+//! US National Weather Service gridpoint forecast client.
+//!
+//! The fallback for when Open-Meteo cannot answer; see
+//! [`super::fallback`]. api.weather.gov is free, needs no key and is run by a
+//! different organisation on different infrastructure, which is the point.
+//! It only covers the United States, and it publishes no radiation at all, so
+//! the components the sun/shade split needs are estimated from its sky cover
+//! with [`solar::under_cloud`], and the forecast says so.
+//!
+//! # Two requests
+//!
+//! `/points/{lat},{lon}` names the forecast office and grid cell for a point
+//! and the IANA time zone it sits in. `/gridpoints/{office}/{x},{y}` is the
+//! raw grid: one series per field, each a list of UTC intervals with a value,
+//! e.g. `2026-10-14T20:00:00+00:00/PT3H`. Those are spread back out into the
+//! hours this site works in.
+//!
+//! # Time zones
+//!
+//! Everything else here arrives in local time already; the grid arrives in
+//! UTC. Rather than take on a time zone database for one fallback, the zones
+//! NWS grids sit in are listed below with the US daylight-saving rule, which
+//! has been the same since 2007.
+//!
+//! # What is missing
+//!
+//! No yesterday, since the grid starts from the latest issuance, and so no
+//! comparison with it. No sunshine duration either; it is the cloud-cover
+//! estimate the Open-Meteo client already falls back on.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::clock;
+use crate::services::fixtures::{self, Mode};
+use crate::services::open_meteo::{Day, Forecast, Hour, Place};
+use crate::services::{self, BoxFuture, WeatherProvider};
+use crate::solar;
+use crate::units::{Speed, Temperature};
+
+/// Only asked once Open-Meteo has already failed, so this is all the patience
+/// left in a page load.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// The grid is reissued every hour or so.
+const CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Same bound as Open-Meteo's cache, for the same reason.
+const CACHE_CAPACITY: usize = 64;
+
+/// How the page credits this provider.
+pub const NAME: &str = "the National Weather Service";
+
+const API_URL: &str = "https://api.weather.gov";
+
+/// Anything that can stop a forecast coming back.
+#[derive(Debug)]
+pub enum Error {
+    Request(reqwest::Error),
+    /// The body arrived but was not the JSON this client expects.
+    Decode(serde_json::Error),
+    /// The response parsed but did not contain a usable day.
+    Incomplete(&'static str),
+    /// Outside the United States and its territories.
+    NotCovered,
+    /// A time zone missing from [`ZONES`].
+    UnknownTimeZone(String),
+    /// NWS does not look up place names.
+    NoPlaceSearch,
+    /// Replaying, and this request was never recorded.
+    NotRecorded(std::path::PathBuf),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Request(err) if err.is_timeout() => {
+                write!(f, "The National Weather Service did not answer in time.")
+            }
+            Error::Request(_) => write!(f, "Could not reach the National Weather Service."),
+            Error::Decode(_) => {
+                write!(f, "The National Weather Service sent something unreadable.")
+            }
+            Error::Incomplete(what) => {
+                write!(f, "The National Weather Service returned no {what}.")
+            }
+            Error::NotCovered => write!(
+                f,
+                "The National Weather Service does not forecast for this place."
+            ),
+            Error::UnknownTimeZone(zone) => write!(
+                f,
+                "The National Weather Service put this place in {zone}, which this site cannot convert to."
+            ),
+            Error::NoPlaceSearch => write!(f, "The National Weather Service has no place search."),
+            Error::NotRecorded(path) => write!(
+                f,
+                "No recorded National Weather Service response at {}.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Request(err) => Some(err),
+            Error::Decode(err) => Some(err),
+            Error::Incomplete(_)
+            | Error::NotCovered
+            | Error::UnknownTimeZone(_)
+            | Error::NoPlaceSearch
+            | Error::NotRecorded(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Request(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Decode(err)
+    }
+}
+
+#[derive(Clone)]
+struct CacheEntry {
+    forecast: Arc<Forecast>,
+    fresh_until: Instant,
+}
+
+/// The api.weather.gov client, with its own forecast cache.
+pub struct Nws {
+    client: reqwest::Client,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+    upstream: Mode,
+}
+
+impl Nws {
+    /// A client with an empty cache. NWS turns away requests without a
+    /// `User-Agent` that says who is asking, so it names `website_domain`.
+    pub fn new(upstream: Mode, website_domain: &str) -> Self {
+        Nws {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent(format!("({website_domain})"))
+                .build()
+                .expect("failed to build http client"),
+            cache: Mutex::new(HashMap::new()),
+            upstream,
+        }
+    }
+
+    /// Today and the rest of the week for a point, from the grid cell NWS
+    /// files it under.
+    pub async fn forecast(&self, latitude: f64, longitude: f64) -> Result<Arc<Forecast>, Error> {
+        let key = format!("{latitude:.4},{longitude:.4}");
+
+        if let Some(entry) = self.cache.lock().expect("cache mutex poisoned").get(&key) {
+            if Instant::now() < entry.fresh_until {
+                return Ok(entry.forecast.clone());
+            }
+        }
+
+        let body = self
+            .fetch(
+                format!("{API_URL}/points/{key}"),
+                fixtures::path("nws", "points", &key),
+            )
+            .await?;
+        let point = serde_json::from_slice::<ApiPoint>(&body)?.properties;
+        let zone = zone(&point.time_zone).ok_or(Error::UnknownTimeZone(point.time_zone))?;
+
+        let cell = format!("{}/{},{}", point.grid_id, point.grid_x, point.grid_y);
+        let body = self
+            .fetch(
+                format!("{API_URL}/gridpoints/{cell}"),
+                fixtures::path("nws", "gridpoints", &cell),
+            )
+            .await?;
+        let grid = serde_json::from_slice::<ApiGridpoint>(&body)?;
+
+        // A replayed grid is frozen at the moment it was issued, so that
+        // stands in for the clock; otherwise "now" would drift off the end
+        // of the recording.
+        let now = match self.upstream {
+            Mode::Replay => parse_instant(&grid.properties.update_time)
+                .ok_or(Error::Incomplete("issue time"))?,
+            Mode::Live | Mode::Record => {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    / 60.0
+            }
+        };
+        let forecast = Arc::new(grid.into_forecast(zone, now, latitude, longitude)?);
+
+        let mut cache = self.cache.lock().expect("cache mutex poisoned");
+        let now = Instant::now();
+        cache.retain(|_, entry| now < entry.fresh_until);
+        if cache.len() >= CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                forecast: forecast.clone(),
+                fresh_until: now + CACHE_TTL,
+            },
+        );
+
+        Ok(forecast)
+    }
+
+    /// One GET, or its recording: see [`fixtures`]. NWS answers a point
+    /// outside its area with a 404.
+    async fn fetch(&self, url: String, fixture: std::path::PathBuf) -> Result<Vec<u8>, Error> {
+        if self.upstream == Mode::Replay {
+            return std::fs::read(&fixture).map_err(|_| Error::NotRecorded(fixture));
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/geo+json")
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::NotCovered);
+        }
+        let body = response.error_for_status()?.bytes().await?;
+        if self.upstream == Mode::Record {
+            if let Err(err) = fixtures::save(&fixture, &body) {
+                tracing::warn!("Could not record {}: {err}", fixture.display());
+            }
+        }
+        Ok(body.to_vec())
+    }
+}
+
+impl WeatherProvider for Nws {
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, services::Error>> {
+        Box::pin(async move { Ok(Nws::forecast(self, latitude, longitude).await?) })
+    }
+
+    fn geocode<'a>(&'a self, _: &'a str) -> BoxFuture<'a, Result<Vec<Place>, services::Error>> {
+        Box::pin(async { Err(Error::NoPlaceSearch.into()) })
+    }
+}
+
+// ==================== Time zones ====================
+
+/// A US time zone as NWS names it.
+struct Zone {
+    /// Hours east of UTC in winter.
+    standard_hours: i32,
+    standard: &'static str,
+    /// `None` where the clocks never change.
+    daylight: Option<&'static str>,
+}
+
+/// IANA name, or a prefix ending in `/` for a whole family; winter offset in
+/// hours; winter and summer abbreviations. First match wins, so the two
+/// Central zones in Indiana come before the Eastern prefix that would
+/// otherwise take them.
+const ZONES: &[(&str, i32, &str, Option<&str>)] = &[
+    ("America/Los_Angeles", -8, "PST", Some("PDT")),
+    ("America/Denver", -7, "MST", Some("MDT")),
+    ("America/Boise", -7, "MST", Some("MDT")),
+    ("America/Phoenix", -7, "MST", None),
+    ("America/Chicago", -6, "CST", Some("CDT")),
+    ("America/Menominee", -6, "CST", Some("CDT")),
+    ("America/North_Dakota/", -6, "CST", Some("CDT")),
+    ("America/Indiana/Knox", -6, "CST", Some("CDT")),
+    ("America/Indiana/Tell_City", -6, "CST", Some("CDT")),
+    ("America/New_York", -5, "EST", Some("EDT")),
+    ("America/Detroit", -5, "EST", Some("EDT")),
+    ("America/Indiana/", -5, "EST", Some("EDT")),
+    ("America/Kentucky/", -5, "EST", Some("EDT")),
+    ("America/Puerto_Rico", -4, "AST", None),
+    ("America/St_Thomas", -4, "AST", None),
+    ("America/Anchorage", -9, "AKST", Some("AKDT")),
+    ("America/Juneau", -9, "AKST", Some("AKDT")),
+    ("America/Sitka", -9, "AKST", Some("AKDT")),
+    ("America/Yakutat", -9, "AKST", Some("AKDT")),
+    ("America/Nome", -9, "AKST", Some("AKDT")),
+    ("America/Metlakatla", -9, "AKST", Some("AKDT")),
+    ("America/Adak", -10, "HST", Some("HDT")),
+    ("Pacific/Honolulu", -10, "HST", None),
+    ("Pacific/Guam", 10, "ChST", None),
+    ("Pacific/Saipan", 10, "ChST", None),
+    ("Pacific/Pago_Pago", -11, "SST", None),
+];
+
+fn zone(name: &str) -> Option<Zone> {
+    ZONES
+        .iter()
+        .find(|(zone, ..)| {
+            if zone.ends_with('/') {
+                name.starts_with(zone)
+            } else {
+                name == *zone
+            }
+        })
+        .map(|&(_, standard_hours, standard, daylight)| Zone {
+            standard_hours,
+            standard,
+            daylight,
+        })
+}
+
+impl Zone {
+    /// Local time minus UTC, and its abbreviation, at UTC hour `hour` (hours
+    /// since 1970).
+    ///
+    /// Daylight time runs from 2 AM on the second Sunday in March to 2 AM on
+    /// the first Sunday in November, each in the clock time then in force.
+    fn offset_at(&self, hour: i64) -> (i32, &'static str) {
+        let standard_seconds = self.standard_hours * 3600;
+        let Some(daylight) = self.daylight else {
+            return (standard_seconds, self.standard);
+        };
+        let year = &clock::format_local(hour.div_euclid(24), 0.0)[..4];
+        let first_sunday_from = |date: String| {
+            let days = clock::parse_date(&date).unwrap_or_default();
+            days + (7 - clock::weekday(days)) % 7
+        };
+        let standard_hours = i64::from(self.standard_hours);
+        let starts = first_sunday_from(format!("{year}-03-08")) * 24 + 2 - standard_hours;
+        let ends = first_sunday_from(format!("{year}-11-01")) * 24 + 1 - standard_hours;
+        if (starts..ends).contains(&hour) {
+            (standard_seconds + 3600, daylight)
+        } else {
+            (standard_seconds, self.standard)
+        }
+    }
+
+    /// `YYYY-MM-DDTHH:MM` on the local clock at UTC hour `hour`.
+    fn local(&self, hour: i64) -> String {
+        let (offset, _) = self.offset_at(hour);
+        clock::format_local(
+            hour.div_euclid(24),
+            (hour.rem_euclid(24) * 60) as f64 + f64::from(offset) / 60.0,
+        )
+    }
+}
+
+/// Minutes since 1970 for an ISO 8601 timestamp with an offset, as NWS writes
+/// them: `2026-10-14T20:00:00+00:00`.
+fn parse_instant(text: &str) -> Option<f64> {
+    let (days, minutes) = clock::parse_local(text)?;
+    let offset = match text.get(19..)? {
+        "Z" | "" => 0.0,
+        zone => {
+            let sign = match zone.get(..1)? {
+                "+" => 1.0,
+                "-" => -1.0,
+                _ => return None,
+            };
+            let hours: f64 = zone.get(1..3)?.parse().ok()?;
+            let minutes: f64 = zone.get(4..6)?.parse().ok()?;
+            sign * (hours * 60.0 + minutes)
+        }
+    };
+    Some(days as f64 * 1440.0 + minutes - offset)
+}
+
+/// The UTC hours an interval such as `2026-10-14T20:00:00+00:00/PT3H` or
+/// `.../P1DT6H` covers, as a first hour since 1970 and a count.
+fn parse_interval(valid_time: &str) -> Option<(i64, i64)> {
+    let (start, duration) = valid_time.split_once('/')?;
+    let first = (parse_instant(start)? / 60.0).floor() as i64;
+
+    let duration = duration.strip_prefix('P')?;
+    let (date_part, time_part) = duration.split_once('T').unwrap_or((duration, ""));
+    let mut hours = 0;
+    for (part, units) in [(date_part, [('D', 24)]), (time_part, [('H', 1)])] {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+            } else {
+                let count: i64 = number.parse().ok()?;
+                number.clear();
+                hours += units
+                    .iter()
+                    .find(|(unit, _)| *unit == c)
+                    .map_or(0, |(_, per)| count * per);
+            }
+        }
+    }
+    Some((first, hours.max(1)))
+}
+
+// ==================== Wire types ====================
+
+#[derive(Deserialize)]
+struct ApiPoint {
+    properties: ApiPointProperties,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiPointProperties {
+    grid_id: String,
+    grid_x: i64,
+    grid_y: i64,
+    time_zone: String,
+}
+
+#[derive(Deserialize)]
+struct ApiGridpoint {
+    geometry: Option<ApiPolygon>,
+    properties: ApiGrid,
+}
+
+#[derive(Deserialize)]
+struct ApiPolygon {
+    coordinates: Vec<Vec<[f64; 2]>>,
+}
+
+/// Only the fields this site reads; the grid has dozens more. A field NWS
+/// leaves out for a cell reads as an empty series.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiGrid {
+    update_time: String,
+    elevation: ApiMeasure,
+    temperature: ApiSeries,
+    #[serde(default)]
+    max_temperature: ApiSeries,
+    #[serde(default)]
+    min_temperature: ApiSeries,
+    relative_humidity: ApiSeries,
+    wind_speed: ApiSeries,
+    #[serde(default)]
+    wind_gust: ApiSeries,
+    #[serde(default)]
+    sky_cover: ApiSeries,
+    #[serde(default)]
+    probability_of_precipitation: ApiSeries,
+    #[serde(default)]
+    quantitative_precipitation: ApiSeries,
+}
+
+#[derive(Deserialize)]
+struct ApiMeasure {
+    value: Option<f64>,
+}
+
+#[derive(Default, Deserialize)]
+struct ApiSeries {
+    uom: Option<String>,
+    values: Vec<ApiValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiValue {
+    valid_time: String,
+    value: Option<f64>,
+}
+
+impl ApiSeries {
+    /// Every value in Celsius, m/s, percent or mm, keyed by each UTC hour its
+    /// interval covers. A total over several hours, like precipitation, is
+    /// `shared` out evenly between them; anything else holds for each.
+    fn by_hour(&self, shared: bool) -> HashMap<i64, f64> {
+        let mut hours = HashMap::new();
+        for entry in &self.values {
+            let (Some(value), Some((first, count))) =
+                (entry.value, parse_interval(&entry.valid_time))
+            else {
+                continue;
+            };
+            let value = in_site_units(self.uom.as_deref(), value);
+            let value = if shared { value / count as f64 } else { value };
+            for hour in first..first + count {
+                hours.insert(hour, value);
+            }
+        }
+        hours
+    }
+}
+
+/// NWS names each series' unit in WMO codes; this site works in SI.
+fn in_site_units(uom: Option<&str>, value: f64) -> f64 {
+    match uom {
+        Some("wmoUnit:degF") => (value - 32.0) / 1.8,
+        Some("wmoUnit:km_h-1") => value / 3.6,
+        Some("wmoUnit:kt") => value * 0.514_444,
+        _ => value,
+    }
+}
+
+impl ApiGridpoint {
+    /// The grid as a [`Forecast`], with `now` in minutes since 1970 and the
+    /// requested point as a stand-in if the cell comes without its outline.
+    fn into_forecast(
+        self,
+        zone: Zone,
+        now: f64,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Forecast, Error> {
+        // The middle of the cell, for the distance the page reports. The
+        // outline repeats its first corner to close the ring.
+        let (grid_latitude, grid_longitude) = self
+            .geometry
+            .as_ref()
+            .and_then(|polygon| polygon.coordinates.first())
+            .filter(|ring| ring.len() > 1)
+            .map(|ring| {
+                let corners = &ring[..ring.len() - 1];
+                let n = corners.len() as f64;
+                (
+                    corners.iter().map(|[_, lat]| lat).sum::<f64>() / n,
+                    corners.iter().map(|[lon, _]| lon).sum::<f64>() / n,
+                )
+            })
+            .unwrap_or((latitude, longitude));
+
+        let grid = self.properties;
+        let temperature = grid.temperature.by_hour(false);
+        let humidity = grid.relative_humidity.by_hour(false);
+        let wind = grid.wind_speed.by_hour(false);
+        let gust = grid.wind_gust.by_hour(false);
+        let sky = grid.sky_cover.by_hour(false);
+        let chance = grid.probability_of_precipitation.by_hour(false);
+        let precipitation = grid.quantitative_precipitation.by_hour(true);
+
+        let mut utc_hours: Vec<i64> = temperature.keys().copied().collect();
+        utc_hours.sort_unstable();
+        let hours: Vec<Hour> = utc_hours
+            .into_iter()
+            .filter_map(|hour| {
+                let (offset, _) = zone.offset_at(hour);
+                let time = zone.local(hour);
+                let sun = solar::over_hour_ending(&time, offset, grid_latitude, grid_longitude)?;
+                let cloud_cover = sky.get(&hour).copied().unwrap_or(0.0);
+                let radiation = solar::under_cloud(sun, cloud_cover);
+                let wind_speed = *wind.get(&hour)?;
+                Some(Hour {
+                    air: Temperature::from_celsius(*temperature.get(&hour)?),
+                    relative_humidity: *humidity.get(&hour)?,
+                    wind: Speed::from_meters_per_second(wind_speed),
+                    gust: Speed::from_meters_per_second(
+                        gust.get(&hour).copied().unwrap_or(wind_speed),
+                    ),
+                    precipitation_mm: precipitation.get(&hour).copied().unwrap_or(0.0),
+                    precipitation_probability: chance.get(&hour).copied().unwrap_or(0.0),
+                    cloud_cover,
+                    sunshine_seconds: if sun.elevation_deg > 0.0 {
+                        (1.0 - cloud_cover / 100.0).clamp(0.0, 1.0) * 3600.0
+                    } else {
+                        0.0
+                    },
+                    direct_normal: radiation.direct_normal,
+                    direct_horizontal: radiation.direct_horizontal,
+                    diffuse: radiation.diffuse,
+                    radiation_estimated: sun.elevation_deg > 0.0,
+                    sun,
+                    time,
+                })
+            })
+            .collect();
+
+        if hours.is_empty() {
+            return Err(Error::Incomplete("hourly data"));
+        }
+
+        // The high is the daytime period that starts on a date; the low is
+        // the overnight period that ends on one, which is how NWS itself
+        // labels them.
+        let mut highs = BTreeMap::new();
+        for entry in &grid.max_temperature.values {
+            if let (Some(value), Some((first, _))) =
+                (entry.value, parse_interval(&entry.valid_time))
+            {
+                let date = zone.local(first)[..10].to_owned();
+                let value = in_site_units(grid.max_temperature.uom.as_deref(), value);
+                highs.insert(date, value);
+            }
+        }
+        let mut lows = HashMap::new();
+        for entry in &grid.min_temperature.values {
+            if let (Some(value), Some((first, count))) =
+                (entry.value, parse_interval(&entry.valid_time))
+            {
+                let date = zone.local(first + count)[..10].to_owned();
+                let value = in_site_units(grid.min_temperature.uom.as_deref(), value);
+                lows.insert(date, value);
+            }
+        }
+
+        let days: Vec<Day> = highs
+            .into_iter()
+            .filter_map(|(date, high)| {
+                let low = *lows.get(&date)?;
+                // Whichever clock is in force at local noon.
+                let noon = clock::parse_date(&date)? * 24 + 12 - i64::from(zone.standard_hours);
+                let (offset, _) = zone.offset_at(noon);
+                let (sunrise, sunset) =
+                    solar::sunrise_sunset(&date, offset, grid_latitude, grid_longitude)?;
+                Some(Day {
+                    date,
+                    high: Temperature::from_celsius(high),
+                    low: Temperature::from_celsius(low),
+                    sunrise,
+                    sunset,
+                })
+            })
+            .collect();
+
+        if days.is_empty() {
+            return Err(Error::Incomplete("daily data"));
+        }
+
+        let (offset, abbreviation) = zone.offset_at((now / 60.0).floor() as i64);
+        Ok(Forecast {
+            grid_latitude,
+            grid_longitude,
+            grid_elevation: grid.elevation.value.unwrap_or(0.0),
+            timezone_abbreviation: abbreviation.to_owned(),
+            utc_offset_seconds: offset,
+            current_time: clock::format_local(0, now + f64::from(offset) / 60.0),
+            hours,
+            days,
+            provider: NAME,
+            estimated: vec!["solar radiation", "sunshine"],
+            fallback: None,
+            stale: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hours since 1970 for a UTC timestamp.
+    fn hour(utc: &str) -> i64 {
+        (parse_instant(utc).unwrap() / 60.0) as i64
+    }
+
+    #[test]
+    fn reads_intervals_of_hours_and_days() {
+        let start = hour("2026-10-14T20:00:00+00:00");
+        assert_eq!(
+            parse_interval("2026-10-14T20:00:00+00:00/PT3H"),
+            Some((start, 3))
+        );
+        assert_eq!(
+            parse_interval("2026-10-14T20:00:00+00:00/P1DT6H"),
+            Some((start, 30))
+        );
+        assert_eq!(
+            parse_interval("2026-10-14T13:00:00-07:00/PT1H"),
+            Some((start, 1))
+        );
+        assert_eq!(parse_interval("2026-10-14T20:00:00+00:00"), None);
+    }
+
+    #[test]
+    fn shares_a_total_out_and_repeats_anything_else() {
+        let series = ApiSeries {
+            uom: Some("wmoUnit:mm".to_owned()),
+            values: vec![ApiValue {
+                valid_time: "2026-10-14T18:00:00+00:00/PT6H".to_owned(),
+                value: Some(3.0),
+            }],
+        };
+        let start = hour("2026-10-14T18:00:00+00:00");
+        let shared = series.by_hour(true);
+        assert_eq!(shared.len(), 6);
+        assert_eq!(shared[&start], 0.5);
+        assert_eq!(series.by_hour(false)[&(start + 5)], 3.0);
+    }
+
+    #[test]
+    fn converts_to_site_units() {
+        assert_eq!(in_site_units(Some("wmoUnit:km_h-1"), 36.0), 10.0);
+        assert_eq!(in_site_units(Some("wmoUnit:degF"), 50.0), 10.0);
+        assert_eq!(in_site_units(Some("wmoUnit:degC"), 10.0), 10.0);
+    }
+
+    #[test]
+    fn keeps_daylight_time_on_the_us_dates() {
+        let pacific = zone("America/Los_Angeles").unwrap();
+        // 2026 changes on 8 March and 1 November, at 2 AM local.
+        assert_eq!(
+            pacific.offset_at(hour("2026-03-08T09:00:00+00:00")),
+            (-8 * 3600, "PST")
+        );
+        assert_eq!(
+            pacific.offset_at(hour("2026-03-08T10:00:00+00:00")),
+            (-7 * 3600, "PDT")
+        );
+        assert_eq!(
+            pacific.offset_at(hour("2026-11-01T08:00:00+00:00")),
+            (-7 * 3600, "PDT")
+        );
+        assert_eq!(
+            pacific.offset_at(hour("2026-11-01T09:00:00+00:00")),
+            (-8 * 3600, "PST")
+        );
+        assert_eq!(
+            pacific.local(hour("2026-10-14T20:00:00+00:00")),
+            "2026-10-14T13:00"
+        );
+    }
+
+    #[test]
+    fn finds_zones_by_name_or_family() {
+        let phoenix = zone("America/Phoenix").unwrap();
+        assert_eq!(
+            phoenix.offset_at(hour("2026-07-01T00:00:00+00:00")),
+            (-7 * 3600, "MST")
+        );
+        assert_eq!(zone("America/Indiana/Knox").unwrap().standard_hours, -6);
+        assert_eq!(zone("America/Indiana/Vevay").unwrap().standard_hours, -5);
+        assert!(zone("Europe/London").is_none());
+    }
+
+    #[tokio::test]
+    async fn replays_a_recorded_grid() {
+        let client = Nws::new(Mode::Replay, "localhost");
+        let forecast = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert_eq!(forecast.provider, NAME);
+        assert_eq!(forecast.current_time, "2026-10-14T13:15");
+        assert_eq!(forecast.timezone_abbreviation, "PDT");
+        assert_eq!(forecast.utc_offset_seconds, -7 * 3600);
+        assert_eq!(forecast.hours.len(), 7 * 24);
+        assert_eq!(forecast.hours[0].time, "2026-10-14T00:00");
+        assert_eq!(forecast.days.len(), 7);
+        assert_eq!(forecast.days[0].date, "2026-10-14");
+        assert!(forecast.days[0].low.celsius() < forecast.days[0].high.celsius());
+        // The middle of the cell, not the point asked for.
+        assert!((forecast.grid_latitude - 37.788).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn estimates_the_radiation_it_does_not_publish() {
+        let client = Nws::new(Mode::Replay, "localhost");
+        let forecast = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert_eq!(forecast.estimated, ["solar radiation", "sunshine"]);
+        let at = |time: &str| {
+            forecast
+                .hours
+                .iter()
+                .find(|hour| hour.time == time)
+                .unwrap()
+        };
+        assert_eq!(at("2026-10-14T02:00").direct_normal, 0.0);
+        assert_eq!(at("2026-10-14T02:00").sunshine_seconds, 0.0);
+        assert!(at("2026-10-14T13:00").direct_normal > 300.0);
+        assert!(at("2026-10-14T13:00").wind.meters_per_second() > 3.0);
+    }
+
+    #[tokio::test]
+    async fn an_unrecorded_point_fails_and_there_is_no_search() {
+        let client = Nws::new(Mode::Replay, "localhost");
+        let err = client.forecast(0.0, 0.0).await.unwrap_err();
+        assert!(matches!(err, Error::NotRecorded(_)), "{err:?}");
+        let err = WeatherProvider::geocode(&client, "Oakland")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The National Weather Service has no place search."
+        );
+    }
+}
@@ -14,13 +14,23 @@
 //! `diffuse_radiation` hourly, needs no API key at all (so there is no secret
 //! for this server to hold or rotate), serves history and forecast from one
 //! endpoint via `past_days`, and is free for non-commercial use.
+//!
+//! Being the only such source also makes it a single point of failure, so a
+//! US forecast falls back to NWS when it cannot answer; see
+//! [`super::fallback`].
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, LazyLock, Mutex};
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
 
 use crate::helpers::query_string;
+use crate::services::fixtures::{self, Mode};
+use crate::services::{self, BoxFuture, WeatherProvider};
+use crate::solar::{self, Position};
 use crate::units::{Speed, Temperature};
 
 /// Upstream is on the critical path of a page load, so fail fast rather than
@@ -29,32 +39,41 @@ const REQUEST_TIMEOUT: Duration = Duration::from_secs(6);
 
 /// Open-Meteo updates hourly data every 15 minutes or so; a shorter window just
 /// spends someone else's quota.
-const CACHE_TTL: Duration = Duration::from_secs(600);
+pub const CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// How long past [`CACHE_TTL`] a forecast may still be served while a newer
+/// one is fetched. Long enough to ride out a short outage, short enough that
+/// the hour the page calls "now" is never far off.
+const STALE_WINDOW: Duration = Duration::from_secs(1800);
 
 /// Upper bound on distinct locations held in memory. Small because the pinned
 /// list is small and search traffic is one person.
 const CACHE_CAPACITY: usize = 64;
 
-static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
-    reqwest::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .build()
-        .expect("failed to build http client")
-});
+/// How the page credits this provider.
+pub const NAME: &str = "Open-Meteo";
 
 const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
 const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
 
 /// Anything that can stop the page rendering a forecast.
-#[derive(Debug)]
+///
+/// Cloneable, so that one failed fetch can be handed to every request that was
+/// waiting on it.
+#[derive(Clone, Debug)]
 pub enum Error {
-    Request(reqwest::Error),
+    Request(Arc<reqwest::Error>),
     /// The body arrived but was not the JSON this client expects.
-    Decode(serde_json::Error),
+    Decode(Arc<serde_json::Error>),
     /// The response parsed but did not contain a usable day.
     Incomplete(&'static str),
     /// A place name that matched nothing.
     NoSuchPlace(String),
+    /// Replaying, and this request was never recorded.
+    NotRecorded(std::path::PathBuf),
+    /// The fetch this request joined ended without answering: it panicked,
+    /// or the runtime shut down under it.
+    Abandoned,
 }
 
 impl std::fmt::Display for Error {
@@ -67,6 +86,10 @@ impl std::fmt::Display for Error {
             Error::Decode(_) => write!(f, "Open-Meteo sent something unreadable."),
             Error::Incomplete(what) => write!(f, "Open-Meteo returned no {what}."),
             Error::NoSuchPlace(query) => write!(f, "No place matched \u{201c}{query}\u{201d}."),
+            Error::NotRecorded(path) => {
+                write!(f, "No recorded Open-Meteo response at {}.", path.display())
+            }
+            Error::Abandoned => write!(f, "The Open-Meteo fetch ended without an answer."),
         }
     }
 }
@@ -74,22 +97,25 @@ impl std::fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Error::Request(err) => Some(err),
-            Error::Decode(err) => Some(err),
-            Error::Incomplete(_) | Error::NoSuchPlace(_) => None,
+            Error::Request(err) => Some(err.as_ref()),
+            Error::Decode(err) => Some(err.as_ref()),
+            Error::Incomplete(_)
+            | Error::NoSuchPlace(_)
+            | Error::NotRecorded(_)
+            | Error::Abandoned => None,
         }
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
-        Error::Request(err)
+        Error::Request(Arc::new(err))
     }
 }
 
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
-        Error::Decode(err)
+        Error::Decode(Arc::new(err))
     }
 }
 
@@ -118,6 +144,13 @@ pub struct Hour {
     pub direct_normal: f64,
     pub direct_horizontal: f64,
     pub diffuse: f64,
+    /// Whether any of the three radiation figures was estimated here, from
+    /// the sun and the cloud cover, rather than published; see
+    /// [`solar::under_cloud`]. Only ever set while the sun is up.
+    pub radiation_estimated: bool,
+    /// Where the sun stood over the hour the radiation figures average, from
+    /// the grid point and the clock rather than from the radiation itself.
+    pub sun: Position,
 }
 
 /// One day's summary, in local time.
@@ -142,10 +175,24 @@ pub struct Forecast {
     /// Elevation of that grid cell (m).
     pub grid_elevation: f64,
     pub timezone_abbreviation: String,
+    /// Local time minus UTC, as of now. A daylight-saving change later in the
+    /// week is not reflected here.
+    pub utc_offset_seconds: i32,
     /// Current local time at the location, `YYYY-MM-DDTHH:MM`.
     pub current_time: String,
     pub hours: Vec<Hour>,
     pub days: Vec<Day>,
+    /// Who made the forecast, as the page credits it: [`NAME`] unless it came
+    /// from a fallback.
+    pub provider: &'static str,
+    /// What the provider does not publish and was worked out here instead,
+    /// e.g. `"solar radiation"`. Empty for Open-Meteo.
+    pub estimated: Vec<&'static str>,
+    /// Why the usual provider was passed over, when it was.
+    pub fallback: Option<String>,
+    /// Served from memory past its time because a newer one has not landed
+    /// yet. `current_time` says how old it is.
+    pub stale: bool,
 }
 
 /// A geocoding hit.
@@ -158,14 +205,105 @@ pub struct Place {
     pub longitude: f64,
 }
 
-#[derive(Clone)]
 struct CacheEntry {
     forecast: Arc<Forecast>,
+    /// The response it was decoded from, kept for the snapshot.
+    body: Arc<str>,
     fresh_until: Instant,
+    /// When a request last read this entry, for eviction.
+    used: Instant,
 }
 
-static CACHE: LazyLock<Mutex<HashMap<String, CacheEntry>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+/// A fetch under way, and what it came to once it has landed.
+type Flight = watch::Receiver<Option<Result<Arc<Forecast>, Error>>>;
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, CacheEntry>,
+    /// At most one fetch per key at a time; everyone else waits on it.
+    flights: HashMap<String, Flight>,
+    /// Snapshots taken so far. Numbered under this lock, so the number orders
+    /// them the way the cache changed.
+    snapshots: u64,
+}
+
+impl Cache {
+    /// Stores a forecast, dropping anything past serving and then, if still
+    /// full, whichever entry was read longest ago.
+    fn insert(
+        &mut self,
+        key: String,
+        forecast: Arc<Forecast>,
+        body: Arc<str>,
+        fresh_until: Instant,
+    ) {
+        let now = Instant::now();
+        self.entries
+            .retain(|_, entry| now < entry.fresh_until + STALE_WINDOW);
+        if self.entries.len() >= CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            key,
+            CacheEntry {
+                forecast,
+                body,
+                fresh_until,
+                used: now,
+            },
+        );
+    }
+
+    /// Every entry still fresh, with its deadline on the wall clock, which
+    /// unlike an [`Instant`] means something to the next process.
+    fn snapshot(&self) -> Vec<Saved> {
+        let now = Instant::now();
+        let wall = SystemTime::now();
+        self.entries
+            .iter()
+            .filter(|(_, entry)| now < entry.fresh_until)
+            .map(|(key, entry)| Saved {
+                key: key.clone(),
+                fresh_until: (wall + (entry.fresh_until - now))
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                body: entry.body.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// What a fetch needs, shared with the task that runs it so the fetch can
+/// outlive the request that started it.
+struct Shared {
+    client: reqwest::Client,
+    cache: Mutex<Cache>,
+    upstream: Mode,
+    /// Where the cache is snapshotted after every fetch, if anywhere.
+    snapshot_path: Option<PathBuf>,
+    /// The number of the snapshot last written, held for the length of a
+    /// write so that writes go one at a time.
+    saved: tokio::sync::Mutex<u64>,
+}
+
+/// The Open-Meteo client, with its own forecast cache.
+///
+/// A forecast past its [`CACHE_TTL`] is still served for [`STALE_WINDOW`]
+/// longer, marked [`Forecast::stale`], while a fresh one is fetched in the
+/// background. So an expired entry never makes a page wait, and an outage
+/// shorter than the window is not one anybody sees. Concurrent requests for
+/// a place share a single fetch.
+pub struct OpenMeteo {
+    shared: Arc<Shared>,
+}
 
 /// Cache key. Rounded so that a search result and a pin for the same block
 /// share an entry, and so float formatting cannot produce two keys for one
@@ -174,100 +312,384 @@ fn cache_key(latitude: f64, longitude: f64) -> String {
     format!("{latitude:.4},{longitude:.4}")
 }
 
-/// Fetches yesterday, today and tomorrow for a point.
-///
-/// Yesterday comes from the same endpoint via `past_days`, which serves the
-/// most recent model analysis for hours that have already happened rather than
-/// the forecast that was live at the time.
-pub async fn forecast(latitude: f64, longitude: f64) -> Result<Arc<Forecast>, Error> {
-    let key = cache_key(latitude, longitude);
-
-    if let Some(entry) = CACHE.lock().expect("cache mutex poisoned").get(&key) {
-        if Instant::now() < entry.fresh_until {
-            return Ok(entry.forecast.clone());
+impl OpenMeteo {
+    /// A client with an empty cache, calling, recording or replaying as
+    /// `upstream` says.
+    pub fn new(upstream: Mode) -> Self {
+        OpenMeteo::build(upstream, Cache::default(), None)
+    }
+
+    /// A client whose cache starts from the snapshot at `path`, and is saved
+    /// back there after every fetch.
+    ///
+    /// Both deploy slots run from the same directory, so the slot coming up
+    /// starts with whatever the one going down had. Anything past its
+    /// deadline is left behind, and a snapshot that will not read is logged
+    /// and ignored: the worst it can cost is the wait there would have been
+    /// anyway.
+    pub fn with_snapshot(upstream: Mode, path: PathBuf) -> Self {
+        let cache = match std::fs::read(&path) {
+            Ok(bytes) => restore(&bytes).unwrap_or_else(|err| {
+                tracing::warn!("Ignoring the forecast cache in {}: {err}", path.display());
+                Cache::default()
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Cache::default(),
+            Err(err) => {
+                tracing::warn!("Could not read {}: {err}", path.display());
+                Cache::default()
+            }
+        };
+        tracing::info!(
+            "Starting with {} forecasts from {}",
+            cache.entries.len(),
+            path.display()
+        );
+        OpenMeteo::build(upstream, cache, Some(path))
+    }
+
+    fn build(upstream: Mode, cache: Cache, snapshot_path: Option<PathBuf>) -> Self {
+        OpenMeteo {
+            shared: Arc::new(Shared {
+                client: reqwest::Client::builder()
+                    .timeout(REQUEST_TIMEOUT)
+                    .build()
+                    .expect("failed to build http client"),
+                cache: Mutex::new(cache),
+                upstream,
+                snapshot_path,
+                saved: tokio::sync::Mutex::new(0),
+            }),
+        }
+    }
+
+    /// Fetches yesterday and the week ahead for a point.
+    ///
+    /// Yesterday comes from the same endpoint via `past_days`, which serves the
+    /// most recent model analysis for hours that have already happened rather than
+    /// the forecast that was live at the time.
+    pub async fn forecast(&self, latitude: f64, longitude: f64) -> Result<Arc<Forecast>, Error> {
+        let key = cache_key(latitude, longitude);
+        let now = Instant::now();
+
+        let (stale, flight) = {
+            let mut cache = self.shared.cache.lock().expect("cache mutex poisoned");
+            let stale = match cache.entries.get_mut(&key) {
+                Some(entry) if now < entry.fresh_until => {
+                    entry.used = now;
+                    return Ok(entry.forecast.clone());
+                }
+                Some(entry) if now < entry.fresh_until + STALE_WINDOW => {
+                    entry.used = now;
+                    Some(entry.forecast.clone())
+                }
+                _ => None,
+            };
+            let flight = match cache.flights.get(&key) {
+                Some(flight) => flight.clone(),
+                None => self.launch(&mut cache, key, latitude, longitude),
+            };
+            (stale, flight)
+        };
+
+        if let Some(stale) = stale {
+            let mut forecast = Forecast::clone(&stale);
+            forecast.stale = true;
+            return Ok(Arc::new(forecast));
         }
+
+        land(flight).await
+    }
+
+    /// Fetches a new forecast for a point even if the cached one is still
+    /// fresh, joining a fetch already under way rather than starting another.
+    pub async fn refresh(&self, latitude: f64, longitude: f64) -> Result<Arc<Forecast>, Error> {
+        let key = cache_key(latitude, longitude);
+        let flight = {
+            let mut cache = self.shared.cache.lock().expect("cache mutex poisoned");
+            match cache.flights.get(&key) {
+                Some(flight) => flight.clone(),
+                None => self.launch(&mut cache, key, latitude, longitude),
+            }
+        };
+        land(flight).await
     }
 
-    let query = query_string(&[
-        ("latitude", &format!("{latitude:.4}")),
-        ("longitude", &format!("{longitude:.4}")),
-        // Both direct components are requested: the normal one drives the
-        // radiation budget, the horizontal one recovers the solar elevation.
-        (
-            "hourly",
-            "temperature_2m,relative_humidity_2m,precipitation,precipitation_probability,\
-             cloud_cover,sunshine_duration,wind_speed_10m,wind_gusts_10m,\
-             direct_radiation,diffuse_radiation,direct_normal_irradiance",
-        ),
-        (
-            "daily",
-            "temperature_2m_max,temperature_2m_min,sunrise,sunset",
-        ),
-        ("current", "temperature_2m"),
-        ("past_days", "1"),
-        ("forecast_days", "2"),
-        // Everything comes back in the location's own clock time, so a
-        // stateless server never has to know what time it is anywhere.
-        ("timezone", "auto"),
-        ("wind_speed_unit", "ms"),
-    ]);
-
-    let body = CLIENT
-        .get(format!("{FORECAST_URL}?{query}"))
-        .send()
-        .await?
-        .error_for_status()?
-        .bytes()
-        .await?;
-
-    let forecast = Arc::new(serde_json::from_slice::<ApiForecast>(&body)?.into_forecast()?);
-
-    let mut cache = CACHE.lock().expect("cache mutex poisoned");
+    /// Starts the one fetch for `key` and registers it, so anyone asking
+    /// before it lands waits on it rather than starting another.
+    fn launch(&self, cache: &mut Cache, key: String, latitude: f64, longitude: f64) -> Flight {
+        let (answer, flight) = watch::channel(None);
+        cache.flights.insert(key.clone(), flight.clone());
+        let shared = self.shared.clone();
+        let grounding = Grounding {
+            shared: shared.clone(),
+            key: key.clone(),
+            flight: flight.clone(),
+        };
+        tokio::spawn(async move {
+            let _grounding = grounding;
+            let result = shared.download(latitude, longitude, &key).await;
+            let snapshot = {
+                let mut cache = shared.cache.lock().expect("cache mutex poisoned");
+                cache.flights.remove(&key);
+                match &result {
+                    Ok((forecast, body)) => {
+                        let fresh_until = Instant::now() + CACHE_TTL;
+                        cache.insert(key, forecast.clone(), body.clone(), fresh_until);
+                        shared.snapshot_path.as_ref().map(|_| {
+                            cache.snapshots += 1;
+                            (cache.snapshots, cache.snapshot())
+                        })
+                    }
+                    Err(err) => {
+                        tracing::warn!("Could not refresh {key}: {err}");
+                        None
+                    }
+                }
+            };
+            answer.send_replace(Some(result.map(|(forecast, _)| forecast)));
+            if let (Some(path), Some((number, snapshot))) = (&shared.snapshot_path, snapshot) {
+                shared.save(path, number, snapshot).await;
+            }
+        });
+        flight
+    }
+
+    /// Looks a place name up. Results are ordered by population, so the first is
+    /// almost always the intended one and the rest become "did you mean" links.
+    pub async fn geocode(&self, query: &str) -> Result<Vec<Place>, Error> {
+        let parameters = query_string(&[
+            ("name", query),
+            ("count", "5"),
+            ("language", "en"),
+            ("format", "json"),
+        ]);
+
+        let body = self
+            .shared
+            .fetch(
+                format!("{GEOCODING_URL}?{parameters}"),
+                fixtures::path("open-meteo", "geocode", query),
+            )
+            .await?;
+
+        let places: Vec<Place> = serde_json::from_slice::<ApiGeocoding>(&body)?
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .map(ApiPlace::into_place)
+            .collect();
+
+        if places.is_empty() {
+            return Err(Error::NoSuchPlace(query.to_owned()));
+        }
+        Ok(places)
+    }
+}
+
+impl Shared {
+    /// One forecast straight from upstream, uncached, with the body it came
+    /// in.
+    async fn download(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        key: &str,
+    ) -> Result<(Arc<Forecast>, Arc<str>), Error> {
+        let query = query_string(&[
+            ("latitude", &format!("{latitude:.4}")),
+            ("longitude", &format!("{longitude:.4}")),
+            // Both direct components are requested: the normal one drives the
+            // radiation budget, the horizontal one adds to what the ground
+            // reflects.
+            (
+                "hourly",
+                "temperature_2m,relative_humidity_2m,precipitation,precipitation_probability,\
+                 cloud_cover,sunshine_duration,wind_speed_10m,wind_gusts_10m,\
+                 direct_radiation,diffuse_radiation,direct_normal_irradiance",
+            ),
+            (
+                "daily",
+                "temperature_2m_max,temperature_2m_min,sunrise,sunset",
+            ),
+            ("current", "temperature_2m"),
+            ("past_days", "1"),
+            // Today plus six: the outlook's week.
+            ("forecast_days", "7"),
+            // Everything comes back in the location's own clock time, so a
+            // stateless server never has to know what time it is anywhere.
+            ("timezone", "auto"),
+            ("wind_speed_unit", "ms"),
+        ]);
+
+        let body = self
+            .fetch(
+                format!("{FORECAST_URL}?{query}"),
+                fixtures::path("open-meteo", "forecast", key),
+            )
+            .await?;
+
+        let forecast = serde_json::from_slice::<ApiForecast>(&body)?.into_forecast()?;
+        Ok((Arc::new(forecast), String::from_utf8_lossy(&body).into()))
+    }
+
+    /// One GET, or its recording: see [`fixtures`].
+    async fn fetch(&self, url: String, fixture: std::path::PathBuf) -> Result<Vec<u8>, Error> {
+        if self.upstream == Mode::Replay {
+            return std::fs::read(&fixture).map_err(|_| Error::NotRecorded(fixture));
+        }
+
+        let body = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        if self.upstream == Mode::Record {
+            if let Err(err) = fixtures::save(&fixture, &body) {
+                tracing::warn!("Could not record {}: {err}", fixture.display());
+            }
+        }
+        Ok(body.to_vec())
+    }
+}
+
+/// Waits for a fetch to answer.
+async fn land(mut flight: Flight) -> Result<Arc<Forecast>, Error> {
+    let landed = flight
+        .wait_for(Option::is_some)
+        .await
+        .map_err(|_| Error::Abandoned)?;
+    landed.clone().expect("waited for an answer")
+}
+
+/// Takes a flight off the board when the task flying it ends, however it
+/// ends. One that panics or is cancelled never answers, and left registered
+/// it would be joined, and failed, by every request for that place after.
+struct Grounding {
+    shared: Arc<Shared>,
+    key: String,
+    flight: Flight,
+}
+
+impl Drop for Grounding {
+    fn drop(&mut self) {
+        // Poisoned or not, the map is still sound: nothing panics mid-update.
+        let mut cache = self
+            .shared
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // A fetch that landed has already gone, and a newer one for the same
+        // place may have taken its slot since.
+        if cache
+            .flights
+            .get(&self.key)
+            .is_some_and(|flight| flight.same_channel(&self.flight))
+        {
+            cache.flights.remove(&self.key);
+        }
+    }
+}
+
+// ==================== Snapshot ====================
+
+/// One cache entry on disk. The raw response rather than the decoded
+/// [`Forecast`], so loading it runs exactly the decoding a fetch does.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    key: String,
+    /// Unix seconds.
+    fresh_until: u64,
+    body: String,
+}
+
+impl Shared {
+    /// Writes snapshot `number` to `path`, unless a later one has been written
+    /// already. Fetches land in any order, and the last to finish is not
+    /// always the one that saw the most.
+    async fn save(&self, path: &Path, number: u64, snapshot: Vec<Saved>) {
+        let mut saved = self.saved.lock().await;
+        if number < *saved {
+            return;
+        }
+        let path = path.to_owned();
+        match tokio::task::spawn_blocking(move || save(&path, &snapshot)).await {
+            Ok(Ok(())) => *saved = number,
+            Ok(Err(err)) => tracing::warn!("Could not save the forecast cache: {err}"),
+            Err(err) => tracing::warn!("Saving the forecast cache failed: {err}"),
+        }
+    }
+}
+
+/// Writes the snapshot beside `path` and renames it into place, so a reader
+/// never sees half of one. The file written first is named for this process
+/// and this write, since both deploy slots save to the same place.
+fn save(path: &Path, snapshot: &[Saved]) -> std::io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let write = WRITES.fetch_add(1, Ordering::Relaxed);
+    let partial = path.with_extension(format!("{}.{write}.partial", std::process::id()));
+    std::fs::write(&partial, serde_json::to_vec(snapshot)?)?;
+    std::fs::rename(&partial, path)
+}
+
+/// A cache from a snapshot, keeping only entries that are still fresh and
+/// still decode.
+fn restore(bytes: &[u8]) -> Result<Cache, serde_json::Error> {
+    let saved: Vec<Saved> = serde_json::from_slice(bytes)?;
     let now = Instant::now();
-    cache.retain(|_, entry| now < entry.fresh_until);
-    if cache.len() >= CACHE_CAPACITY {
-        cache.clear();
-    }
-    cache.insert(
-        key,
-        CacheEntry {
-            forecast: forecast.clone(),
-            fresh_until: now + CACHE_TTL,
-        },
-    );
-
-    Ok(forecast)
-}
-
-/// Looks a place name up. Results are ordered by population, so the first is
-/// almost always the intended one and the rest become "did you mean" links.
-pub async fn geocode(query: &str) -> Result<Vec<Place>, Error> {
-    let parameters = query_string(&[
-        ("name", query),
-        ("count", "5"),
-        ("language", "en"),
-        ("format", "json"),
-    ]);
-
-    let body = CLIENT
-        .get(format!("{GEOCODING_URL}?{parameters}"))
-        .send()
-        .await?
-        .error_for_status()?
-        .bytes()
-        .await?;
-
-    let places: Vec<Place> = serde_json::from_slice::<ApiGeocoding>(&body)?
-        .results
+    let wall = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .into_iter()
-        .map(ApiPlace::into_place)
-        .collect();
+        .as_secs();
+    let mut cache = Cache::default();
+    for entry in saved {
+        let Some(left) = entry.fresh_until.checked_sub(wall).filter(|&left| left > 0) else {
+            continue;
+        };
+        let forecast = match serde_json::from_str::<ApiForecast>(&entry.body)
+            .map_err(Error::from)
+            .and_then(ApiForecast::into_forecast)
+        {
+            Ok(forecast) => forecast,
+            Err(err) => {
+                tracing::warn!("Dropping the saved forecast for {}: {err}", entry.key);
+                continue;
+            }
+        };
+        cache.insert(
+            entry.key,
+            Arc::new(forecast),
+            entry.body.into(),
+            // No forecast is fresh for longer than a fetch makes it, whatever
+            // a damaged snapshot claims.
+            now + Duration::from_secs(left).min(CACHE_TTL),
+        );
+    }
+    Ok(cache)
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, services::Error>> {
+        Box::pin(async move { Ok(OpenMeteo::forecast(self, latitude, longitude).await?) })
+    }
+
+    fn refresh(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, services::Error>> {
+        Box::pin(async move { Ok(OpenMeteo::refresh(self, latitude, longitude).await?) })
+    }
 
-    if places.is_empty() {
-        return Err(Error::NoSuchPlace(query.to_owned()));
+    fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, services::Error>> {
+        Box::pin(async move { Ok(OpenMeteo::geocode(self, query).await?) })
     }
-    Ok(places)
 }
 
 /// Great-circle distance in miles.
@@ -298,6 +720,7 @@ struct ApiForecast {
     latitude: f64,
     longitude: f64,
     elevation: f64,
+    utc_offset_seconds: i32,
     timezone_abbreviation: String,
     current: ApiCurrent,
     hourly: ApiHourly,
@@ -372,22 +795,38 @@ fn value_at(series: &[Option<f64>], index: usize) -> Option<f64> {
     series.get(index).copied().flatten()
 }
 
-/// Same, but for series where a gap is harmless. Radiation is zero at night and
-/// precipitation probability is zero when the model has nothing to say.
+/// Same, but for series where a gap is harmless: precipitation probability is
+/// zero when the model has nothing to say.
 fn value_or_zero(series: &[Option<f64>], index: usize) -> f64 {
     value_at(series, index).unwrap_or(0.0)
 }
 
 impl ApiForecast {
     fn into_forecast(self) -> Result<Forecast, Error> {
+        let (latitude, longitude, offset) =
+            (self.latitude, self.longitude, self.utc_offset_seconds);
         let hourly = self.hourly;
         let hours: Vec<Hour> = (0..hourly.time.len())
             .filter_map(|i| {
+                let time = hourly.time.get(i)?;
+                let sun = solar::over_hour_ending(time, offset, latitude, longitude)?;
+                let cloud_cover = value_or_zero(&hourly.cloud_cover, i);
+                // A gap in the radiation is not darkness: read as zero, it
+                // would put a sunny hour in the shade. Fill each missing
+                // component from the clear-sky model instead, and say so.
+                let published = [
+                    value_at(&hourly.direct_normal_irradiance, i),
+                    value_at(&hourly.direct_radiation, i),
+                    value_at(&hourly.diffuse_radiation, i),
+                ];
+                let estimate = solar::under_cloud(sun, cloud_cover);
+                let [direct_normal, direct_horizontal, diffuse] = published;
                 // An hour with no temperature, humidity or wind cannot be run
                 // through the comfort model at all, so drop it rather than
-                // invent a value. Everything else defaults to zero.
+                // invent a value.
                 Some(Hour {
-                    time: hourly.time.get(i)?.clone(),
+                    sun,
+                    time: time.clone(),
                     air: Temperature::from_celsius(value_at(&hourly.temperature_2m, i)?),
                     relative_humidity: value_at(&hourly.relative_humidity_2m, i)?,
                     wind: Speed::from_meters_per_second(value_at(&hourly.wind_speed_10m, i)?),
@@ -399,16 +838,19 @@ impl ApiForecast {
                     ),
                     precipitation_mm: value_or_zero(&hourly.precipitation, i),
                     precipitation_probability: value_or_zero(&hourly.precipitation_probability, i),
-                    cloud_cover: value_or_zero(&hourly.cloud_cover, i),
+                    cloud_cover,
                     // Absent means "not reported", not "no sun", so fall back
                     // to the cloud-cover estimate rather than to zero.
                     sunshine_seconds: value_at(&hourly.sunshine_duration, i).unwrap_or_else(|| {
-                        let clear = 1.0 - value_or_zero(&hourly.cloud_cover, i) / 100.0;
+                        let clear = 1.0 - cloud_cover / 100.0;
                         clear.clamp(0.0, 1.0) * 3600.0
                     }),
-                    direct_normal: value_or_zero(&hourly.direct_normal_irradiance, i),
-                    direct_horizontal: value_or_zero(&hourly.direct_radiation, i),
-                    diffuse: value_or_zero(&hourly.diffuse_radiation, i),
+                    direct_normal: direct_normal.unwrap_or(estimate.direct_normal),
+                    direct_horizontal: direct_horizontal.unwrap_or(estimate.direct_horizontal),
+                    diffuse: diffuse.unwrap_or(estimate.diffuse),
+                    // At night the estimate is the zero it replaces.
+                    radiation_estimated: sun.elevation_deg > 0.0
+                        && published.iter().any(Option::is_none),
                 })
             })
             .collect();
@@ -420,12 +862,23 @@ impl ApiForecast {
         let daily = self.daily;
         let days: Vec<Day> = (0..daily.time.len())
             .filter_map(|i| {
+                let date = daily.time.get(i)?;
+                // Open-Meteo sometimes leaves a day's sun times null; they are
+                // geometry, so work them out rather than lose the day.
+                let local = || solar::sunrise_sunset(date, offset, latitude, longitude);
+                let (sunrise, sunset) = match (
+                    daily.sunrise.get(i).cloned().flatten(),
+                    daily.sunset.get(i).cloned().flatten(),
+                ) {
+                    (Some(sunrise), Some(sunset)) => (sunrise, sunset),
+                    _ => local()?,
+                };
                 Some(Day {
-                    date: daily.time.get(i)?.clone(),
+                    date: date.clone(),
                     high: Temperature::from_celsius(value_at(&daily.temperature_2m_max, i)?),
                     low: Temperature::from_celsius(value_at(&daily.temperature_2m_min, i)?),
-                    sunrise: daily.sunrise.get(i)?.clone()?,
-                    sunset: daily.sunset.get(i)?.clone()?,
+                    sunrise,
+                    sunset,
                 })
             })
             .collect();
@@ -439,9 +892,14 @@ impl ApiForecast {
             grid_longitude: self.longitude,
             grid_elevation: self.elevation,
             timezone_abbreviation: self.timezone_abbreviation,
+            utc_offset_seconds: offset,
             current_time: self.current.time,
             hours,
             days,
+            provider: NAME,
+            estimated: Vec::new(),
+            fallback: None,
+            stale: false,
         })
     }
 }
@@ -492,6 +950,7 @@ mod tests {
         assert_eq!(forecast.current_time, "2026-08-02T13:15");
         assert_eq!(forecast.grid_elevation, 65.0);
         assert_eq!(forecast.timezone_abbreviation, "GMT-7");
+        assert_eq!(forecast.utc_offset_seconds, -25200);
         assert_eq!(forecast.hours.len(), 3);
         assert_eq!(forecast.days.len(), 2);
     }
@@ -564,13 +1023,44 @@ mod tests {
 
     #[test]
     fn treats_a_missing_optional_series_value_as_zero() {
+        let json = SAMPLE.replace("[0, 0, 12]", "[0, 0, null]");
+        let forecast = serde_json::from_str::<ApiForecast>(&json)
+            .unwrap()
+            .into_forecast()
+            .unwrap();
+        assert_eq!(forecast.hours[2].precipitation_probability, 0.0);
+        assert_eq!(forecast.hours[2].air.celsius(), 22.7);
+    }
+
+    #[test]
+    fn fills_missing_radiation_from_the_clear_sky_model() {
         let json = SAMPLE.replace("[722.0, 863.0, 700.5]", "[722.0, null, 700.5]");
         let forecast = serde_json::from_str::<ApiForecast>(&json)
             .unwrap()
             .into_forecast()
             .unwrap();
-        assert_eq!(forecast.hours[1].direct_horizontal, 0.0);
-        assert_eq!(forecast.hours[1].air.celsius(), 21.9);
+        let gap = &forecast.hours[1];
+        // A cloudless early afternoon: well up, and near what was published
+        // either side of it.
+        assert!((600.0..950.0).contains(&gap.direct_horizontal), "{gap:?}");
+        assert!(gap.radiation_estimated);
+        // The published components are kept as they were.
+        assert_eq!(gap.diffuse, 112.0);
+        assert_eq!(gap.direct_normal, 936.6);
+        assert!(!forecast.hours[0].radiation_estimated);
+    }
+
+    #[test]
+    fn does_not_flag_a_gap_in_the_dark() {
+        let json = SAMPLE
+            .replace("2026-08-01T12:00", "2026-08-01T02:00")
+            .replace("[722.0, 863.0, 700.5]", "[null, 863.0, 700.5]");
+        let forecast = serde_json::from_str::<ApiForecast>(&json)
+            .unwrap()
+            .into_forecast()
+            .unwrap();
+        assert_eq!(forecast.hours[0].direct_horizontal, 0.0);
+        assert!(!forecast.hours[0].radiation_estimated);
     }
 
     #[test]
@@ -583,10 +1073,36 @@ mod tests {
     }
 
     #[test]
-    fn rejects_a_response_with_no_usable_days() {
+    fn positions_the_sun_from_the_clock_and_the_grid_point() {
+        let forecast = sample();
+        // 12:00-13:00 PDT at the Inner Sunset: high, and just east of south.
+        let sun = forecast.hours[1].sun;
+        assert!((65.0..75.0).contains(&sun.elevation_deg), "{sun:?}");
+        assert!((140.0..180.0).contains(&sun.azimuth_deg), "{sun:?}");
+    }
+
+    #[test]
+    fn works_out_missing_sun_times_rather_than_dropping_the_day() {
         let json = SAMPLE.replace(
             r#""sunset": ["2026-08-01T20:18", "2026-08-02T20:17"]"#,
-            r#""sunset": [null, null]"#,
+            r#""sunset": ["2026-08-01T20:18", null]"#,
+        );
+        let forecast = serde_json::from_str::<ApiForecast>(&json)
+            .unwrap()
+            .into_forecast()
+            .unwrap();
+        assert_eq!(forecast.days.len(), 2);
+        let day = &forecast.days[1];
+        assert!(day.sunset.starts_with("2026-08-02T20:"), "{}", day.sunset);
+        // Both times come from the same source, never one of each.
+        assert_ne!(day.sunrise, "2026-08-02T06:14");
+    }
+
+    #[test]
+    fn rejects_a_response_with_no_usable_days() {
+        let json = SAMPLE.replace(
+            r#""temperature_2m_max": [22.0, 23.0]"#,
+            r#""temperature_2m_max": [null, null]"#,
         );
         let result = serde_json::from_str::<ApiForecast>(&json)
             .unwrap()
@@ -643,4 +1159,262 @@ mod tests {
             "No place matched \u{201c}qqqq\u{201d}."
         );
     }
+
+    #[tokio::test]
+    async fn replays_a_recorded_forecast() {
+        // Tests replay by default; see `fixtures`.
+        let client = OpenMeteo::new(Mode::Replay);
+        let forecast = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert_eq!(forecast.current_time, "2026-10-14T13:15");
+        assert_eq!(forecast.days.len(), 8);
+        assert_eq!(forecast.hours.len(), 8 * 24);
+        assert_eq!(forecast.timezone_abbreviation, "PDT");
+    }
+
+    #[tokio::test]
+    async fn replays_a_recorded_search() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let places = client.geocode("Oakland").await.unwrap();
+        assert_eq!(places.len(), 3);
+        assert_eq!(places[0].detail, "California, United States");
+        assert!(matches!(
+            client.geocode("nowhere at all").await,
+            Err(Error::NoSuchPlace(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_fetch() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let (first, second) = tokio::join!(
+            client.forecast(37.7946, -122.3999),
+            client.forecast(37.7946, -122.3999)
+        );
+        // Two fetches would have decoded two separate forecasts.
+        assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+        assert!(client.shared.cache.lock().unwrap().flights.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_fetch_that_dies_is_an_error_and_is_not_joined_again() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let key = cache_key(37.7946, -122.3999);
+        let (answer, flight) = watch::channel(None);
+        client
+            .shared
+            .cache
+            .lock()
+            .unwrap()
+            .flights
+            .insert(key.clone(), flight.clone());
+        let grounding = Grounding {
+            shared: client.shared.clone(),
+            key,
+            flight: flight.clone(),
+        };
+
+        // What a panicking fetch task leaves behind as it unwinds.
+        drop((answer, grounding));
+        assert!(matches!(land(flight).await, Err(Error::Abandoned)));
+        assert!(client.shared.cache.lock().unwrap().flights.is_empty());
+        assert!(client.forecast(37.7946, -122.3999).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_refresh_replaces_a_fresh_forecast() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let first = client.forecast(37.7946, -122.3999).await.unwrap();
+        let refreshed = client.refresh(37.7946, -122.3999).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &refreshed));
+        let next = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert!(Arc::ptr_eq(&next, &refreshed));
+    }
+
+    /// Makes the cached entry for `key` one second past its time.
+    fn expire(client: &OpenMeteo, key: &str) {
+        let mut cache = client.shared.cache.lock().unwrap();
+        let entry = cache.entries.get_mut(key).unwrap();
+        entry.fresh_until = Instant::now() - Duration::from_secs(1);
+    }
+
+    #[tokio::test]
+    async fn an_expired_forecast_is_served_while_a_new_one_is_fetched() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let first = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert!(!first.stale);
+        expire(&client, "37.7946,-122.3999");
+
+        let stale = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert!(stale.stale);
+        assert_eq!(stale.current_time, first.current_time);
+
+        let mut flight = client.shared.cache.lock().unwrap().flights["37.7946,-122.3999"].clone();
+        flight.wait_for(Option::is_some).await.unwrap();
+        let fresh = client.forecast(37.7946, -122.3999).await.unwrap();
+        assert!(!fresh.stale);
+        assert!(!Arc::ptr_eq(&fresh, &first));
+    }
+
+    #[tokio::test]
+    async fn an_outage_inside_the_stale_window_is_not_an_error() {
+        let client = OpenMeteo::new(Mode::Replay);
+        // Nothing is recorded for the open ocean, so every refresh fails.
+        client.shared.cache.lock().unwrap().insert(
+            cache_key(0.0, 0.0),
+            Arc::new(sample()),
+            SAMPLE.into(),
+            Instant::now() + CACHE_TTL,
+        );
+        expire(&client, "0.0000,0.0000");
+        for _ in 0..3 {
+            let forecast = client.forecast(0.0, 0.0).await.unwrap();
+            assert!(forecast.stale);
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn a_full_cache_evicts_the_least_recently_read() {
+        let mut cache = Cache::default();
+        let forecast = Arc::new(sample());
+        let fresh_until = Instant::now() + CACHE_TTL;
+        for i in 0..CACHE_CAPACITY {
+            cache.insert(
+                format!("key-{i}"),
+                forecast.clone(),
+                SAMPLE.into(),
+                fresh_until,
+            );
+        }
+        // Read the first entry again, leaving the second as the oldest.
+        cache.entries.get_mut("key-0").unwrap().used = Instant::now() + Duration::from_secs(1);
+        cache.entries.get_mut("key-1").unwrap().used = Instant::now() - Duration::from_secs(1);
+
+        cache.insert("one more".to_owned(), forecast, SAMPLE.into(), fresh_until);
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY);
+        assert!(cache.entries.contains_key("key-0"));
+        assert!(!cache.entries.contains_key("key-1"));
+        assert!(cache.entries.contains_key("one more"));
+    }
+
+    /// A snapshot file of this test's own, removed when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            Scratch(
+                std::env::temp_dir()
+                    .join(format!("forecast-cache-{name}-{}.json", std::process::id())),
+            )
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_snapshot_carries_the_cache_across_a_restart() {
+        let file = Scratch::new("restart");
+        let mut cache = Cache::default();
+        // Nothing is recorded for the open ocean, so only the snapshot can
+        // answer for it.
+        cache.insert(
+            cache_key(0.0, 0.0),
+            Arc::new(sample()),
+            SAMPLE.into(),
+            Instant::now() + CACHE_TTL,
+        );
+        save(&file.0, &cache.snapshot()).unwrap();
+
+        let client = OpenMeteo::with_snapshot(Mode::Replay, file.0.clone());
+        let forecast = client.forecast(0.0, 0.0).await.unwrap();
+        assert!(!forecast.stale);
+        assert_eq!(forecast.current_time, sample().current_time);
+        let fresh_until = client.shared.cache.lock().unwrap().entries["0.0000,0.0000"].fresh_until;
+        assert!(fresh_until > Instant::now() + CACHE_TTL - Duration::from_secs(60));
+    }
+
+    #[test]
+    fn a_snapshot_leaves_expired_forecasts_behind() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let saved = [
+            Saved {
+                key: "expired".to_owned(),
+                fresh_until: now - 1,
+                body: SAMPLE.to_owned(),
+            },
+            Saved {
+                key: "fresh".to_owned(),
+                fresh_until: now + 60,
+                body: SAMPLE.to_owned(),
+            },
+            Saved {
+                key: "garbled".to_owned(),
+                fresh_until: now + 60,
+                body: "{}".to_owned(),
+            },
+        ];
+        let cache = restore(&serde_json::to_vec(&saved[..]).unwrap()).unwrap();
+        assert_eq!(cache.entries.keys().collect::<Vec<_>>(), ["fresh"]);
+    }
+
+    #[test]
+    fn a_snapshot_cannot_keep_a_forecast_fresh_for_longer_than_a_fetch() {
+        let saved = [Saved {
+            key: "forever".to_owned(),
+            fresh_until: u64::MAX,
+            body: SAMPLE.to_owned(),
+        }];
+        let cache = restore(&serde_json::to_vec(&saved[..]).unwrap()).unwrap();
+        assert!(cache.entries["forever"].fresh_until <= Instant::now() + CACHE_TTL);
+    }
+
+    #[test]
+    fn a_corrupt_snapshot_is_ignored() {
+        let file = Scratch::new("corrupt");
+        std::fs::write(&file.0, "[{\"key\": \"0.0000,0.0000\", \"fresh_unt").unwrap();
+        let client = OpenMeteo::with_snapshot(Mode::Replay, file.0.clone());
+        assert!(client.shared.cache.lock().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn a_missing_snapshot_starts_empty() {
+        let file = Scratch::new("missing");
+        let client = OpenMeteo::with_snapshot(Mode::Replay, file.0.clone());
+        assert!(client.shared.cache.lock().unwrap().entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn an_older_snapshot_never_replaces_a_newer_one() {
+        let file = Scratch::new("order");
+        let client = OpenMeteo::with_snapshot(Mode::Replay, file.0.clone());
+        let saved = |key: &str| {
+            vec![Saved {
+                key: key.to_owned(),
+                fresh_until: u64::MAX,
+                body: SAMPLE.to_owned(),
+            }]
+        };
+        client.shared.save(&file.0, 2, saved("newer")).await;
+        client.shared.save(&file.0, 1, saved("older")).await;
+
+        let on_disk: Vec<Saved> = serde_json::from_slice(&std::fs::read(&file.0).unwrap()).unwrap();
+        assert_eq!(on_disk[0].key, "newer");
+    }
+
+    #[tokio::test]
+    async fn an_unrecorded_request_fails_like_an_unreachable_one() {
+        let client = OpenMeteo::new(Mode::Replay);
+        let err = client.forecast(0.0, 0.0).await.unwrap_err();
+        assert!(matches!(err, Error::NotRecorded(_)), "{err:?}");
+        assert!(err
+            .to_string()
+            .starts_with("No recorded Open-Meteo response"));
+    }
 }
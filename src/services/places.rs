@@ -0,0 +1,440 @@
+//! Place search, remembered.
+//!
+//! A place name means the same thing tomorrow as it does today, so each search
+//! is asked upstream once and its answer kept for [`SEARCH_TTL`], including an
+//! answer of nothing: a typeahead asks about a lot of half-typed names that
+//! match no place at all. Searches are keyed on their text with case and
+//! spacing folded, so `oakland` and ` Oakland ` are one search.
+//!
+//! Searches that a page was actually rendered for are also tallied, and the
+//! places they found are offered first as suggestions, before and sometimes
+//! instead of asking upstream. Suggestions alone are never tallied, or every
+//! prefix typed on the way to a name would count as a visit.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::services::open_meteo::{self, Forecast, Place};
+use crate::services::{BoxFuture, Error, WeatherProvider};
+
+/// How long an upstream answer to a search is reused.
+const SEARCH_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Upper bound on distinct searches held in memory.
+const SEARCH_CAPACITY: usize = 256;
+
+/// Upper bound on places remembered from past searches.
+const RECENT_CAPACITY: usize = 32;
+
+/// Most suggestions offered for one prefix.
+const SUGGESTIONS: usize = 5;
+
+/// Shorter prefixes are answered from past searches only. Upstream matches on
+/// one or two letters are mostly the world's largest cities, whoever is
+/// asking.
+const MIN_UPSTREAM_CHARS: usize = 3;
+
+/// One search's answer. Empty when nothing matched.
+struct Lookup {
+    places: Vec<Place>,
+    fresh_until: Instant,
+    /// When a request last read this entry, for eviction.
+    used: Instant,
+}
+
+/// A place a rendered search led to.
+struct Recent {
+    place: Place,
+    /// How many searches led here.
+    count: u32,
+    last: Instant,
+}
+
+#[derive(Default)]
+struct Memory {
+    searches: HashMap<String, Lookup>,
+    recent: Vec<Recent>,
+}
+
+impl Memory {
+    /// Stores an answer, dropping anything expired and then, if still full,
+    /// whichever search was read longest ago.
+    fn insert(&mut self, key: String, places: Vec<Place>) {
+        let now = Instant::now();
+        self.searches.retain(|_, lookup| now < lookup.fresh_until);
+        if self.searches.len() >= SEARCH_CAPACITY && !self.searches.contains_key(&key) {
+            if let Some(oldest) = self
+                .searches
+                .iter()
+                .min_by_key(|(_, lookup)| lookup.used)
+                .map(|(key, _)| key.clone())
+            {
+                self.searches.remove(&oldest);
+            }
+        }
+        self.searches.insert(
+            key,
+            Lookup {
+                places,
+                fresh_until: now + SEARCH_TTL,
+                used: now,
+            },
+        );
+    }
+
+    /// Counts one more search leading to `place`, making room by forgetting
+    /// the least searched-for place if need be.
+    fn record(&mut self, place: &Place) {
+        let now = Instant::now();
+        if let Some(recent) = self
+            .recent
+            .iter_mut()
+            .find(|recent| same(&recent.place, place))
+        {
+            recent.count += 1;
+            recent.last = now;
+            return;
+        }
+        if self.recent.len() >= RECENT_CAPACITY {
+            if let Some(least) = self
+                .recent
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, recent)| (recent.count, recent.last))
+                .map(|(index, _)| index)
+            {
+                self.recent.swap_remove(least);
+            }
+        }
+        self.recent.push(Recent {
+            place: place.clone(),
+            count: 1,
+            last: now,
+        });
+    }
+
+    /// Remembered places whose names start with `prefix`, already
+    /// normalised, most searched-for first.
+    fn recent(&self, prefix: &str) -> Vec<Place> {
+        let mut matches: Vec<&Recent> = self
+            .recent
+            .iter()
+            .filter(|recent| normalise(&recent.place.name).starts_with(prefix))
+            .collect();
+        matches.sort_by(|a, b| b.count.cmp(&a.count).then(b.last.cmp(&a.last)));
+        matches
+            .into_iter()
+            .take(SUGGESTIONS)
+            .map(|recent| recent.place.clone())
+            .collect()
+    }
+}
+
+/// Case and runs of whitespace folded, ends trimmed.
+pub(super) fn normalise(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Whether two results are the same place, whichever search found them.
+fn same(a: &Place, b: &Place) -> bool {
+    format!("{:.4},{:.4}", a.latitude, a.longitude)
+        == format!("{:.4},{:.4}", b.latitude, b.longitude)
+}
+
+/// Another provider's searches, cached and tallied. Forecasts pass straight
+/// through.
+pub struct Places {
+    inner: Arc<dyn WeatherProvider>,
+    memory: Mutex<Memory>,
+}
+
+impl Places {
+    pub fn new(inner: Arc<dyn WeatherProvider>) -> Self {
+        Places {
+            inner,
+            memory: Mutex::new(Memory::default()),
+        }
+    }
+
+    /// The places matching `query`, from memory if it has been asked before.
+    async fn lookup(&self, query: &str) -> Result<Vec<Place>, Error> {
+        let key = normalise(query);
+        let now = Instant::now();
+        let cached = {
+            let mut memory = self.memory.lock().expect("places mutex poisoned");
+            memory
+                .searches
+                .get_mut(&key)
+                .filter(|lookup| now < lookup.fresh_until)
+                .map(|lookup| {
+                    lookup.used = now;
+                    lookup.places.clone()
+                })
+        };
+        let places = match cached {
+            Some(places) => places,
+            None => {
+                let places = match self.inner.geocode(query).await {
+                    Ok(places) => places,
+                    Err(err) if err.no_such_place() => Vec::new(),
+                    Err(err) => return Err(err),
+                };
+                self.memory
+                    .lock()
+                    .expect("places mutex poisoned")
+                    .insert(key, places.clone());
+                places
+            }
+        };
+        if places.is_empty() {
+            return Err(open_meteo::Error::NoSuchPlace(query.trim().to_owned()).into());
+        }
+        Ok(places)
+    }
+
+    async fn geocode(&self, query: &str) -> Result<Vec<Place>, Error> {
+        let places = self.lookup(query).await?;
+        self.memory
+            .lock()
+            .expect("places mutex poisoned")
+            .record(&places[0]);
+        Ok(places)
+    }
+
+    async fn suggest(&self, query: &str) -> Result<Vec<Place>, Error> {
+        let prefix = normalise(query);
+        let mut suggestions = self
+            .memory
+            .lock()
+            .expect("places mutex poisoned")
+            .recent(&prefix);
+        if suggestions.len() >= SUGGESTIONS || prefix.chars().count() < MIN_UPSTREAM_CHARS {
+            return Ok(suggestions);
+        }
+        match self.lookup(query).await {
+            Ok(places) => {
+                for place in places {
+                    if !suggestions.iter().any(|known| same(known, &place)) {
+                        suggestions.push(place);
+                    }
+                }
+                suggestions.truncate(SUGGESTIONS);
+                Ok(suggestions)
+            }
+            Err(err) if err.no_such_place() => Ok(suggestions),
+            Err(err) if !suggestions.is_empty() => {
+                tracing::warn!("Suggesting only past searches for {query:?}: {err}");
+                Ok(suggestions)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl WeatherProvider for Places {
+    fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+        self.inner.forecast(latitude, longitude)
+    }
+
+    fn refresh(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+        self.inner.refresh(latitude, longitude)
+    }
+
+    fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+        Box::pin(Places::geocode(self, query))
+    }
+
+    fn nearest(&self, latitude: f64, longitude: f64) -> BoxFuture<'_, Option<Place>> {
+        self.inner.nearest(latitude, longitude)
+    }
+
+    fn suggest<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+        Box::pin(Places::suggest(self, query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fixtures::Mode;
+    use crate::services::open_meteo::OpenMeteo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Replayed Open-Meteo, counting searches that reach it.
+    struct Counting {
+        inner: OpenMeteo,
+        searches: AtomicUsize,
+    }
+
+    impl Counting {
+        fn new() -> Arc<Self> {
+            Arc::new(Counting {
+                inner: OpenMeteo::new(Mode::Replay),
+                searches: AtomicUsize::new(0),
+            })
+        }
+
+        fn searches(&self) -> usize {
+            self.searches.load(Ordering::SeqCst)
+        }
+    }
+
+    impl WeatherProvider for Counting {
+        fn forecast(
+            &self,
+            latitude: f64,
+            longitude: f64,
+        ) -> BoxFuture<'_, Result<Arc<Forecast>, Error>> {
+            WeatherProvider::forecast(&self.inner, latitude, longitude)
+        }
+
+        fn geocode<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<Place>, Error>> {
+            self.searches.fetch_add(1, Ordering::SeqCst);
+            WeatherProvider::geocode(&self.inner, query)
+        }
+    }
+
+    fn place(name: &str, latitude: f64) -> Place {
+        Place {
+            name: name.to_owned(),
+            detail: String::new(),
+            latitude,
+            longitude: 0.0,
+        }
+    }
+
+    #[test]
+    fn queries_fold_case_and_spacing() {
+        assert_eq!(normalise("  New   York\tCity "), "new york city");
+    }
+
+    #[tokio::test]
+    async fn a_search_goes_upstream_once() {
+        let upstream = Counting::new();
+        let places = Places::new(upstream.clone());
+        let first = places.geocode("Oakland").await.unwrap();
+        let again = places.geocode(" oakland ").await.unwrap();
+        assert_eq!(upstream.searches(), 1);
+        assert_eq!(first[0].detail, again[0].detail);
+    }
+
+    #[tokio::test]
+    async fn no_match_is_remembered_too() {
+        let upstream = Counting::new();
+        let places = Places::new(upstream.clone());
+        for _ in 0..2 {
+            let err = places.geocode("nowhere at all").await.unwrap_err();
+            assert!(err.no_such_place(), "{err:?}");
+        }
+        assert_eq!(upstream.searches(), 1);
+    }
+
+    #[tokio::test]
+    async fn an_expired_search_is_asked_again() {
+        let upstream = Counting::new();
+        let places = Places::new(upstream.clone());
+        places.geocode("Oakland").await.unwrap();
+        places
+            .memory
+            .lock()
+            .unwrap()
+            .searches
+            .get_mut("oakland")
+            .unwrap()
+            .fresh_until = Instant::now() - Duration::from_secs(1);
+        places.geocode("Oakland").await.unwrap();
+        assert_eq!(upstream.searches(), 2);
+    }
+
+    #[test]
+    fn a_full_cache_evicts_the_least_recently_read() {
+        let mut memory = Memory::default();
+        for i in 0..SEARCH_CAPACITY {
+            memory.insert(format!("search {i}"), Vec::new());
+        }
+        memory.searches.get_mut("search 0").unwrap().used = Instant::now() + Duration::from_secs(1);
+        memory.searches.get_mut("search 1").unwrap().used = Instant::now() - Duration::from_secs(1);
+
+        memory.insert("one more".to_owned(), Vec::new());
+        assert_eq!(memory.searches.len(), SEARCH_CAPACITY);
+        assert!(memory.searches.contains_key("search 0"));
+        assert!(!memory.searches.contains_key("search 1"));
+    }
+
+    #[tokio::test]
+    async fn past_searches_are_suggested_without_asking_upstream() {
+        let upstream = Counting::new();
+        let places = Places::new(upstream.clone());
+        places.geocode("Oakland").await.unwrap();
+
+        let suggestions = places.suggest("Oa").await.unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].detail, "California, United States");
+        assert!(places.suggest("Be").await.unwrap().is_empty());
+        assert_eq!(upstream.searches(), 1);
+    }
+
+    #[tokio::test]
+    async fn suggestions_put_past_searches_first_without_repeating_them() {
+        let upstream = Counting::new();
+        let places = Places::new(upstream.clone());
+        let oakland = places.geocode("Oakland").await.unwrap();
+        {
+            let mut memory = places.memory.lock().unwrap();
+            memory.record(&oakland[2]);
+            memory.record(&oakland[2]);
+        }
+
+        let suggestions = places.suggest("oak").await.unwrap();
+        assert_eq!(upstream.searches(), 2);
+        assert_eq!(suggestions[0].detail, "New Jersey, United States");
+        assert_eq!(suggestions[1].detail, "California, United States");
+        assert_eq!(
+            suggestions
+                .iter()
+                .filter(|place| same(place, &oakland[2]))
+                .count(),
+            1
+        );
+        assert!(suggestions.len() <= SUGGESTIONS);
+    }
+
+    #[tokio::test]
+    async fn suggestions_alone_are_not_tallied() {
+        let places = Places::new(Counting::new());
+        places.suggest("Oakland").await.unwrap();
+        assert!(places.memory.lock().unwrap().recent.is_empty());
+    }
+
+    #[test]
+    fn the_least_searched_for_place_is_forgotten_first() {
+        let mut memory = Memory::default();
+        for i in 0..RECENT_CAPACITY {
+            let place = place(&format!("Place {i}"), i as f64);
+            memory.record(&place);
+            if i != 3 {
+                memory.record(&place);
+            }
+        }
+        memory.record(&place("Newcomer", -1.0));
+        assert_eq!(memory.recent.len(), RECENT_CAPACITY);
+        assert!(!memory
+            .recent
+            .iter()
+            .any(|recent| recent.place.name == "Place 3"));
+        assert_eq!(memory.recent("newcomer").len(), 1);
+    }
+}
@@ -3,10 +3,10 @@
 use reqwest::header::{self, HeaderMap};
 use reqwest::StatusCode;
 use std::net::IpAddr;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+use crate::services::{BoxFuture, RelayRangeSource};
 
 const EGRESS_RANGES_URL: &str = "https://mask-api.icloud.com/egress-ip-ranges.csv";
 
@@ -50,62 +50,80 @@ struct CachedRanges {
     fresh_until: Instant,
 }
 
-static CACHE: LazyLock<Mutex<Option<CachedRanges>>> = LazyLock::new(|| Mutex::new(None));
-
-/// Checks if an IP address belongs to iCloud Private Relay.
-/// Returns the matching CSV line if found, or None if not a Private Relay IP.
-pub async fn get_private_relay_range(
-    ip_addr: &IpAddr,
-) -> Result<Option<EgressRange>, reqwest::Error> {
-    let ranges = egress_ranges().await?;
-    Ok(find_egress_range(&ranges, ip_addr).cloned())
+/// Apple's published ranges, held in memory between requests. A default one
+/// has not fetched anything yet.
+#[derive(Default)]
+pub struct PrivateRelay {
+    client: reqwest::Client,
+    cache: Mutex<Option<CachedRanges>>,
 }
 
-/// Fetches and parses Apple's egress ranges, honouring `Cache-Control` and `ETag`.
-///
-/// The file is ~12 MB and nearly 300k rows, and it used to be downloaded and
-/// parsed on every request. The parsed ranges are now held in memory for the
-/// `max-age` the response advertises, then revalidated with `If-None-Match`:
-/// Apple answers `304 Not Modified` when nothing changed, which refreshes the
-/// entry without transferring or reparsing anything.
-async fn egress_ranges() -> Result<Arc<[EgressRange]>, reqwest::Error> {
-    // Cloned out so the lock is never held across an await.
-    let cached = CACHE.lock().expect("cache mutex poisoned").clone();
-
-    if let Some(entry) = &cached {
-        if Instant::now() < entry.fresh_until {
-            return Ok(entry.ranges.clone());
-        }
-    }
-
-    let mut request = CLIENT.get(EGRESS_RANGES_URL);
-    if let Some(etag) = cached.as_ref().and_then(|entry| entry.etag.as_deref()) {
-        request = request.header(header::IF_NONE_MATCH, etag);
+impl PrivateRelay {
+    /// Checks if an IP address belongs to iCloud Private Relay.
+    /// Returns the matching CSV line if found, or None if not a Private Relay IP.
+    pub async fn get_private_relay_range(
+        &self,
+        ip_addr: &IpAddr,
+    ) -> Result<Option<EgressRange>, reqwest::Error> {
+        let ranges = self.egress_ranges().await?;
+        Ok(find_egress_range(&ranges, ip_addr).cloned())
     }
 
-    let response = request.send().await?.error_for_status()?;
-    let status = response.status();
-    let fresh_until = Instant::now() + max_age(response.headers());
-    // A 304 should repeat the ETag, but keep the old one if it does not.
-    let etag = response
-        .headers()
-        .get(header::ETAG)
-        .and_then(|value| value.to_str().ok())
-        .map(str::to_owned)
-        .or_else(|| cached.as_ref().and_then(|entry| entry.etag.clone()));
+    /// Fetches and parses Apple's egress ranges, honouring `Cache-Control` and `ETag`.
+    ///
+    /// The file is ~12 MB and nearly 300k rows, and it used to be downloaded and
+    /// parsed on every request. The parsed ranges are now held in memory for the
+    /// `max-age` the response advertises, then revalidated with `If-None-Match`:
+    /// Apple answers `304 Not Modified` when nothing changed, which refreshes the
+    /// entry without transferring or reparsing anything.
+    async fn egress_ranges(&self) -> Result<Arc<[EgressRange]>, reqwest::Error> {
+        // Cloned out so the lock is never held across an await.
+        let cached = self.cache.lock().expect("cache mutex poisoned").clone();
+
+        if let Some(entry) = &cached {
+            if Instant::now() < entry.fresh_until {
+                return Ok(entry.ranges.clone());
+            }
+        }
 
-    let ranges = match cached {
-        Some(entry) if status == StatusCode::NOT_MODIFIED => entry.ranges,
-        _ => Arc::from(parse_egress_ranges(&response.bytes().await?)),
-    };
+        let mut request = self.client.get(EGRESS_RANGES_URL);
+        if let Some(etag) = cached.as_ref().and_then(|entry| entry.etag.as_deref()) {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
 
-    *CACHE.lock().expect("cache mutex poisoned") = Some(CachedRanges {
-        ranges: ranges.clone(),
-        etag,
-        fresh_until,
-    });
+        let response = request.send().await?.error_for_status()?;
+        let status = response.status();
+        let fresh_until = Instant::now() + max_age(response.headers());
+        // A 304 should repeat the ETag, but keep the old one if it does not.
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+            .or_else(|| cached.as_ref().and_then(|entry| entry.etag.clone()));
+
+        let ranges = match cached {
+            Some(entry) if status == StatusCode::NOT_MODIFIED => entry.ranges,
+            _ => Arc::from(parse_egress_ranges(&response.bytes().await?)),
+        };
+
+        *self.cache.lock().expect("cache mutex poisoned") = Some(CachedRanges {
+            ranges: ranges.clone(),
+            etag,
+            fresh_until,
+        });
+
+        Ok(ranges)
+    }
+}
 
-    Ok(ranges)
+impl RelayRangeSource for PrivateRelay {
+    fn range_for<'a>(
+        &'a self,
+        ip_addr: &'a IpAddr,
+    ) -> BoxFuture<'a, Result<Option<EgressRange>, reqwest::Error>> {
+        Box::pin(self.get_private_relay_range(ip_addr))
+    }
 }
 
 /// Reads `max-age` out of `Cache-Control`, falling back to [`DEFAULT_MAX_AGE`].
@@ -0,0 +1,366 @@
+//! Where the sun is, from the clock and the map.
+//!
+//! The comfort model needs the sun's elevation for the projected area of a
+//! standing body. Recovering it from the two direct-beam components,
+//! `asin(direct_horizontal / direct_normal)`, costs nothing but says nothing
+//! when the beam is faint, is noise when both are rounded to a few W/m², and
+//! cannot give an azimuth at all. The position is pure geometry, so it is
+//! computed here instead.
+//!
+//! # The algorithm
+//!
+//! NOAA's solar calculator, after Meeus, *Astronomical Algorithms* (1991): the
+//! sun's apparent longitude and the obliquity of the ecliptic give declination
+//! and the equation of time, and those with the hour angle give elevation and
+//! azimuth. It is good to about a minute of time and a hundredth of a degree
+//! for any date this site will ever see, which is far inside what the
+//! radiation budget can tell apart. Elevation is geometric, with no refraction:
+//! refraction only matters within a degree of the horizon, where the beam has
+//! nothing left to give.
+//!
+//! Sunrise and sunset use the same terms with the conventional 90.833° zenith,
+//! which folds in refraction and the sun's radius, so they can be checked
+//! against the times Open-Meteo publishes.
+//!
+//! # Time
+//!
+//! Forecast times are naive local ISO strings, so every function here takes
+//! the UTC offset alongside them and leaves the calendar arithmetic to
+//! [`crate::clock`].
+//!
+//! # Radiation without a radiation forecast
+//!
+//! Where a provider publishes no radiation at all, or leaves an hour of it
+//! out, [`under_cloud`] estimates it from the sun's position and the cloud
+//! cover. The cloudless sky is
+//! Haurwitz (1945), which needs nothing but the zenith angle and does as well
+//! as models that want aerosol and water vapour too. Cloud attenuates it after
+//! Kasten and Czeplak (1980), who also give the share of what is left that
+//! arrives as diffuse light. Both were fitted to hourly means, which is what
+//! they stand in for here.
+
+use crate::clock;
+
+/// Minutes in a day.
+const DAY_MINUTES: f64 = 1440.0;
+
+/// Julian date of 1970-01-01T00:00Z.
+const UNIX_EPOCH_JULIAN: f64 = 2_440_587.5;
+
+/// Julian date of J2000.0, 2000-01-01T12:00 TT.
+const J2000: f64 = 2_451_545.0;
+
+/// Zenith angle at which the sun's upper limb touches the horizon, with
+/// standard refraction.
+const HORIZON_ZENITH_DEG: f64 = 90.833;
+
+/// The sun as seen from one place at one moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    /// Degrees above the horizon; negative at night.
+    pub elevation_deg: f64,
+    /// Degrees clockwise from true north.
+    pub azimuth_deg: f64,
+}
+
+/// Where the sun stood, on average, over the hour ending at `local`.
+///
+/// Open-Meteo's radiation figures are means over the preceding hour, so this
+/// is the position that goes with them: the middle of that hour, not its end.
+pub fn over_hour_ending(
+    local: &str,
+    utc_offset_seconds: i32,
+    latitude: f64,
+    longitude: f64,
+) -> Option<Position> {
+    let (days, minutes) = clock::parse_local(local)?;
+    let utc_minutes = minutes - 30.0 - f64::from(utc_offset_seconds) / 60.0;
+    Some(position_at(days, utc_minutes, latitude, longitude))
+}
+
+/// Local sunrise and sunset on `date` (`YYYY-MM-DD`), as `YYYY-MM-DDTHH:MM`.
+///
+/// `None` during polar day or night, when one or the other does not happen.
+pub fn sunrise_sunset(
+    date: &str,
+    utc_offset_seconds: i32,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(String, String)> {
+    let days = clock::parse_date(date)?;
+    let offset_minutes = f64::from(utc_offset_seconds) / 60.0;
+    // Evaluate the sun at local noon; over half a day it moves too little to
+    // shift either time by a minute.
+    let sun = Sun::at(julian_day(days, 720.0 - offset_minutes));
+
+    let latitude_rad = latitude.to_radians();
+    let cos_hour_angle = HORIZON_ZENITH_DEG.to_radians().cos()
+        / (latitude_rad.cos() * sun.declination.cos())
+        - latitude_rad.tan() * sun.declination.tan();
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let half_day_minutes = 4.0 * cos_hour_angle.acos().to_degrees();
+    let noon = 720.0 - 4.0 * longitude - sun.equation_of_time + offset_minutes;
+
+    Some((
+        clock::format_local(days, noon - half_day_minutes),
+        clock::format_local(days, noon + half_day_minutes),
+    ))
+}
+
+/// The three radiation components an [`crate::services::open_meteo::Hour`]
+/// carries, in W/m².
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Irradiance {
+    pub direct_normal: f64,
+    pub direct_horizontal: f64,
+    pub diffuse: f64,
+}
+
+/// Radiation with the sun at `sun` and `cloud_cover` percent of the sky
+/// covered. Zero with the sun below the horizon.
+pub fn under_cloud(sun: Position, cloud_cover: f64) -> Irradiance {
+    let cos_zenith = sun.elevation_deg.to_radians().sin();
+    if cos_zenith <= 0.0 {
+        return Irradiance::default();
+    }
+    let clear = 1098.0 * cos_zenith * (-0.057 / cos_zenith).exp();
+
+    let cloud = (cloud_cover / 100.0).clamp(0.0, 1.0);
+    let global = clear * (1.0 - 0.75 * cloud.powf(3.4));
+    let diffuse = global * (0.3 + 0.7 * cloud.powi(2));
+    let direct_horizontal = global - diffuse;
+    Irradiance {
+        // The beam share above is at most 70% of Haurwitz, which is itself
+        // proportional to cos z, so this stays bounded as the sun sets.
+        direct_normal: direct_horizontal / cos_zenith,
+        direct_horizontal,
+        diffuse,
+    }
+}
+
+/// The terms of the sun's apparent position that depend only on the instant.
+struct Sun {
+    /// Radians.
+    declination: f64,
+    /// Apparent solar time minus mean solar time, in minutes.
+    equation_of_time: f64,
+}
+
+impl Sun {
+    fn at(julian_day: f64) -> Sun {
+        let century = (julian_day - J2000) / 36_525.0;
+
+        let mean_longitude =
+            (280.466_46 + century * (36_000.769_83 + century * 0.000_303_2)).rem_euclid(360.0);
+        let mean_anomaly = 357.529_11 + century * (35_999.050_29 - 0.000_153_7 * century);
+        let eccentricity = 0.016_708_634 - century * (0.000_042_037 + 0.000_000_126_7 * century);
+
+        let anomaly = mean_anomaly.to_radians();
+        let centre = anomaly.sin() * (1.914_602 - century * (0.004_817 + 0.000_014 * century))
+            + (2.0 * anomaly).sin() * (0.019_993 - 0.000_101 * century)
+            + (3.0 * anomaly).sin() * 0.000_289;
+        let node = (125.04 - 1_934.136 * century).to_radians();
+        let apparent_longitude =
+            (mean_longitude + centre - 0.005_69 - 0.004_78 * node.sin()).to_radians();
+
+        let mean_obliquity = 23.0
+            + (26.0
+                + (21.448 - century * (46.815 + century * (0.000_59 - century * 0.001_813)))
+                    / 60.0)
+                / 60.0;
+        let obliquity = (mean_obliquity + 0.002_56 * node.cos()).to_radians();
+
+        let declination = (obliquity.sin() * apparent_longitude.sin()).asin();
+
+        let y = (obliquity / 2.0).tan().powi(2);
+        let longitude = mean_longitude.to_radians();
+        let equation_of_time = 4.0
+            * (y * (2.0 * longitude).sin() - 2.0 * eccentricity * anomaly.sin()
+                + 4.0 * eccentricity * y * anomaly.sin() * (2.0 * longitude).cos()
+                - 0.5 * y * y * (4.0 * longitude).sin()
+                - 1.25 * eccentricity * eccentricity * (2.0 * anomaly).sin())
+            .to_degrees();
+
+        Sun {
+            declination,
+            equation_of_time,
+        }
+    }
+}
+
+/// Position for a UTC instant given as a civil day count and minutes into it.
+fn position_at(days: i64, utc_minutes: f64, latitude: f64, longitude: f64) -> Position {
+    let sun = Sun::at(julian_day(days, utc_minutes));
+
+    let solar_minutes =
+        (utc_minutes + sun.equation_of_time + 4.0 * longitude).rem_euclid(DAY_MINUTES);
+    let hour_angle = (solar_minutes / 4.0 - 180.0).to_radians();
+
+    let latitude_rad = latitude.to_radians();
+    let cos_zenith = (latitude_rad.sin() * sun.declination.sin()
+        + latitude_rad.cos() * sun.declination.cos() * hour_angle.cos())
+    .clamp(-1.0, 1.0);
+    let zenith = cos_zenith.acos();
+
+    // Measured from north: the formula gives the angle from due north on the
+    // morning side, mirrored for the afternoon.
+    let denominator = latitude_rad.cos() * zenith.sin();
+    let from_north = if denominator.abs() < 1e-9 {
+        // At a pole or with the sun overhead, azimuth is undefined; call it
+        // south, where the sun is at noon from anywhere this site covers.
+        std::f64::consts::PI
+    } else {
+        ((latitude_rad.sin() * cos_zenith - sun.declination.sin()) / denominator)
+            .clamp(-1.0, 1.0)
+            .acos()
+    };
+    let azimuth_deg = if hour_angle > 0.0 {
+        from_north.to_degrees() + 180.0
+    } else {
+        540.0 - from_north.to_degrees()
+    }
+    .rem_euclid(360.0);
+
+    Position {
+        elevation_deg: 90.0 - zenith.to_degrees(),
+        azimuth_deg,
+    }
+}
+
+fn julian_day(days: i64, utc_minutes: f64) -> f64 {
+    UNIX_EPOCH_JULIAN + days as f64 + utc_minutes / DAY_MINUTES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pacific daylight time.
+    const PDT: i32 = -7 * 3600;
+
+    /// Where the sun is at local time `local`.
+    fn position(
+        local: &str,
+        utc_offset_seconds: i32,
+        latitude: f64,
+        longitude: f64,
+    ) -> Option<Position> {
+        let (days, minutes) = clock::parse_local(local)?;
+        let utc_minutes = minutes - f64::from(utc_offset_seconds) / 60.0;
+        Some(position_at(days, utc_minutes, latitude, longitude))
+    }
+
+    /// Minutes between two local timestamps on the same day.
+    fn minutes_apart(a: &str, b: &str) -> i64 {
+        let (_, a) = clock::parse_local(a).unwrap();
+        let (_, b) = clock::parse_local(b).unwrap();
+        (a - b).abs() as i64
+    }
+
+    #[test]
+    fn julian_day_matches_j2000() {
+        let days = clock::parse_date("2000-01-01").unwrap();
+        assert_eq!(julian_day(days, 720.0), J2000);
+    }
+
+    #[test]
+    fn equinox_noon_on_the_equator_is_nearly_overhead() {
+        // Greenwich, so local clock time is UTC and the equation of time is
+        // the only reason noon is not exactly 12:00.
+        let noon = position("2026-03-20T12:07", 0, 0.0, 0.0).unwrap();
+        assert!(noon.elevation_deg > 89.0, "{noon:?}");
+    }
+
+    #[test]
+    fn san_francisco_summer_afternoon() {
+        // Solar noon there is about 13:16 PDT, with the sun at 90° - 37.8° +
+        // 18° of declination; three quarters of an hour later it is about 68°
+        // up and a little west of south.
+        let sun = position("2026-08-01T14:00", PDT, 37.756, -122.446).unwrap();
+        assert!((sun.elevation_deg - 68.0).abs() < 1.0, "{sun:?}");
+        assert!((200.0..230.0).contains(&sun.azimuth_deg), "{sun:?}");
+    }
+
+    #[test]
+    fn morning_sun_is_east_and_evening_sun_is_west() {
+        let morning = position("2026-08-01T08:00", PDT, 37.756, -122.446).unwrap();
+        let evening = position("2026-08-01T18:00", PDT, 37.756, -122.446).unwrap();
+        assert!((45.0..135.0).contains(&morning.azimuth_deg), "{morning:?}");
+        assert!((225.0..315.0).contains(&evening.azimuth_deg), "{evening:?}");
+    }
+
+    #[test]
+    fn midnight_is_below_the_horizon() {
+        let night = position("2026-08-01T00:00", PDT, 37.756, -122.446).unwrap();
+        assert!(night.elevation_deg < -20.0, "{night:?}");
+    }
+
+    #[test]
+    fn the_hour_mean_sits_half_an_hour_earlier() {
+        let mean = over_hour_ending("2026-08-01T10:00", PDT, 37.756, -122.446).unwrap();
+        let half_past = position("2026-08-01T09:30", PDT, 37.756, -122.446).unwrap();
+        assert_eq!(mean, half_past);
+    }
+
+    #[test]
+    fn sunrise_and_sunset_agree_with_open_meteo() {
+        // Open-Meteo's own times for the Inner Sunset on 2026-08-01.
+        let (sunrise, sunset) = sunrise_sunset("2026-08-01", PDT, 37.756, -122.446).unwrap();
+        assert!(
+            minutes_apart(&sunrise, "2026-08-01T06:13") <= 5,
+            "{sunrise}"
+        );
+        assert!(minutes_apart(&sunset, "2026-08-01T20:18") <= 5, "{sunset}");
+    }
+
+    #[test]
+    fn polar_night_has_no_sunrise() {
+        assert_eq!(sunrise_sunset("2026-12-21", 0, 80.0, 15.0), None);
+        assert_eq!(sunrise_sunset("2026-06-21", 0, 80.0, 15.0), None);
+    }
+
+    #[test]
+    fn a_clear_noon_is_bright_and_mostly_beam() {
+        let sun = position("2026-08-01T13:16", PDT, 37.756, -122.446).unwrap();
+        let clear = under_cloud(sun, 0.0);
+        // Haurwitz at 70° up: about 970 W/m² on the ground.
+        let global = clear.direct_horizontal + clear.diffuse;
+        assert!((900.0..1050.0).contains(&global), "{clear:?}");
+        assert!(clear.direct_normal > clear.diffuse * 2.0, "{clear:?}");
+    }
+
+    #[test]
+    fn cloud_trades_beam_for_sky() {
+        let sun = position("2026-08-01T13:16", PDT, 37.756, -122.446).unwrap();
+        let clear = under_cloud(sun, 0.0);
+        let broken = under_cloud(sun, 50.0);
+        let overcast = under_cloud(sun, 100.0);
+        // Broken cloud scatters the beam into a brighter sky...
+        assert!(broken.direct_normal < clear.direct_normal, "{broken:?}");
+        assert!(broken.diffuse > clear.diffuse, "{broken:?}");
+        // ...and a full deck leaves a quarter of the light, none of it beam.
+        assert!(overcast.direct_normal < 1.0, "{overcast:?}");
+        let ratio = overcast.diffuse / (clear.direct_horizontal + clear.diffuse);
+        assert!((ratio - 0.25).abs() < 0.01, "{ratio}");
+    }
+
+    #[test]
+    fn no_sun_no_radiation() {
+        let night = position("2026-08-01T00:00", PDT, 37.756, -122.446).unwrap();
+        assert_eq!(under_cloud(night, 0.0), Irradiance::default());
+        // Just above the horizon the beam must not blow up.
+        let low = Position {
+            elevation_deg: 0.5,
+            azimuth_deg: 270.0,
+        };
+        assert!(under_cloud(low, 0.0).direct_normal < 100.0);
+    }
+
+    #[test]
+    fn rejects_timestamps_it_cannot_read() {
+        assert_eq!(position("yesterday", 0, 0.0, 0.0), None);
+        assert_eq!(sunrise_sunset("2026-13-01", 0, 0.0, 0.0), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! What every handler shares.
+
+use std::sync::Arc;
+
+use crate::config::get_config;
+use crate::services::fallback::Fallback;
+use crate::services::fixtures::Mode;
+use crate::services::gazetteer::{Gazetteer, WithGazetteer};
+use crate::services::nws::Nws;
+use crate::services::open_meteo::OpenMeteo;
+use crate::services::places::Places;
+use crate::services::private_relay::PrivateRelay;
+use crate::services::{RelayRangeSource, WeatherProvider};
+
+/// The services behind the routes, handed to the router once and cloned
+/// cheaply into each request.
+#[derive(Clone)]
+pub struct AppState {
+    pub weather: Arc<dyn WeatherProvider>,
+    pub relay: Arc<dyn RelayRangeSource>,
+}
+
+impl AppState {
+    /// The real services. Forecasts come from Open-Meteo, or from the
+    /// National Weather Service when it cannot answer, and searches are
+    /// cached in front of both, with the gazetteer beside them if one is
+    /// configured. Open-Meteo's cache starts from the last run's snapshot,
+    /// unless replaying fixtures, which should neither read a real snapshot
+    /// nor leave one behind.
+    pub fn production() -> Self {
+        let config = get_config();
+        let open_meteo = match config.upstream {
+            Mode::Replay => OpenMeteo::new(config.upstream),
+            _ => OpenMeteo::with_snapshot(config.upstream, config.forecast_cache_path.clone()),
+        };
+        let mut weather: Arc<dyn WeatherProvider> = Arc::new(Fallback::new(
+            Arc::new(open_meteo),
+            Arc::new(Nws::new(config.upstream, &config.website_domain)),
+        ));
+        if let Some(path) = &config.gazetteer_path {
+            match Gazetteer::read(path) {
+                Ok(gazetteer) => {
+                    tracing::info!("Loaded {} places from {}", gazetteer.len(), path.display());
+                    weather = Arc::new(WithGazetteer::new(
+                        weather,
+                        gazetteer,
+                        config.gazetteer_order,
+                    ));
+                }
+                Err(err) => tracing::error!("Searching without a gazetteer: {err}"),
+            }
+        }
+        AppState {
+            weather: Arc::new(Places::new(weather)),
+            relay: Arc::new(PrivateRelay::default()),
+        }
+    }
+}
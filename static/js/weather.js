@@ -48,7 +48,7 @@ function saveDefault(value) {
  * @param {string} param
  */
 function isSafeParam(param) {
-  return /^(loc=[a-z0-9-]+|lat=-?\d+(\.\d+)?&lon=-?\d+(\.\d+)?&name=[^&#/\\]*)$/.test(
+  return /^(loc=[a-z0-9-]+|lat=-?\d+(\.\d+)?&lon=-?\d+(\.\d+)?(&name=[^&#/\\]*)?)$/.test(
     param,
   );
 }
@@ -110,8 +110,92 @@ function setUpProbes() {
   });
 }
 
+/**
+ * Offers places as the search box is typed into.
+ *
+ * Suggestions fill the box's `<datalist>`, so the browser draws the list and
+ * handles the keyboard. Picking one and submitting goes straight to that
+ * place's coordinates rather than searching its label again. Without script
+ * the box is a plain search that submits `?q=`.
+ */
+function setUpSuggestions() {
+  const input = document.getElementById("q");
+  const list = document.getElementById("q-suggestions");
+  if (
+    !(input instanceof HTMLInputElement) ||
+    !(list instanceof HTMLDataListElement) ||
+    !(input.form instanceof HTMLFormElement)
+  ) {
+    return;
+  }
+  const form = input.form;
+
+  /**
+   * Every label offered so far, mapped to that place's query string. Never
+   * cleared, so a label picked just before a newer keystroke's answer lands
+   * still goes where it said.
+   * @type {Map<string, string>}
+   */
+  const offered = new Map();
+  /** @type {number | undefined} */
+  let pending;
+  /** @type {AbortController | undefined} */
+  let inFlight;
+
+  async function load() {
+    inFlight?.abort();
+    const query = input.value.trim();
+    if (query === "") {
+      list.replaceChildren();
+      return;
+    }
+    inFlight = new AbortController();
+    try {
+      const response = await fetch(
+        `/api/places?q=${encodeURIComponent(query)}`,
+        { signal: inFlight.signal },
+      );
+      if (!response.ok) {
+        return;
+      }
+      /** @type {{name: string, detail: string, query: string}[]} */
+      const places = await response.json();
+      const labels = places.map((place) => {
+        const label = place.detail
+          ? `${place.name}, ${place.detail}`
+          : place.name;
+        offered.set(label, place.query);
+        return label;
+      });
+      list.replaceChildren(...labels.map((label) => new Option(label)));
+    } catch {
+      // Aborted by a newer keystroke, or offline: the plain search still works.
+    }
+  }
+
+  input.addEventListener("input", () => {
+    window.clearTimeout(pending);
+    pending = window.setTimeout(load, 200);
+  });
+
+  form.addEventListener("submit", (event) => {
+    const param = offered.get(input.value);
+    if (param === undefined || !isSafeParam(param)) {
+      return;
+    }
+    event.preventDefault();
+    const activity = form.elements.namedItem("activity");
+    const carried =
+      activity instanceof HTMLInputElement
+        ? `&activity=${encodeURIComponent(activity.value)}`
+        : "";
+    window.location.assign(`/weather?${param}${carried}`);
+  });
+}
+
 document.addEventListener("DOMContentLoaded", () => {
   setUpProbes();
+  setUpSuggestions();
 
   const button = document.getElementById("weather-set-default");
   const note = document.getElementById("weather-default-note");